@@ -0,0 +1,18 @@
+extern crate shader_lang_spec_lib;
+use std::{error::Error, path::PathBuf};
+
+use shader_lang_spec_lib::*;
+
+/// writes the builtin conformance test shaders to the directory given as the first argument,
+/// e.g. `cargo run --example conformance -- target/wgsl-conformance`
+fn main() -> Result<(), Box<dyn Error>> {
+    let dir: PathBuf = std::env::args()
+        .nth(1)
+        .unwrap_or_else(|| "wgsl-conformance".to_string())
+        .into();
+    let wgsl_spec = wgsl::WgslSpec::from_download()?;
+    let suite = wgsl_spec.conformance_tests();
+    suite.write_to(&dir)?;
+    print!("{suite}");
+    Ok(())
+}
@@ -7,15 +7,19 @@ use shader_lang_spec_lib::*;
 
 fn main() -> Result<(), Box<dyn Error>> {
     let wgsl_spec = wgsl::WgslSpec::from_download()?;
+    print_builtins(&wgsl_spec);
+    Ok(())
+}
 
-    /// iterate over all overload table rows in the document
-    for f in wgsl_spec.overloads {
+/// works with the spec of any language
+fn print_builtins(spec: &dyn spec::ShaderLangSpec) {
+    /// iterate over every instance of every builtin function overload
+    for f in spec.builtin_functions() {
         println!("{}", f);
     }
-    Ok(())
 }
 
-const EXCLUDE_NAMES: &[&'static str] = &[
+const EXCLUDE_NAMES: &[&str] = &[
     "f",
     "f1",
     "f2",
@@ -0,0 +1,368 @@
+use std::{
+    collections::HashMap,
+    ops::Range,
+    path::{Path, PathBuf},
+};
+
+use lazy_static::lazy_static;
+use regex::Regex;
+
+use crate::{bikeshed::Heading, misc::normalize_whitespace};
+
+/// expands the `include::path[]` directives of the AsciiDoc document at `path` and keeps only the
+/// lines whose `ifdef::NAME[]` / `ifndef::NAME[]` conditions hold. `attributes` are the attributes set
+/// on the command line of the document build, e.g. `GLSL`. attributes set in the document with
+/// `:NAME:` lines are added to them
+pub fn read_document(path: &Path, attributes: &[&str]) -> std::io::Result<String> {
+    let mut attributes = attributes
+        .iter()
+        .map(|a| (a.to_string(), String::new()))
+        .collect();
+    let mut out = String::new();
+    preprocess(path, &mut attributes, &mut out)?;
+    Ok(out)
+}
+
+/// true if the attribute condition of a `ifdef::a,b[]` (any of) or `ifdef::a+b[]` (all of) holds
+fn is_defined(names: &str, attributes: &HashMap<String, String>) -> bool {
+    match names.contains('+') {
+        true => names.split('+').all(|n| attributes.contains_key(n)),
+        false => names.split(',').any(|n| attributes.contains_key(n)),
+    }
+}
+
+/// replaces the `{name}` references of attributes that are set, others are kept
+fn substitute_attributes(s: &str, attributes: &HashMap<String, String>) -> String {
+    lazy_static! {
+        static ref REFERENCE: Regex = Regex::new(r"\{([\w-]+)\}").unwrap();
+    }
+    let replaced = REFERENCE.replace_all(s, |c: &regex::Captures| match attributes.get(&c[1]) {
+        Some(value) => value.clone(),
+        None => c[0].to_string(),
+    });
+    replaced.into_owned()
+}
+
+fn preprocess(
+    path: &Path,
+    attributes: &mut HashMap<String, String>,
+    out: &mut String,
+) -> std::io::Result<()> {
+    lazy_static! {
+        static ref CONDITIONAL: Regex =
+            Regex::new(r"^(ifdef|ifndef|endif)::([^\[]*)\[(.*)\]\s*$").unwrap();
+        static ref INCLUDE: Regex = Regex::new(r"^include::([^\[]+)\[.*\]\s*$").unwrap();
+        static ref ATTRIBUTE: Regex = Regex::new(r"^:(!)?([\w-]+)(!)?:\s*(.*)$").unwrap();
+    }
+    let text = std::fs::read_to_string(path)?;
+    let dir = path.parent().map(Path::to_path_buf).unwrap_or_default();
+    // whether the lines of each enclosing conditional block are kept
+    let mut kept = vec![];
+    for line in text.lines() {
+        if let Some(c) = CONDITIONAL.captures(line) {
+            let holds = match &c[1] {
+                "endif" => {
+                    kept.pop();
+                    continue;
+                }
+                "ifdef" => is_defined(&c[2], attributes),
+                _ => !is_defined(&c[2], attributes),
+            };
+            // a single line conditional, e.g. `ifdef::GLSL[text]`
+            if !c[3].is_empty() {
+                if holds && !kept.contains(&false) {
+                    out.push_str(&c[3]);
+                    out.push('\n');
+                }
+                continue;
+            }
+            kept.push(holds);
+            continue;
+        }
+        if kept.contains(&false) {
+            continue;
+        }
+        if let Some(c) = INCLUDE.captures(line) {
+            let include: PathBuf = dir.join(substitute_attributes(&c[1], attributes));
+            preprocess(&include, attributes, out)?;
+            continue;
+        }
+        if let Some(c) = ATTRIBUTE.captures(line) {
+            match c.get(1).or(c.get(3)) {
+                Some(_) => attributes.remove(&c[2]),
+                None => attributes.insert(c[2].to_string(), c[4].trim().to_string()),
+            };
+        }
+        out.push_str(line);
+        out.push('\n');
+    }
+    Ok(())
+}
+
+/// the value of a `:name: value` attribute entry of the document, e.g. `:revnumber: 4.60.8`
+pub fn attribute<'a>(text: &'a str, name: &str) -> Option<&'a str> {
+    text.lines().find_map(|l| {
+        let value = l.strip_prefix(':')?.strip_prefix(name)?.strip_prefix(':')?;
+        Some(value.trim())
+    })
+}
+
+/// removes inline AsciiDoc markup from a piece of text, e.g.
+/// `genFType *abs*(genFType _x_)` becomes `genFType abs(genFType x)`
+pub fn strip_markup(s: &str) -> String {
+    lazy_static! {
+        static ref STRONG: Regex = Regex::new(r"\*\*?([^*\s](?:[^*\n]*?[^*\s])?)\*\*?").unwrap();
+        static ref EMPHASIS: Regex = Regex::new(r"\b__?([^_\n]+?)__?\b").unwrap();
+        static ref PASSTHROUGH: Regex =
+            Regex::new(r"\+\+?([^+\s](?:[^+\n]*?[^+\s])?)\+\+?").unwrap();
+        static ref XREF: Regex = Regex::new(r"<<[^,>]*,([^>]*)>>").unwrap();
+        static ref ANCHOR: Regex = Regex::new(r"\[\[[^\]]*\]\]").unwrap();
+        static ref HARD_BREAK: Regex = Regex::new(r" \+\n").unwrap();
+    }
+    let s = HARD_BREAK.replace_all(s, "\n");
+    let s = ANCHOR.replace_all(&s, "");
+    let s = XREF.replace_all(&s, "$1");
+    let s = STRONG.replace_all(&s, "$1");
+    let s = EMPHASIS.replace_all(&s, "$1");
+    let s = PASSTHROUGH.replace_all(&s, "$1");
+    s.replace("\\|", "|")
+        .replace("{cdot}", "·")
+        .replace("{times}", "×")
+        .replace("{leq}", "≤")
+        .replace("{geq}", "≥")
+        .replace("{pi}", "π")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&amp;", "&")
+}
+
+/// all section titles of an AsciiDoc document, e.g. `=== Angle and Trigonometry Functions`.
+/// a document title `= Title` has level 1
+pub fn headings(text: &str) -> Vec<Heading> {
+    lazy_static! {
+        static ref TITLE: Regex = Regex::new(r"(?m)^(={1,6})[ \t]+(\S.*?)[ \t]*$").unwrap();
+    }
+    TITLE
+        .captures_iter(text)
+        .map(|c| Heading {
+            offset: c.get(0).unwrap().start(),
+            level: c[1].len(),
+            title: normalize_whitespace(&strip_markup(&c[2])),
+        })
+        .collect()
+}
+
+/// the byte range of the section titled `title`, up to the next title of the same or a higher level
+pub fn section(text: &str, title: &str) -> Option<Range<usize>> {
+    let headings = headings(text);
+    let i = headings.iter().position(|h| h.title == title)?;
+    let level = headings[i].level;
+    let end = headings[i + 1..].iter().find(|h| h.level <= level);
+    Some(headings[i].offset..end.map_or(text.len(), |h| h.offset))
+}
+
+/// a row of a [`Table`], with the raw (still marked up) content of its cells
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TableRow<'a> {
+    /// byte offset of the first cell of the row in the document
+    pub offset: usize,
+    pub cells: Vec<&'a str>,
+}
+
+/// a `|===` table of an AsciiDoc document
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Table<'a> {
+    /// byte offset of the table delimiter in the document
+    pub offset: usize,
+    /// the cells of the first row if the table has the `header` option, with markup stripped
+    pub header: Vec<String>,
+    pub rows: Vec<TableRow<'a>>,
+}
+
+impl<'a> Table<'a> {
+    /// true if the header contains a column whose name contains `name`, ignoring case
+    pub fn has_column(&self, name: &str) -> bool {
+        let name = name.to_lowercase();
+        self.header.iter().any(|h| h.to_lowercase().contains(&name))
+    }
+}
+
+/// the number of columns given by a `cols="1,3"` or `cols="2*"` table attribute
+fn column_count(attributes: &str) -> Option<usize> {
+    lazy_static! {
+        static ref COLS: Regex = Regex::new(r#"cols="([^"]*)""#).unwrap();
+    }
+    let cols = COLS.captures(attributes)?;
+    let cols = cols.get(1)?.as_str();
+    match cols.split_once('*') {
+        Some((n, _)) if !cols.contains(',') => n.trim().parse().ok(),
+        _ => Some(cols.split(',').count()),
+    }
+}
+
+/// the cells of a table body, each starting after its unescaped `|` separator
+fn cells(body: &str) -> Vec<(usize, &str)> {
+    let bytes = body.as_bytes();
+    let mut starts = vec![];
+    for (i, _) in body.match_indices('|') {
+        if i > 0 && bytes[i - 1] == b'\\' {
+            continue;
+        }
+        starts.push(i);
+    }
+    starts.push(body.len());
+    starts
+        .windows(2)
+        .map(|w| (w[0] + 1, body[w[0] + 1..w[1]].trim()))
+        .collect()
+}
+
+/// extracts all `|===` tables of an AsciiDoc document. rows are made of as many cells as the table has
+/// columns, taken from the `cols` attribute or else from the cells on the first line of the table
+pub fn tables(text: &str) -> Vec<Table<'_>> {
+    lazy_static! {
+        static ref DELIMITER: Regex = Regex::new(r"(?m)^\|={3,}[ \t]*$").unwrap();
+    }
+    let delimiters: Vec<_> = DELIMITER.find_iter(text).collect();
+    let mut tables = vec![];
+    for pair in delimiters.chunks_exact(2) {
+        let (open, close) = (pair[0], pair[1]);
+        let before = &text[..open.start()];
+        let attributes = before
+            .trim_end()
+            .lines()
+            .last()
+            .filter(|l| l.starts_with('['))
+            .unwrap_or_default();
+        let body = &text[open.end()..close.start()];
+        let first_line = body.trim_start().lines().next().unwrap_or_default();
+        let columns = column_count(attributes)
+            .unwrap_or_else(|| cells(first_line).len())
+            .max(1);
+
+        let cells = cells(&body[body.find('|').unwrap_or(body.len())..]);
+        let body_offset = open.end() + body.find('|').unwrap_or(body.len());
+        let mut rows: Vec<TableRow> = cells
+            .chunks(columns)
+            .map(|row| TableRow {
+                offset: body_offset + row[0].0 - 1,
+                cells: row.iter().map(|(_, c)| *c).collect(),
+            })
+            .collect();
+        let header = match attributes.contains("header") && !rows.is_empty() {
+            true => rows
+                .remove(0)
+                .cells
+                .iter()
+                .map(|c| strip_markup(c))
+                .collect(),
+            false => vec![],
+        };
+        tables.push(Table {
+            offset: open.start(),
+            header,
+            rows,
+        });
+    }
+    tables
+}
+
+/// a `----` delimited listing, e.g. a `[source,glsl]` block
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceBlock<'a> {
+    /// byte offset of the content in the document
+    pub offset: usize,
+    /// the language of a `[source,lang]` block
+    pub language: Option<&'a str>,
+    pub content: &'a str,
+}
+
+/// extracts all listing blocks of an AsciiDoc document
+pub fn source_blocks(text: &str) -> Vec<SourceBlock<'_>> {
+    lazy_static! {
+        static ref DELIMITER: Regex = Regex::new(r"(?m)^-{4,}[ \t]*$").unwrap();
+        static ref SOURCE: Regex = Regex::new(r"^\[source,\s*([\w+-]+)").unwrap();
+    }
+    let delimiters: Vec<_> = DELIMITER.find_iter(text).collect();
+    delimiters
+        .chunks_exact(2)
+        .map(|pair| {
+            let before = text[..pair[0].start()].trim_end().lines().last();
+            let language = before
+                .and_then(|l| SOURCE.captures(l))
+                .map(|c| c.get(1).unwrap().as_str());
+            let offset = pair[0].end() + 1;
+            SourceBlock {
+                offset,
+                language,
+                content: &text[offset.min(pair[1].start())..pair[1].start()],
+            }
+        })
+        .collect()
+}
+
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_asciidoc() {
+        let dir = std::env::temp_dir().join(format!("asciidoc-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("chapters")).unwrap();
+        let main = ":revnumber: 4.60.8\n= Spec\ninclude::chapters/a.adoc[]\nifdef::ESSL[]\nessl\nendif::ESSL[]\nifndef::ESSL[glsl only]\n";
+        std::fs::write(dir.join("spec.adoc"), main).unwrap();
+        std::fs::write(
+            dir.join("chapters/a.adoc"),
+            "== A\nifdef::GLSL,ESSL[]\nboth\nendif::[]\n",
+        )
+        .unwrap();
+        let text = read_document(&dir.join("spec.adoc"), &["GLSL"]).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(text, ":revnumber: 4.60.8\n= Spec\n== A\nboth\nglsl only\n");
+        assert_eq!(attribute(&text, "revnumber"), Some("4.60.8"));
+        let titles: Vec<_> = headings(&text)
+            .into_iter()
+            .map(|h| (h.level, h.title))
+            .collect();
+        assert_eq!(titles, [(1, "Spec".to_string()), (2, "A".to_string())]);
+        assert_eq!(section(&text, "A"), Some(26..text.len()));
+
+        assert_eq!(
+            strip_markup("genFType *abs*(genFType _x_) +\n  {pi} {cdot} _x_"),
+            "genFType abs(genFType x)\n  π · x"
+        );
+
+        let text = r#"
+[options="header"]
+|====
+| Syntax | Description
+| genFType *abs*(genFType _x_) +
+  genIType *abs*(genIType _x_)
+  | Returns _x_ if _x_ {geq} 0.
+| float *length*(genFType _x_) | Returns the length of vector _x_, i.e., sqrt(x[0]\|x[1]).
+|====
+
+[source,glsl]
+----
+in int gl_VertexID;
+----
+"#;
+        let tables = tables(text);
+        assert_eq!(tables.len(), 1);
+        assert_eq!(tables[0].header, ["Syntax", "Description"]);
+        assert!(tables[0].has_column("syntax"));
+        assert_eq!(tables[0].rows.len(), 2);
+        assert_eq!(
+            tables[0].rows[0].cells,
+            [
+                "genFType *abs*(genFType _x_) +\n  genIType *abs*(genIType _x_)",
+                "Returns _x_ if _x_ {geq} 0."
+            ]
+        );
+        assert_eq!(&text[tables[0].rows[1].offset..][..7], "| float");
+
+        let blocks = source_blocks(text);
+        assert_eq!(blocks.len(), 1);
+        assert_eq!(blocks[0].language, Some("glsl"));
+        assert_eq!(blocks[0].content, "in int gl_VertexID;\n");
+    }
+}
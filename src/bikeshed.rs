@@ -0,0 +1,307 @@
+use lazy_static::lazy_static;
+use regex::Regex;
+
+use crate::{misc::normalize_whitespace, nom_prelude::*};
+
+/// collects the `Text Macro: NAME replacement` lines of the metadata block of a `.bs` document
+pub fn text_macros(text: &str) -> Vec<(String, String)> {
+    lazy_static! {
+        static ref MACRO: Regex =
+            Regex::new(r"(?m)^\s*Text Macro:\s*([A-Z0-9_]+)\s+(.*)$").unwrap();
+    }
+    MACRO
+        .captures_iter(text)
+        .map(|c| (c[1].to_string(), c[2].trim_end().to_string()))
+        .collect()
+}
+
+/// replaces every `[NAME]` with the text of the `Text Macro: NAME ...` declared in the document
+pub fn expand_text_macros(text: &str) -> String {
+    let mut text = text.to_string();
+    for (name, replacement) in text_macros(&text) {
+        text = text.replace(&format!("[{name}]"), &replacement);
+    }
+    text
+}
+
+/// the value of a `Key: value` line of the metadata block of a `.bs` document, e.g. `Date: 2023-04-01`
+pub fn metadata<'a>(text: &'a str, key: &str) -> Option<&'a str> {
+    let metadata = &text[..text.find("</pre>").unwrap_or(text.len())];
+    metadata.lines().find_map(|l| {
+        let (k, value) = l.trim().split_once(':')?;
+        (k.trim() == key).then(|| value.trim())
+    })
+}
+
+/// removes inline bikeshed/html markup from a piece of text, e.g.
+/// `` |e1| `+` [=type/abstract|AbstractInt=] vec|N|&lt;|S|&gt; `` becomes `e1 + AbstractInt vecN<S>`.
+/// superscripts are written with a `^`, so `2<sup>-11</sup>` becomes `2^-11`
+pub fn strip_markup(s: &str) -> String {
+    lazy_static! {
+        static ref VAR: Regex = Regex::new(r"<var[^>]*>([^<]*)</var>").unwrap();
+        static ref SUP: Regex = Regex::new(r"<sup>([^<]*)</sup>").unwrap();
+        static ref BR: Regex = Regex::new(r"<br\s*/?>").unwrap();
+        // only known html tags, so that e.g. the `<T>` in `vec3<T>` is kept
+        static ref TAG: Regex = Regex::new(
+            r"</?(?:a|abbr|b|code|dfn|div|em|i|li|ol|p|pre|small|span|strong|sub|sup|td|th|tr|ul|xmp)\b[^>]*>"
+        )
+        .unwrap();
+        static ref AUTOLINK: Regex = Regex::new(r"\[=(?:[^=\]|]*\|)?([^=\]]*)=\]").unwrap();
+        static ref IDL_LINK: Regex = Regex::new(r"\{\{(?:[^}|]*\|)?([^}]*)\}\}").unwrap();
+        static ref BAR_VAR: Regex = Regex::new(r"\|(\w+)\|").unwrap();
+    }
+    let s = VAR.replace_all(s, "$1");
+    let s = SUP.replace_all(&s, "^$1");
+    let s = BR.replace_all(&s, "\n");
+    let s = TAG.replace_all(&s, "");
+    let s = AUTOLINK.replace_all(&s, "$1");
+    let s = IDL_LINK.replace_all(&s, "$1");
+    let s = BAR_VAR.replace_all(&s, "$1");
+    s.replace('`', "")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&le;", "≤")
+        .replace("&ge;", "≥")
+        .replace("&times;", "×")
+        .replace("&minus;", "-")
+        .replace("&pi;", "π")
+        .replace("&infin;", "∞")
+        .replace("&amp;", "&")
+}
+
+/// a section heading, either markdown like `## Numeric Built-in Functions ## {#numeric-builtin-functions}`
+/// or html like `<h3 id=...>Numeric Built-in Functions</h3>`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Heading {
+    /// byte offset of the heading in the document
+    pub offset: usize,
+    /// 1 for `#` or `<h1>`
+    pub level: usize,
+    pub title: String,
+}
+
+/// all headings of a bikeshed document, in document order
+pub fn headings(text: &str) -> Vec<Heading> {
+    lazy_static! {
+        static ref MARKDOWN: Regex =
+            Regex::new(r"(?m)^(#{1,6})[ \t]+(.*?)[ \t]*#*[ \t]*(?:\{#[^}]*\})?[ \t]*$").unwrap();
+        static ref HTML: Regex = Regex::new(r"(?s)<h([1-6])\b[^>]*>(.*?)</h[1-6]>").unwrap();
+    }
+    let heading = |c: regex::Captures, level: usize| Heading {
+        offset: c.get(0).unwrap().start(),
+        level,
+        title: normalize_whitespace(&strip_markup(&c[2])),
+    };
+    let mut headings: Vec<Heading> = MARKDOWN
+        .captures_iter(text)
+        .map(|c| {
+            let level = c[1].len();
+            heading(c, level)
+        })
+        .chain(HTML.captures_iter(text).map(|c| {
+            let level = c[1].parse().unwrap();
+            heading(c, level)
+        }))
+        .collect();
+    headings.sort_by_key(|h| h.offset);
+    headings
+}
+
+/// the innermost heading before `offset`
+pub fn section_of(headings: &[Heading], offset: usize) -> Option<&Heading> {
+    let i = headings.partition_point(|h| h.offset <= offset);
+    i.checked_sub(1).map(|i| &headings[i])
+}
+
+/// parses an opening table cell tag, e.g. `<td>` or `<td class="nowrap">`
+pub fn td_open(s: &str) -> NomResult<&str, &str> {
+    recognize(tuple((tag("<td"), take_till(|c: char| c == '>'), tag(">"))))(s)
+}
+
+/// parses the raw content of a table cell up to the start of the next cell, row or the table end
+pub fn cell_content(s: &str) -> NomResult<&str, &str> {
+    alt((
+        take_until_matches(alt((
+            tag("<td"),
+            tag("</td>"),
+            tag("<tr"),
+            tag("</tr>"),
+            tag("</table>"),
+        ))),
+        nom::combinator::rest,
+    ))(s)
+}
+
+/// a row of a [`Table`], with the raw (still marked up) content of its cells
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TableRow<'a> {
+    /// byte offset of the row in the document
+    pub offset: usize,
+    /// the `algorithm="..."` attribute of the `<tr>`, if any
+    pub algorithm: Option<&'a str>,
+    pub raw: &'a str,
+    pub cells: Vec<&'a str>,
+}
+
+/// a `<table>` of a bikeshed document
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Table<'a> {
+    /// byte offset of the table in the document
+    pub offset: usize,
+    pub caption: Option<String>,
+    /// the `<th>` cells, with markup stripped
+    pub header: Vec<String>,
+    pub rows: Vec<TableRow<'a>>,
+}
+
+impl<'a> Table<'a> {
+    /// true if the header contains a column whose name contains `name`, ignoring case
+    pub fn has_column(&self, name: &str) -> bool {
+        let name = name.to_lowercase();
+        self.header.iter().any(|h| h.to_lowercase().contains(&name))
+    }
+}
+
+/// splits `s` at every occurrence of `pat`, each piece (except the first) starting with `pat`
+fn split_before<'a>(s: &'a str, pat: &str) -> Vec<(usize, &'a str)> {
+    let mut starts = vec![0];
+    starts.extend(s.match_indices(pat).map(|(i, _)| i));
+    starts.push(s.len());
+    starts.windows(2).map(|w| (w[0], &s[w[0]..w[1]])).collect()
+}
+
+/// the content of an element that starts with `open` (e.g. `<td`), after the end of the opening tag
+fn element_content<'a>(s: &'a str, close: &[&str]) -> &'a str {
+    let s = s.find('>').map(|i| &s[i + 1..]).unwrap_or_default();
+    let end = close
+        .iter()
+        .filter_map(|c| s.find(c))
+        .min()
+        .unwrap_or(s.len());
+    &s[..end]
+}
+
+/// extracts all tables of a bikeshed document. rows and cells do not need closing tags.
+pub fn tables(text: &str) -> Vec<Table<'_>> {
+    let mut tables = vec![];
+    for (offset, _) in text.match_indices("<table") {
+        let table = &text[offset..];
+        let table = &table[..table.find("</table>").unwrap_or(table.len())];
+
+        let caption = table.find("<caption").map(|i| {
+            normalize_whitespace(&strip_markup(element_content(&table[i..], &["</caption>"])))
+        });
+
+        let mut header = vec![];
+        let mut rows = vec![];
+        for (row_offset, row) in split_before(table, "<tr").into_iter().skip(1) {
+            if !row.contains("<td") {
+                let cells = split_before(row, "<th").into_iter().skip(1);
+                let close = ["<th", "</th>", "</tr>", "</thead>"];
+                header.extend(
+                    cells.map(|(_, c)| {
+                        normalize_whitespace(&strip_markup(element_content(c, &close)))
+                    }),
+                );
+                continue;
+            }
+            let row = &row[..row.find("</tbody>").unwrap_or(row.len())];
+            let algorithm = row
+                .strip_prefix("<tr algorithm=\"")
+                .and_then(|r| r.find('"').map(|i| &r[..i]));
+            let close = ["<td", "</td>", "</tr>"];
+            let cells = split_before(row, "<td").into_iter().skip(1);
+            rows.push(TableRow {
+                offset: offset + row_offset,
+                algorithm,
+                raw: row,
+                cells: cells.map(|(_, c)| element_content(c, &close)).collect(),
+            });
+        }
+        tables.push(Table {
+            offset,
+            caption,
+            header,
+            rows,
+        });
+    }
+    tables
+}
+
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_markup() {
+        let bs = "Text Macro: ALLFLOATINGDECL |S| is AbstractFloat<br>|T| is |S|\n\n<td>[ALLFLOATINGDECL]";
+        assert_eq!(
+            expand_text_macros(bs).lines().last(),
+            Some("<td>|S| is AbstractFloat<br>|T| is |S|")
+        );
+        assert_eq!(
+            metadata(
+                "<pre class='metadata'>\nDate: 2023-04-01\n</pre>\nDate: x",
+                "Date"
+            ),
+            Some("2023-04-01")
+        );
+
+        let s = "`-`<var ignore>e</var>`:` [=type/abstract|AbstractInt=] vec|N|&lt;[=f32=]&gt;";
+        assert_eq!(strip_markup(s), "-e: AbstractInt vecN<f32>");
+        assert_eq!(strip_markup("2<sup>-11</sup> &le; &pi;"), "2^-11 ≤ π");
+
+        let s = r#"<td class="nowrap">|e1| + |e2|<td>Addition"#;
+        let (s, _) = td_open(s).unwrap();
+        assert_eq!(cell_content(s), Ok(("<td>Addition", "|e1| + |e2|")));
+    }
+
+    #[test]
+    fn test_tables() {
+        let bs = r#"text
+<table class='data'>
+  <caption>Logical expressions</caption>
+  <thead>
+    <tr><th>Precondition<th>Conclusion<th>Notes
+  </thead>
+  <tr algorithm="not"><td>|e|: bool<td>`!`|e|`:` bool<td>Negation.
+  <tr><td>a</td><td>b</td></tr>
+</table>"#;
+        let tables = tables(bs);
+        assert_eq!(tables.len(), 1);
+        let table = &tables[0];
+        assert_eq!(table.offset, 5);
+        assert_eq!(table.caption.as_deref(), Some("Logical expressions"));
+        assert_eq!(table.header, vec!["Precondition", "Conclusion", "Notes"]);
+        assert!(table.has_column("conclusion"));
+        assert_eq!(table.rows.len(), 2);
+        assert_eq!(table.rows[0].algorithm, Some("not"));
+        assert_eq!(
+            table.rows[0].cells,
+            vec!["|e|: bool", "`!`|e|`:` bool", "Negation.\n  "]
+        );
+        assert_eq!(table.rows[1].cells, vec!["a", "b"]);
+        assert_eq!(&bs[table.rows[1].offset..][..7], "<tr><td");
+    }
+
+    #[test]
+    fn test_headings() {
+        let bs = "# WGSL #\n\n## Numeric Built-in Functions ## {#numeric-builtin-functions}\ntext\n<h3 id=x>The `abs` function</h3>\n<tr>";
+        let headings = headings(bs);
+        let titles: Vec<_> = headings
+            .iter()
+            .map(|h| (h.level, h.title.as_str()))
+            .collect();
+        assert_eq!(
+            titles,
+            vec![
+                (1, "WGSL"),
+                (2, "Numeric Built-in Functions"),
+                (3, "The abs function")
+            ]
+        );
+        let row = bs.find("<tr>").unwrap();
+        assert_eq!(section_of(&headings, row).unwrap().level, 3);
+        assert_eq!(section_of(&headings, 5).unwrap().title, "WGSL");
+        assert!(section_of(&headings[1..], 0).is_none());
+    }
+}
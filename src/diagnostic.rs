@@ -0,0 +1,286 @@
+use std::fmt::Display;
+
+use nom::error::{ErrorKind, VerboseErrorKind};
+
+use crate::{nom_prelude::*, span::Span};
+
+/// tags of the spec documents, suggested if the text at an error is a near miss, e.g. `<tr algoritm="`
+pub const KNOWN_TAGS: &[&str] = &[
+    "<tr algorithm=\"",
+    "<xmp highlight=rust>",
+    "</xmp>",
+    "<var ignore>",
+    "</var>",
+    "<table",
+    "</table>",
+    "<td>",
+    "<th>",
+    "<br>",
+];
+
+/// the number of single character insertions, deletions or substitutions that turn `a` into `b`
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = diagonal + (ca != *cb) as usize;
+            diagonal = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(row[j + 1] + 1);
+        }
+    }
+    row[b.len()]
+}
+
+/// a known tag which differs from the text at the start of `s` by one or two characters,
+/// if `s` does not start with a known tag,
+/// and the number of characters of `s` that make up the near miss
+pub fn near_miss(s: &str) -> Option<(&'static str, usize)> {
+    if KNOWN_TAGS.iter().any(|tag| s.starts_with(tag)) {
+        return None;
+    }
+    let ends: Vec<usize> = s.char_indices().map(|(i, _)| i).chain([s.len()]).collect();
+    KNOWN_TAGS
+        .iter()
+        .filter_map(|&tag| {
+            // the text may have a character more or less than the tag
+            let chars = tag.chars().count();
+            let max = (chars + 1).min(ends.len() - 1);
+            let (distance, len) = (0..=max)
+                .map(|n| (edit_distance(&s[..ends[n]], tag), n))
+                .min()?;
+            // short tags are too similar to each other to allow two differences
+            let allowed = if chars > 5 { 2 } else { 1 };
+            (distance <= allowed).then_some((distance, tag, len))
+        })
+        .min_by_key(|(distance, _, _)| *distance)
+        .map(|(_, tag, len)| (tag, len))
+}
+
+pub fn did_you_mean(s: &str) -> Option<&'static str> {
+    near_miss(s).map(|(tag, _)| tag)
+}
+
+/// a position in a source text, with the line it is on
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Location {
+    /// starting at 1
+    pub line: usize,
+    /// in characters, starting at 1
+    pub column: usize,
+    pub source_line: String,
+    /// the number of characters to underline
+    pub len: usize,
+}
+
+impl Location {
+    /// the location of `part`, which must be a slice of `text`
+    pub fn of(text: &str, part: &str, len: usize) -> Option<Self> {
+        let span = Span::of(text, part);
+        if !span.is_known() {
+            return None;
+        }
+        let offset = span.byte_range.start;
+        let line_start = text[..offset].rfind('\n').map_or(0, |i| i + 1);
+        let line_end = text[offset..].find('\n').map_or(text.len(), |i| offset + i);
+        Some(Location {
+            line: span.line,
+            column: span.column,
+            source_line: text[line_start..line_end].trim_end().to_string(),
+            len: len.max(1),
+        })
+    }
+}
+
+/// a message, optionally pointing to a part of the source
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Label {
+    pub message: String,
+    pub location: Option<Location>,
+}
+
+/// a parse failure, rendered with the source line it happened at, e.g.
+/// ```text
+/// error: expected `<tr algorithm="`
+///  --> index.bs:1:1
+///   |
+/// 1 | <tr algoritm="abs">
+///   | ^^^^^^^^^^^^^^ expected `<tr algorithm="`
+///   |
+/// note: while parsing OverloadRow::parse
+///  --> index.bs:1:1
+/// help: did you mean `<tr algorithm="`?
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    /// the name of the source, e.g. a file name or url
+    pub file: Option<String>,
+    pub primary: Label,
+    /// the `context(...)` stack of the parsers, innermost first
+    pub notes: Vec<Label>,
+    pub help: Option<String>,
+}
+
+fn describe(kind: &VerboseErrorKind) -> String {
+    match kind {
+        VerboseErrorKind::Char(c) => format!("expected `{c}`"),
+        VerboseErrorKind::Context(c) => format!("while parsing {c}"),
+        VerboseErrorKind::Nom(ErrorKind::Eof) => "expected the end of the input".to_string(),
+        VerboseErrorKind::Nom(ErrorKind::Tag) => "unexpected text".to_string(),
+        VerboseErrorKind::Nom(kind) => format!("unexpected text ({})", kind.description()),
+    }
+}
+
+impl Diagnostic {
+    /// turns the error of a parser called with `input` into a diagnostic
+    pub fn from_nom<I: std::ops::Deref<Target = str>>(input: &str, error: &NomError<I>) -> Self {
+        let (primary, notes): (Vec<_>, Vec<_>) = error
+            .errors
+            .iter()
+            .partition(|(_, kind)| !matches!(kind, VerboseErrorKind::Context(_)));
+
+        let (at, kind) = match primary.first().or(notes.first()) {
+            Some((at, kind)) => (&**at, kind.clone()),
+            None => (input, VerboseErrorKind::Nom(ErrorKind::Fail)),
+        };
+        let suggestion = near_miss(at);
+        let (message, len) = match suggestion {
+            Some((tag, len)) => (format!("expected `{tag}`"), len),
+            None => (describe(&kind), 1),
+        };
+        let mut labels: Vec<Label> = vec![];
+        for (at, kind) in notes {
+            let message = describe(kind);
+            if labels.iter().all(|l| l.message != message) {
+                labels.push(Label {
+                    message,
+                    location: Location::of(input, at, 1),
+                });
+            }
+        }
+        Diagnostic {
+            file: None,
+            primary: Label {
+                message,
+                location: Location::of(input, at, len),
+            },
+            notes: labels,
+            help: suggestion.map(|(tag, _)| format!("did you mean `{tag}`?")),
+        }
+    }
+
+    pub fn with_file(mut self, file: &str) -> Self {
+        self.file = Some(file.to_string());
+        self
+    }
+
+    /// the number of digits of the largest line number, to align the source lines
+    fn gutter(&self) -> usize {
+        let lines = [&self.primary].into_iter().chain(&self.notes);
+        let lines = lines.filter_map(|l| l.location.as_ref().map(|l| l.line));
+        lines.max().unwrap_or_default().to_string().len()
+    }
+
+    fn write_location(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+        label: &Label,
+        snippet: bool,
+    ) -> std::fmt::Result {
+        let Some(location) = &label.location else {
+            return Ok(());
+        };
+        let gutter = " ".repeat(self.gutter());
+        let file = self.file.as_deref().unwrap_or("<input>");
+        writeln!(
+            f,
+            "{gutter}--> {file}:{}:{}",
+            location.line, location.column
+        )?;
+        if snippet {
+            let source_line = location.source_line.replace('\t', " ");
+            let line = format!("{:>1$}", location.line, gutter.len());
+            let indent = " ".repeat(location.column - 1);
+            let underline = "^".repeat(location.len);
+            writeln!(f, "{gutter} |")?;
+            writeln!(f, "{line} | {source_line}")?;
+            writeln!(f, "{gutter} | {indent}{underline} {}", label.message)?;
+            writeln!(f, "{gutter} |")?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "error: {}", self.primary.message)?;
+        self.write_location(f, &self.primary, true)?;
+        for note in &self.notes {
+            writeln!(f, "note: {}", note.message)?;
+            self.write_location(f, note, false)?;
+        }
+        if let Some(help) = &self.help {
+            writeln!(f, "help: {help}")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for Diagnostic {}
+
+/// the errors of downloading and parsing a spec
+#[derive(Debug, thiserror::Error)]
+pub enum SpecError {
+    #[error("failed to download {url}: {message}")]
+    Download { url: String, message: String },
+    #[error("failed to read {path}: {message}")]
+    Read { path: String, message: String },
+    #[error("invalid {file}: {message}")]
+    Invalid { file: String, message: String },
+    #[error("{0}")]
+    Parse(Box<Diagnostic>),
+    #[error("{0}")]
+    Unmatched(crate::wgsl::coverage::UnmatchedAnchors),
+}
+
+mod tests {
+    use super::*;
+    use crate::wgsl::parametrization::OverloadRow;
+
+    #[test]
+    fn test_diagnostic() {
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+        assert_eq!(
+            did_you_mean("<tr algoritm=\"abs\">"),
+            Some("<tr algorithm=\"")
+        );
+        assert_eq!(did_you_mean("</xmb> rest"), Some("</xmp>"));
+        assert_eq!(did_you_mean("<tr algorithm=\"abs\">"), None);
+        assert_eq!(did_you_mean("fn abs"), None);
+
+        let str = "\n<tr algoritm=\"abs\">\n<td>|T| is f32\n";
+        let Err(nom::Err::Error(e)) = OverloadRow::parse(&str[1..]) else {
+            panic!("parsed");
+        };
+        let diagnostic = Diagnostic::from_nom(str, &e).with_file("index.bs");
+        assert_eq!(
+            diagnostic.help.as_deref(),
+            Some("did you mean `<tr algorithm=\"`?")
+        );
+        assert_eq!(
+            diagnostic.to_string(),
+            r#"error: expected `<tr algorithm="`
+ --> index.bs:2:1
+  |
+2 | <tr algoritm="abs">
+  | ^^^^^^^^^^^^^^ expected `<tr algorithm="`
+  |
+note: while parsing OverloadRow::parse
+ --> index.bs:2:1
+help: did you mean `<tr algorithm="`?
+"#
+        );
+    }
+}
@@ -0,0 +1,522 @@
+use std::fmt::Display;
+
+use lazy_static::lazy_static;
+use nom::{combinator::verify, multi::many1};
+use regex::Regex;
+
+use crate::{
+    asciidoc, bikeshed, fn_name,
+    misc::normalize_whitespace,
+    nom_prelude::*,
+    span::*,
+    wgsl::{
+        parametrization::{OverloadRow, Parametrization},
+        primitives::{FnDecl, Ident, TemplateArg, Ty},
+        resolution::{size_arg, Substitution},
+    },
+};
+
+/// the qualifiers which may precede the type of a parameter or of the result, e.g. `out` or `highp`
+pub const QUALIFIERS: &[&str] = &[
+    "inout",
+    "in",
+    "out",
+    "highp",
+    "mediump",
+    "lowp",
+    "const",
+    "coherent",
+    "volatile",
+    "restrict",
+    "readonly",
+    "writeonly",
+    "precise",
+];
+
+/// the type families which stand for several types, e.g. `genFType` for `float`, `vec2`, `vec3` and `vec4`
+const FAMILIES: &[(&str, &[&str])] = &[
+    ("genType", &["float", "vec2", "vec3", "vec4"]),
+    ("genFType", &["float", "vec2", "vec3", "vec4"]),
+    ("genDType", &["double", "dvec2", "dvec3", "dvec4"]),
+    ("genIType", &["int", "ivec2", "ivec3", "ivec4"]),
+    ("genUType", &["uint", "uvec2", "uvec3", "uvec4"]),
+    ("genBType", &["bool", "bvec2", "bvec3", "bvec4"]),
+    ("vec", &["vec2", "vec3", "vec4"]),
+    ("dvec", &["dvec2", "dvec3", "dvec4"]),
+    ("ivec", &["ivec2", "ivec3", "ivec4"]),
+    ("uvec", &["uvec2", "uvec3", "uvec4"]),
+    ("bvec", &["bvec2", "bvec3", "bvec4"]),
+    (
+        "mat",
+        &[
+            "mat2", "mat3", "mat4", "mat2x3", "mat2x4", "mat3x2", "mat3x4", "mat4x2", "mat4x3",
+        ],
+    ),
+    (
+        "dmat",
+        &[
+            "dmat2", "dmat3", "dmat4", "dmat2x3", "dmat2x4", "dmat3x2", "dmat3x4", "dmat4x2",
+            "dmat4x3",
+        ],
+    ),
+];
+
+/// the prefixes a `g` in front of a vector, sampler or image type stands for
+const G_PREFIXES: &[&str] = &["", "i", "u"];
+
+/// the type without its `g` if `name` is a family like `gvec4` or `gsampler2D`
+fn g_family(name: &str) -> Option<&str> {
+    let rest = name.strip_prefix('g')?;
+    let prefixed = ["vec", "sampler", "image", "texture", "subpassInput"];
+    prefixed.iter().any(|p| rest.starts_with(p)).then_some(rest)
+}
+
+/// the types a family name like `genFType` or `gsampler2D` stands for.
+/// a `g` in front of a vector, sampler or image type stands for no prefix, `i` and `u`
+pub fn family_members(name: &str) -> Option<Vec<String>> {
+    if let Some((_, members)) = FAMILIES.iter().find(|(family, _)| *family == name) {
+        return Some(members.iter().map(|m| m.to_string()).collect());
+    }
+    let rest = g_family(name)?;
+    Some(G_PREFIXES.iter().map(|p| format!("{p}{rest}")).collect())
+}
+
+/// the names of the types of the arguments and the result of `decl`, and of their type parameters
+fn type_names(decl: &FnDecl) -> impl Iterator<Item = &Ident> {
+    let tys = decl.args.iter().map(|(_, ty)| ty).chain([&decl.out]);
+    let tys = tys.flat_map(|ty| [ty].into_iter().chain(ty.param_tys()));
+    tys.map(|ty| &ty.name)
+}
+
+/// the shape of a member of a family, which the members of the other families of a declaration
+/// have too, e.g. `3` for `vec3` and `ivec3`, `1` for `float` and `mat2x3` for `dmat2x3`
+fn member_shape(member: &str) -> String {
+    let size = member.trim_start_matches(|c: char| c.is_ascii_lowercase());
+    match (size.is_empty(), member.contains("mat")) {
+        (true, _) => "1".to_string(),
+        (false, true) => format!("mat{size}"),
+        (false, false) => size.to_string(),
+    }
+}
+
+/// `decl` with its families replaced by their members, once for each shape the members
+/// of all of its families have, and once for each of the [`G_PREFIXES`] of its `g` families.
+/// the families of a declaration stand for the same component count and prefix, e.g.
+/// `bvec lessThan(vec x, vec y)` becomes `bvec2 lessThan(vec2 x, vec2 y)`, `bvec3 lessThan(vec3 x, vec3 y)`
+/// and `bvec4 lessThan(vec4 x, vec4 y)`, and `gvec4 texture(gsampler2D sampler, vec2 P)`
+/// becomes `vec4 texture(sampler2D sampler, vec2 P)`, `ivec4 texture(isampler2D sampler, vec2 P)`
+/// and `uvec4 texture(usampler2D sampler, vec2 P)`. a declaration without families is returned as is
+pub fn expand_families(decl: &FnDecl) -> Vec<FnDecl> {
+    let mut families: Vec<&Ident> = vec![];
+    for name in type_names(decl) {
+        if family_members(name).is_some() && !families.contains(&name) {
+            families.push(name);
+        }
+    }
+    let sized = families.iter().filter(|f| g_family(f).is_none());
+    let members: Vec<Vec<String>> = sized.filter_map(|f| family_members(f)).collect();
+    let mut shapes: Vec<String> = match members.first() {
+        Some(first) => first.iter().map(|m| member_shape(m)).collect(),
+        None => vec![String::new()],
+    };
+    shapes.retain(|shape| {
+        members
+            .iter()
+            .all(|m| m.iter().any(|m| member_shape(m) == *shape))
+    });
+    let prefixes = match families.iter().any(|f| g_family(f).is_some()) {
+        true => G_PREFIXES,
+        false => &[""],
+    };
+    let mut decls = vec![];
+    for shape in &shapes {
+        for prefix in prefixes {
+            let mut subst = Substitution::default();
+            for &family in &families {
+                let member = match g_family(family) {
+                    Some(rest) => format!("{prefix}{rest}"),
+                    None => {
+                        let members = family_members(family).unwrap_or_default();
+                        let member = members.into_iter().find(|m| member_shape(m) == *shape);
+                        member.unwrap_or_default()
+                    }
+                };
+                let ty = Ty::new(member.as_str().into(), vec![]);
+                subst.insert(family.clone(), TemplateArg::from_ty(ty));
+            }
+            decls.push(subst.apply_fn_decl(decl));
+        }
+    }
+    decls
+}
+
+fn parse_qualifiers(s: &str) -> NomResult<&str, Vec<String>> {
+    let qualifier = verify(identifier, |q: &str| QUALIFIERS.contains(&q));
+    let parser = many0(terminated(ws0_then(qualifier), peek(ws1)));
+    map(context(fn_name!(), parser), |qualifiers| {
+        qualifiers.into_iter().map(str::to_string).collect()
+    })(s)
+}
+
+fn parse_ty(s: &str) -> NomResult<&str, Ty> {
+    let parser = ws0_then(spanned(Ident::parse));
+    map(context(fn_name!(), parser), |(name, span)| Ty {
+        span,
+        ..Ty::new(name, vec![])
+    })(s)
+}
+
+/// a name written in italics `_x_`, bold `*abs*`, or without markup
+fn parse_marked_name(s: &str) -> NomResult<&str, Ident> {
+    let parser = ws0_then(alt((
+        delimited(tag("**"), Ident::parse, tag("**")),
+        delimited(tag("*"), Ident::parse, tag("*")),
+        delimited(tag("__"), Ident::parse, tag("__")),
+        delimited(tag("_"), Ident::parse, tag("_")),
+        Ident::parse,
+    )));
+    context(fn_name!(), parser)(s)
+}
+
+/// a parameter like `out highp genIType _exp_` or `ivec2 _offsets_[4]`
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Param {
+    qualifiers: Vec<String>,
+    name: Ident,
+    ty: Ty,
+}
+
+fn parse_param(s: &str) -> NomResult<&str, Param> {
+    let array = delimited(ws0_then(tag("[")), ws0_then(digit1), ws0_then(tag("]")));
+    let parser = tuple((parse_qualifiers, parse_ty, parse_marked_name, opt(array)));
+    map(
+        context(fn_name!(), parser),
+        |(qualifiers, ty, name, size)| Param {
+            qualifiers,
+            name,
+            ty: match size {
+                Some(size) => {
+                    Ty::with_args("array".into(), vec![TemplateArg::Type(ty), size_arg(size)])
+                }
+                None => ty,
+            },
+        },
+    )(s)
+}
+
+/// the parameters, with the optional ones written in brackets, e.g. `gsampler2D _sampler_, vec2 _P_ [, float _bias_]`
+fn parse_params(s: &str) -> NomResult<&str, Vec<(Param, bool)>> {
+    let comma = || ws0_then(tag(","));
+    let optional = delimited(
+        ws0_then(tag("[")),
+        many1(preceded(comma(), parse_param)),
+        ws0_then(tag("]")),
+    );
+    let optional = map(optional, |params| {
+        params.into_iter().map(|p| (p, true)).collect()
+    });
+    let required = map(preceded(comma(), parse_param), |p| vec![(p, false)]);
+    let params = map(
+        pair(parse_param, many0(alt((required, optional)))),
+        |(first, rest)| {
+            let mut params = vec![(first, false)];
+            params.extend(rest.into_iter().flatten());
+            params
+        },
+    );
+    let void = map(ws0_then(tag("void")), |_| vec![]);
+    let parser = alt((
+        terminated(void, peek(ws0_then(tag(")")))),
+        params,
+        map(ws0, |_| vec![]),
+    ));
+    context(fn_name!(), parser)(s)
+}
+
+/// a builtin function declaration of a "Syntax" table cell, with its type families
+/// replaced by members of the same shape, see [`expand_families`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BuiltinFunction {
+    pub overload: OverloadRow,
+    /// the qualifiers of each argument, e.g. `out` or `highp`
+    pub arg_qualifiers: Vec<Vec<String>>,
+    /// the qualifiers of the result, e.g. `highp`
+    pub out_qualifiers: Vec<String>,
+    /// the "Description" cell, with markup stripped
+    pub description: String,
+    /// the title of the section of the table, e.g. "Angle and Trigonometry Functions"
+    pub section: Option<String>,
+}
+
+impl Display for BuiltinFunction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.overload)
+    }
+}
+
+impl BuiltinFunction {
+    /// parses a declaration like `genFType *clamp*(genFType _x_, float _minVal_, float _maxVal_)`.
+    /// a declaration with optional parameters in brackets is expanded to one function for
+    /// each number of optional parameters, and each of those to one function for each member
+    /// of its type families, see [`expand_families`]
+    pub fn parse(s: &str) -> NomResult<&str, Vec<Self>> {
+        let parser = tuple((
+            parse_qualifiers,
+            parse_ty,
+            parse_marked_name,
+            delimited(ws0_then(tag("(")), parse_params, ws0_then(tag(")"))),
+        ));
+        let parser = preceded(ws0, spanned(parser));
+        map(
+            context(fn_name!(), parser),
+            |((out_qualifiers, out, name, params), span)| {
+                let optional = params.iter().filter(|(_, optional)| *optional).count();
+                (0..=optional)
+                    .flat_map(|n| {
+                        let mut skipped = optional - n;
+                        let mut params: Vec<_> = params.iter().collect();
+                        // optional parameters are left out from the end
+                        while skipped > 0 {
+                            let i = params.iter().rposition(|(_, optional)| *optional).unwrap();
+                            params.remove(i);
+                            skipped -= 1;
+                        }
+                        let fn_decl = FnDecl {
+                            name: name.clone(),
+                            args: params
+                                .iter()
+                                .map(|(p, _)| (p.name.clone(), p.ty.clone()))
+                                .collect(),
+                            out: out.clone(),
+                            span: span.clone(),
+                        };
+                        let arg_qualifiers: Vec<_> =
+                            params.iter().map(|(p, _)| p.qualifiers.clone()).collect();
+                        let functions = expand_families(&fn_decl).into_iter();
+                        let functions = functions.map(|fn_decl| BuiltinFunction {
+                            overload: OverloadRow {
+                                algorithm_attr: name.to_string(),
+                                parametrization: Parametrization(vec![]),
+                                attributes: vec![],
+                                fn_decl,
+                                span: span.clone(),
+                            },
+                            arg_qualifiers: arg_qualifiers.clone(),
+                            out_qualifiers: out_qualifiers.clone(),
+                            description: String::new(),
+                            section: None,
+                        });
+                        functions.collect::<Vec<_>>()
+                    })
+                    .collect()
+            },
+        )(s)
+    }
+
+    pub fn name(&self) -> &str {
+        &self.overload.fn_decl.name
+    }
+}
+
+/// a declaration of a "Syntax" cell that could not be parsed, e.g. one using a macro like `IMAGE_PARAMS`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnparsedSignature {
+    /// byte offset of the declaration in the document
+    pub offset: usize,
+    pub text: String,
+    pub section: Option<String>,
+}
+
+impl Display for UnparsedSignature {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.section {
+            Some(section) => write!(f, "{section}: {}", self.text),
+            None => write!(f, "{}", self.text),
+        }
+    }
+}
+
+/// the declarations of a "Syntax" cell, separated by hard line breaks ` +`
+fn signatures(cell: &str) -> impl Iterator<Item = &str> {
+    lazy_static! {
+        static ref BREAK: Regex = Regex::new(r"[ \t]\+[ \t]*(?:\n|$)|\n[ \t]*\n").unwrap();
+    }
+    BREAK
+        .split(cell)
+        .map(str::trim_start)
+        .filter(|s| !s.is_empty())
+}
+
+/// the declarations of the "Syntax" column of the tables of the "Built-In Functions" chapter,
+/// and the ones that could not be parsed. `text` is the source the spans are created for
+pub fn extract(text: &str) -> (Vec<BuiltinFunction>, Vec<UnparsedSignature>) {
+    let headings = asciidoc::headings(text);
+    let chapter = asciidoc::section(text, "Built-In Functions");
+    let (start, end) = chapter.map_or((0, text.len()), |r| (r.start, r.end));
+    let mut functions = vec![];
+    let mut unparsed = vec![];
+    let tables = asciidoc::tables(text);
+    let tables = tables.iter().filter(|t| (start..end).contains(&t.offset));
+    for table in tables.filter(|t| t.has_column("syntax")) {
+        let section = bikeshed::section_of(&headings, table.offset).map(|h| h.title.clone());
+        for row in &table.rows {
+            let [syntax, rest @ ..] = row.cells.as_slice() else {
+                continue;
+            };
+            let description = rest.first().map(|d| asciidoc::strip_markup(d));
+            let description = normalize_whitespace(&description.unwrap_or_default());
+            for signature in signatures(syntax) {
+                let offset = signature.as_ptr() as usize - text.as_ptr() as usize;
+                match terminated(BuiltinFunction::parse, ws0_then(eof))(signature) {
+                    Ok((_, parsed)) => {
+                        functions.extend(parsed.into_iter().map(|f| BuiltinFunction {
+                            description: description.clone(),
+                            section: section.clone(),
+                            ..f
+                        }))
+                    }
+                    Err(_) => unparsed.push(UnparsedSignature {
+                        offset,
+                        text: normalize_whitespace(signature),
+                        section: section.clone(),
+                    }),
+                }
+            }
+        }
+    }
+    (functions, unparsed)
+}
+
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_functions() {
+        let text = r#"
+== Built-In Functions
+
+=== Angle and Trigonometry Functions
+
+[options="header"]
+|====
+| Syntax | Description
+| genType *radians*(genType _degrees_)
+  | Converts _degrees_ to radians, i.e., (π / 180) {cdot} _degrees_.
+| genFType *modf*(genFType _x_, out genFType _i_) +
+  highp genIType *floatBitsToInt*(highp genFType _value_)
+  | Returns the fractional part of _x_.
+| gvec4 *texture*(gsampler2D _sampler_, vec2 _P_ [, float _bias_]) +
+  gvec4 *textureGatherOffsets*(gsampler2D _sampler_, vec2 _P_, ivec2 _offsets_[4]) +
+  gvec4 *imageLoad*(readonly IMAGE_PARAMS)
+  | Texture lookups.
+| genFType *ldexp*(genFType _x_, genIType _exp_) | Builds a floating-point number.
+| bvec *lessThan*(vec _x_, vec _y_) | Compares componentwise.
+| void *barrier*() | Synchronizes.
+|====
+
+== Built-In Variables
+
+[options="header"]
+|====
+| Syntax | Description
+| int *notAFunction*(int _x_) | Not in the functions chapter.
+|====
+"#;
+        let (functions, unparsed) = with_source(text, || extract(text));
+        let mut names: Vec<_> = functions.iter().map(BuiltinFunction::name).collect();
+        names.dedup();
+        assert_eq!(
+            names,
+            [
+                "radians",
+                "modf",
+                "floatBitsToInt",
+                "texture",
+                "textureGatherOffsets",
+                "ldexp",
+                "lessThan",
+                "barrier"
+            ]
+        );
+        assert_eq!(unparsed.len(), 1);
+        assert_eq!(unparsed[0].text, "gvec4 *imageLoad*(readonly IMAGE_PARAMS)");
+        assert_eq!(&text[unparsed[0].offset..][..5], "gvec4");
+
+        let named = |name: &str| -> Vec<String> {
+            let functions = functions.iter().filter(|f| f.name() == name);
+            let functions = functions.map(|f| {
+                let decl = &f.overload.fn_decl;
+                let args: Vec<_> = decl.args.iter().map(|(_, ty)| ty.to_string()).collect();
+                format!("{} {}({})", decl.out, decl.name, args.join(", "))
+            });
+            functions.collect()
+        };
+        let radians = &functions[0];
+        assert_eq!(
+            radians.section.as_deref(),
+            Some("Angle and Trigonometry Functions")
+        );
+        assert_eq!(
+            radians.description,
+            "Converts degrees to radians, i.e., (π / 180) · degrees."
+        );
+        assert!(radians.overload.parametrization.is_empty());
+        assert_eq!(radians.overload.instances().len(), 1);
+        assert_eq!(radians.overload.fn_decl.span.line, 9);
+        assert_eq!(named("radians").len(), 4);
+
+        let modf = functions.iter().find(|f| f.name() == "modf").unwrap();
+        assert_eq!(modf.arg_qualifiers, [vec![], vec!["out".to_string()]]);
+        let float_bits = functions.iter().find(|f| f.name() == "floatBitsToInt");
+        assert_eq!(float_bits.unwrap().out_qualifiers, ["highp"]);
+
+        // the `g` families of a declaration have the same prefix
+        let texture = named("texture");
+        assert_eq!(texture.len(), 6);
+        assert_eq!(
+            texture[..3],
+            [
+                "vec4 texture(sampler2D, vec2)",
+                "ivec4 texture(isampler2D, vec2)",
+                "uvec4 texture(usampler2D, vec2)",
+            ]
+        );
+        let offsets = named("textureGatherOffsets");
+        assert_eq!(
+            offsets[0],
+            "vec4 textureGatherOffsets(sampler2D, vec2, array<ivec2, 4>)"
+        );
+
+        // the other families of a declaration have the same component count
+        let ldexp = named("ldexp");
+        assert_eq!(
+            ldexp,
+            [
+                "float ldexp(float, int)",
+                "vec2 ldexp(vec2, ivec2)",
+                "vec3 ldexp(vec3, ivec3)",
+                "vec4 ldexp(vec4, ivec4)",
+            ]
+        );
+        let less_than = named("lessThan");
+        assert_eq!(
+            less_than,
+            [
+                "bvec2 lessThan(vec2, vec2)",
+                "bvec3 lessThan(vec3, vec3)",
+                "bvec4 lessThan(vec4, vec4)",
+            ]
+        );
+        assert_eq!(named("barrier"), ["void barrier()"]);
+
+        assert_eq!(
+            family_members("genType").unwrap(),
+            ["float", "vec2", "vec3", "vec4"]
+        );
+        assert_eq!(
+            family_members("gsampler2DRect").unwrap(),
+            ["sampler2DRect", "isampler2DRect", "usampler2DRect"]
+        );
+        assert!(family_members("sampler2D").is_none());
+    }
+}
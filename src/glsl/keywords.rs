@@ -0,0 +1,83 @@
+use lazy_static::lazy_static;
+use regex::Regex;
+
+/// the words of the first delimited (`--`) block or paragraph after the one at `offset`, e.g.
+/// `*const* *uniform* *buffer*`
+fn words_after(text: &str, offset: usize) -> Vec<String> {
+    lazy_static! {
+        static ref WORD: Regex = Regex::new(r"^[A-Za-z_][A-Za-z0-9_]*$").unwrap();
+    }
+    let rest = &text[offset..];
+    let rest = rest[rest.find("\n\n").unwrap_or(rest.len())..].trim_start();
+    // skip a block attribute line like `[role="bnf"]`
+    let rest = match rest.starts_with('[') {
+        true => rest[rest.find('\n').unwrap_or(rest.len())..].trim_start(),
+        false => rest,
+    };
+    let block = match rest.strip_prefix("--\n") {
+        Some(open) => &open[..open.find("\n--").unwrap_or(open.len())],
+        None => &rest[..rest.find("\n\n").unwrap_or(rest.len())],
+    };
+    block
+        .split_whitespace()
+        .map(|w| w.trim_matches(|c| c == '*' || c == '+' || c == '`'))
+        .filter(|w| WORD.is_match(w))
+        .map(str::to_string)
+        .collect()
+}
+
+/// the keywords listed after "The following are the keywords of the language", without the
+/// ones reserved for future use
+pub fn extract_keywords(text: &str) -> Vec<String> {
+    lazy_static! {
+        static ref INTRO: Regex =
+            Regex::new(r"(?i)following are the (?:language's )?keywords").unwrap();
+    }
+    let intros = INTRO.find_iter(text);
+    let intros = intros.filter(|m| !text[m.end()..].starts_with(" reserved"));
+    intros.flat_map(|m| words_after(text, m.start())).collect()
+}
+
+/// the words listed after "The following are the keywords reserved for future use"
+pub fn extract_reserved_words(text: &str) -> Vec<String> {
+    lazy_static! {
+        static ref INTRO: Regex = Regex::new(r"(?i)keywords reserved for future use").unwrap();
+    }
+    let intros = INTRO.find_iter(text);
+    intros.flat_map(|m| words_after(text, m.start())).collect()
+}
+
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keywords() {
+        let text = r#"
+=== Keywords
+
+The following are the language's keywords and (after preprocessing) can only
+be used as described in this specification, or a compile-time error results:
+
+[role="bnf"]
+--
+*const* *uniform* *buffer* +
+*sampler2D* *atomic_uint*
+--
+
+The following are the keywords reserved for future use.
+Using them will result in a compile-time error:
+
+*common* *partition* *active*
+
+In addition, all identifiers containing two consecutive underscores are reserved.
+"#;
+        assert_eq!(
+            extract_keywords(text),
+            ["const", "uniform", "buffer", "sampler2D", "atomic_uint"]
+        );
+        assert_eq!(
+            extract_reserved_words(text),
+            ["common", "partition", "active"]
+        );
+    }
+}
@@ -0,0 +1,131 @@
+use std::path::Path;
+
+use crate::{
+    asciidoc,
+    diagnostic::SpecError,
+    grammar::GrammarRule,
+    spec::{BuiltinSignature, ShaderLangSpec},
+    wgsl::{enumerants::BuiltinValue, primitives::Ty},
+};
+
+use self::{
+    functions::{BuiltinFunction, UnparsedSignature},
+    variables::BuiltinVariable,
+};
+
+pub mod functions;
+pub mod keywords;
+pub mod variables;
+
+/// the main file of the GLSL specification in a checkout of https://github.com/KhronosGroup/GLSL
+pub const MAIN_FILE: &str = "GLSLangSpec.adoc";
+
+pub struct GlslSpec {
+    /// the document with its includes expanded and its conditionals evaluated
+    pub text: String,
+    /// the declarations of the "Syntax" columns of the built-in function tables
+    pub functions: Vec<BuiltinFunction>,
+    /// declarations that could not be parsed, e.g. ones using a macro like `IMAGE_PARAMS`
+    pub unparsed_functions: Vec<UnparsedSignature>,
+    pub keywords: Vec<String>,
+    pub reserved_words: Vec<String>,
+    pub builtin_variables: Vec<BuiltinVariable>,
+    /// the `:revnumber:` attribute of the document, if any
+    pub revision: Option<String>,
+}
+
+impl GlslSpec {
+    /// reads the GLSL (not the ESSL) specification from a local checkout of its AsciiDoc sources
+    pub fn from_checkout(dir: impl AsRef<Path>) -> Result<Self, SpecError> {
+        Self::from_adoc_file(dir.as_ref().join(MAIN_FILE), &["GLSL"])
+    }
+
+    /// reads an AsciiDoc document with its includes. `attributes` select the conditional parts,
+    /// e.g. `GLSL` or `ESSL`
+    pub fn from_adoc_file(path: impl AsRef<Path>, attributes: &[&str]) -> Result<Self, SpecError> {
+        let path = path.as_ref();
+        let text = asciidoc::read_document(path, attributes).map_err(|e| SpecError::Read {
+            path: path.display().to_string(),
+            message: e.to_string(),
+        })?;
+        Ok(Self::parse_adoc(&text))
+    }
+
+    /// the spans of the parsed nodes point into [`Self::text`]
+    pub fn parse_adoc(text: &str) -> Self {
+        crate::span::with_source(text, || {
+            let (functions, unparsed_functions) = functions::extract(text);
+            GlslSpec {
+                text: text.to_string(),
+                functions,
+                unparsed_functions,
+                keywords: keywords::extract_keywords(text),
+                reserved_words: keywords::extract_reserved_words(text),
+                builtin_variables: variables::extract(text),
+                revision: asciidoc::attribute(text, "revnumber").map(str::to_string),
+            }
+        })
+    }
+
+    /// the built-in functions named `name`, e.g. `"clamp"`
+    pub fn functions_named<'a>(
+        &'a self,
+        name: &'a str,
+    ) -> impl Iterator<Item = &'a BuiltinFunction> {
+        self.functions.iter().filter(move |f| f.name() == name)
+    }
+
+    /// the built-in constants, e.g. `gl_MaxDrawBuffers`
+    pub fn builtin_constants(&self) -> impl Iterator<Item = &BuiltinVariable> {
+        self.builtin_variables.iter().filter(|v| v.is_const())
+    }
+
+    /// the built-in variables of `stage` (e.g. `fragment`), including the ones of every stage
+    pub fn builtin_variables_of<'a>(
+        &'a self,
+        stage: &'a str,
+    ) -> impl Iterator<Item = &'a BuiltinVariable> {
+        let variables = self.builtin_variables.iter().filter(|v| !v.is_const());
+        variables.filter(move |v| v.stage.as_deref().is_none_or(|s| s == stage))
+    }
+
+    /// the built-in variables with an equivalent WGSL `@builtin` value
+    pub fn wgsl_builtins(&self) -> impl Iterator<Item = (&BuiltinVariable, BuiltinValue)> {
+        let variables = self.builtin_variables.iter();
+        variables.filter_map(|v| Some((v, v.wgsl_builtin()?)))
+    }
+}
+
+impl ShaderLangSpec for GlslSpec {
+    fn language(&self) -> &'static str {
+        "GLSL"
+    }
+
+    fn revision(&self) -> Option<&str> {
+        self.revision.as_deref()
+    }
+
+    fn source_text(&self) -> &str {
+        &self.text
+    }
+
+    fn builtin_functions(&self) -> Vec<BuiltinSignature> {
+        let functions = self.functions.iter();
+        functions
+            .flat_map(|f| f.overload.signatures(Ty::to_string))
+            .collect()
+    }
+
+    fn keywords(&self) -> Vec<&str> {
+        self.keywords.iter().map(String::as_str).collect()
+    }
+
+    fn reserved_words(&self) -> Vec<&str> {
+        self.reserved_words.iter().map(String::as_str).collect()
+    }
+
+    /// the grammar chapter is not parsed
+    fn grammar(&self) -> &[GrammarRule] {
+        &[]
+    }
+}
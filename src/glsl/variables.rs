@@ -0,0 +1,443 @@
+use std::fmt::Display;
+
+use lazy_static::lazy_static;
+use nom::{bytes::complete::take_until, combinator::map_opt, multi::many1};
+use regex::Regex;
+
+use crate::{
+    asciidoc, fn_name,
+    nom_prelude::*,
+    span::*,
+    wgsl::{
+        enumerants::BuiltinValue,
+        primitives::{Ident, Ty},
+    },
+};
+
+/// the built-in variables of GLSL (as used by Vulkan) with an equivalent WGSL built-in value,
+/// as `(name, stage, in or out, value)`.
+/// `gl_VertexID` and `gl_InstanceID` do not include the base vertex and instance, so they
+/// have none. `gl_SampleMaskIn` and `gl_SampleMask` are `int[]`, while `sample_mask` is a `u32`
+pub const WGSL_BUILTINS: &[(&str, &str, &str, BuiltinValue)] = &[
+    ("gl_VertexIndex", "vertex", "in", BuiltinValue::VertexIndex),
+    (
+        "gl_InstanceIndex",
+        "vertex",
+        "in",
+        BuiltinValue::InstanceIndex,
+    ),
+    ("gl_Position", "vertex", "out", BuiltinValue::Position),
+    (
+        "gl_ClipDistance",
+        "vertex",
+        "out",
+        BuiltinValue::ClipDistances,
+    ),
+    ("gl_FragCoord", "fragment", "in", BuiltinValue::Position),
+    (
+        "gl_FrontFacing",
+        "fragment",
+        "in",
+        BuiltinValue::FrontFacing,
+    ),
+    ("gl_FragDepth", "fragment", "out", BuiltinValue::FragDepth),
+    ("gl_SampleID", "fragment", "in", BuiltinValue::SampleIndex),
+    (
+        "gl_SampleMaskIn",
+        "fragment",
+        "in",
+        BuiltinValue::SampleMask,
+    ),
+    ("gl_SampleMask", "fragment", "out", BuiltinValue::SampleMask),
+    (
+        "gl_LocalInvocationID",
+        "compute",
+        "in",
+        BuiltinValue::LocalInvocationId,
+    ),
+    (
+        "gl_LocalInvocationIndex",
+        "compute",
+        "in",
+        BuiltinValue::LocalInvocationIndex,
+    ),
+    (
+        "gl_GlobalInvocationID",
+        "compute",
+        "in",
+        BuiltinValue::GlobalInvocationId,
+    ),
+    ("gl_WorkGroupID", "compute", "in", BuiltinValue::WorkgroupId),
+    (
+        "gl_NumWorkGroups",
+        "compute",
+        "in",
+        BuiltinValue::NumWorkgroups,
+    ),
+    (
+        "gl_SubgroupInvocationID",
+        "compute",
+        "in",
+        BuiltinValue::SubgroupInvocationId,
+    ),
+    (
+        "gl_SubgroupInvocationID",
+        "fragment",
+        "in",
+        BuiltinValue::SubgroupInvocationId,
+    ),
+    (
+        "gl_SubgroupSize",
+        "compute",
+        "in",
+        BuiltinValue::SubgroupSize,
+    ),
+    (
+        "gl_SubgroupSize",
+        "fragment",
+        "in",
+        BuiltinValue::SubgroupSize,
+    ),
+];
+
+/// a built-in variable or constant, e.g. `in int gl_VertexID;`, a member of a built-in block
+/// like `out gl_PerVertex { vec4 gl_Position; };`, or `const mediump int gl_MaxDrawBuffers = 4;`
+#[derive(Debug, Clone)]
+pub struct BuiltinVariable {
+    pub name: Ident,
+    pub ty: Ty,
+    /// e.g. `in`, `out`, `uniform`, `const` or `highp`
+    pub qualifiers: Vec<String>,
+    /// the size of an array, empty for `gl_ClipDistance[]`
+    pub array: Option<String>,
+    /// the initializer of a constant, e.g. `4` or `ivec3(65535, 65535, 65535)`
+    pub value: Option<String>,
+    /// the interface block the variable is a member of, e.g. `gl_PerVertex`
+    pub block: Option<String>,
+    /// the instance name of the block, e.g. `gl_in` for `in gl_PerVertex { ... } gl_in[];`
+    pub instance: Option<String>,
+    /// the shader stage the declaration is made in, e.g. `vertex` or `tessellation control`
+    pub stage: Option<String>,
+    pub span: Span,
+}
+
+crate::eq_ignoring_span!(BuiltinVariable {
+    name,
+    ty,
+    qualifiers,
+    array,
+    value,
+    block,
+    instance,
+    stage
+});
+
+impl Display for BuiltinVariable {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for qualifier in &self.qualifiers {
+            write!(f, "{qualifier} ")?;
+        }
+        write!(f, "{} {}", self.ty, self.name)?;
+        if let Some(size) = &self.array {
+            write!(f, "[{size}]")?;
+        }
+        if let Some(value) = &self.value {
+            write!(f, " = {value}")?;
+        }
+        write!(f, ";")
+    }
+}
+
+impl BuiltinVariable {
+    pub fn is_const(&self) -> bool {
+        self.qualifiers.iter().any(|q| q == "const")
+    }
+
+    /// the components of the value of a constant, e.g. `[65535, 65535, 65535]` for
+    /// `ivec3(65535, 65535, 65535)`. the values of the built-in constants are the minimums
+    /// an implementation must support
+    pub fn minimum_value(&self) -> Option<Vec<i64>> {
+        let value = self.value.as_deref()?.trim();
+        let components = match value.split_once('(') {
+            Some((_, args)) => args.strip_suffix(')')?,
+            None => value,
+        };
+        let components = components.split(',').map(|c| c.trim().parse().ok());
+        components.collect()
+    }
+
+    /// the equivalent WGSL `@builtin` value, e.g. `global_invocation_id` for `gl_GlobalInvocationID`.
+    /// a variable declared for every stage matches the entries of each stage. members of
+    /// block instances like `gl_in[]` are the values of other invocations, so they have none
+    pub fn wgsl_builtin(&self) -> Option<BuiltinValue> {
+        if self.instance.is_some() {
+            return None;
+        }
+        let mut builtins = WGSL_BUILTINS.iter();
+        builtins
+            .find(|(name, stage, direction, _)| {
+                *name == self.name.as_str()
+                    && self.stage.as_deref().is_none_or(|s| s == *stage)
+                    && self.qualifiers.iter().any(|q| q == direction)
+            })
+            .map(|(.., value)| *value)
+    }
+}
+
+/// whitespace, comments and preprocessor lines
+fn skip(s: &str) -> NomResult<&str, ()> {
+    let line_comment = recognize(pair(tag("//"), take_till(|c| c == '\n')));
+    let block_comment = recognize(tuple((tag("/*"), take_until("*/"), tag("*/"))));
+    let directive = recognize(pair(tag("#"), take_till(|c| c == '\n')));
+    map(
+        many0(alt((ws1, line_comment, block_comment, directive))),
+        |_| (),
+    )(s)
+}
+
+fn skip_then<'a, O>(
+    parser: impl FnMut(&'a str) -> NomResult<&'a str, O>,
+) -> impl FnMut(&'a str) -> NomResult<&'a str, O> {
+    preceded(skip, parser)
+}
+
+/// `qualifiers type name[size] = value;`, the words are parsed by the caller
+fn parse_declarator(s: &str) -> NomResult<&str, (Option<String>, Option<String>)> {
+    let array = delimited(tag("["), take_until("]"), tag("]"));
+    let array = map(skip_then(array), |size: &str| size.trim().to_string());
+    let value = preceded(tag("="), take_until(";"));
+    let value = map(skip_then(value), |value: &str| value.trim().to_string());
+    let parser = terminated(pair(opt(array), opt(value)), skip_then(tag(";")));
+    context(fn_name!(), parser)(s)
+}
+
+/// `qualifiers... type name`, at least a type and a name
+fn parse_words(s: &str) -> NomResult<&str, Vec<(&str, Span)>> {
+    let parser = many1(skip_then(spanned(identifier)));
+    context(fn_name!(), parser)(s)
+}
+
+fn variable(
+    words: Vec<(&str, Span)>,
+    declarator: (Option<String>, Option<String>),
+) -> Option<BuiltinVariable> {
+    let [qualifiers @ .., (ty, ty_span), (name, name_span)] = words.as_slice() else {
+        return None;
+    };
+    let (array, value) = declarator;
+    Some(BuiltinVariable {
+        name: Ident::new(name, name_span.clone()),
+        ty: Ty {
+            span: ty_span.clone(),
+            ..Ty::new((*ty).into(), vec![])
+        },
+        qualifiers: qualifiers.iter().map(|(q, _)| q.to_string()).collect(),
+        array,
+        value,
+        block: None,
+        instance: None,
+        stage: None,
+        span: Span::default(),
+    })
+}
+
+/// a declaration, or the members of a block declaration like `out gl_PerVertex { ... } gl_out[];`.
+/// `precision` statements and `struct` declarations declare no variables
+fn parse_statement(s: &str) -> NomResult<&str, Vec<BuiltinVariable>> {
+    let member = map_opt(
+        spanned(pair(parse_words, parse_declarator)),
+        |((words, declarator), span)| {
+            Some(BuiltinVariable {
+                span,
+                ..variable(words, declarator)?
+            })
+        },
+    );
+    let block = tuple((
+        parse_words,
+        delimited(skip_then(tag("{")), many0(member), skip_then(tag("}"))),
+        opt(skip_then(identifier)),
+        parse_declarator,
+    ));
+    let block = map(block, |(words, members, instance, _)| {
+        let Some(((name, _), qualifiers)) = words.split_last() else {
+            return vec![];
+        };
+        if qualifiers.iter().any(|(q, _)| *q == "struct") {
+            return vec![];
+        }
+        let qualifiers: Vec<_> = qualifiers.iter().map(|(q, _)| q.to_string()).collect();
+        let members = members
+            .into_iter()
+            .map(|m: BuiltinVariable| BuiltinVariable {
+                block: Some(name.to_string()),
+                instance: instance.map(ToString::to_string),
+                qualifiers: [qualifiers.clone(), m.qualifiers.clone()].concat(),
+                ..m
+            });
+        members.collect()
+    });
+    let declaration = spanned(pair(parse_words, parse_declarator));
+    let declaration = map(declaration, |((words, declarator), span)| {
+        if words.first().is_some_and(|(w, _)| *w == "precision") {
+            return vec![];
+        }
+        let variable = variable(words, declarator).map(|v| BuiltinVariable { span, ..v });
+        variable.into_iter().collect()
+    });
+    context(fn_name!(), alt((block, declaration)))(s)
+}
+
+/// the declarations of a `[source,glsl]` block. statements that cannot be parsed are skipped
+fn parse_block(s: &str) -> Vec<BuiltinVariable> {
+    let mut variables = vec![];
+    let mut rest = s;
+    while let Ok((r, _)) = skip(rest) {
+        if r.is_empty() {
+            break;
+        }
+        match parse_statement(r) {
+            Ok((r, parsed)) => {
+                variables.extend(parsed);
+                rest = r;
+            }
+            Err(_) => match r.find(';') {
+                Some(i) => rest = &r[i + 1..],
+                None => break,
+            },
+        }
+    }
+    variables
+}
+
+/// the built-in variables and constants declared in the source blocks of the "Built-In Variables"
+/// chapter, or of the whole document if there is no such chapter.
+/// `text` is the source the spans are created for
+pub fn extract(text: &str) -> Vec<BuiltinVariable> {
+    lazy_static! {
+        static ref STAGE: Regex = Regex::new(
+            r"(?i)\bin the (vertex|tessellation control|tessellation evaluation|geometry|fragment|compute|task|mesh) (?:shader )?language"
+        )
+        .unwrap();
+    }
+    let chapter = asciidoc::section(text, "Built-In Variables");
+    let (start, end) = chapter.map_or((0, text.len()), |r| (r.start, r.end));
+    let mut variables = vec![];
+    let mut prose_start = start;
+    let blocks = asciidoc::source_blocks(text);
+    for block in blocks.iter().filter(|b| (start..end).contains(&b.offset)) {
+        if block.language.is_some_and(|l| l != "glsl") {
+            continue;
+        }
+        let prose = &text[prose_start.min(block.offset)..block.offset];
+        let stage = STAGE
+            .captures_iter(prose)
+            .last()
+            .map(|c| c[1].to_lowercase());
+        prose_start = block.offset + block.content.len();
+        let declared = parse_block(block.content).into_iter();
+        variables.extend(declared.map(|v| BuiltinVariable {
+            stage: stage.clone(),
+            ..v
+        }));
+    }
+    variables
+}
+
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_variables() {
+        let text = r#"
+== Built-In Variables
+
+In the vertex language, built-ins are intrinsically declared as:
+
+[source,glsl]
+----
+in int gl_VertexID;     // only present when not targeting Vulkan
+in int gl_InstanceID;
+
+out gl_PerVertex {
+    vec4 gl_Position;
+    float gl_ClipDistance[];
+};
+precision highp float;
+----
+
+In the tessellation control language, built-in variables are intrinsically declared as:
+
+[source,glsl]
+----
+in gl_PerVertex {
+    vec4 gl_Position;
+} gl_in[gl_MaxPatchVertices];
+patch out float gl_TessLevelOuter[4];
+----
+
+=== Built-In Constants
+
+[source,glsl]
+----
+const mediump int gl_MaxDrawBuffers = 4;
+const highp ivec3 gl_MaxComputeWorkGroupCount = ivec3(65535, 65535, 65535);
+struct gl_DepthRangeParameters {
+    float near;
+};
+uniform gl_DepthRangeParameters gl_DepthRange;
+----
+
+== Built-In Functions
+
+[source,glsl]
+----
+in int notAVariable;
+----
+"#;
+        let variables = with_source(text, || extract(text));
+        let names: Vec<_> = variables.iter().map(|v| v.name.as_str()).collect();
+        assert_eq!(
+            names,
+            [
+                "gl_VertexID",
+                "gl_InstanceID",
+                "gl_Position",
+                "gl_ClipDistance",
+                "gl_Position",
+                "gl_TessLevelOuter",
+                "gl_MaxDrawBuffers",
+                "gl_MaxComputeWorkGroupCount",
+                "gl_DepthRange"
+            ]
+        );
+        assert_eq!(variables[0].stage.as_deref(), Some("vertex"));
+        assert_eq!(variables[0].span.line, 8);
+        assert_eq!(variables[3].block.as_deref(), Some("gl_PerVertex"));
+        assert_eq!(variables[3].qualifiers, ["out"]);
+        assert_eq!(variables[3].to_string(), "out float gl_ClipDistance[];");
+        assert_eq!(variables[4].stage.as_deref(), Some("tessellation control"));
+        assert_eq!(variables[4].qualifiers, ["in"]);
+        assert_eq!(variables[4].instance.as_deref(), Some("gl_in"));
+        assert_eq!(variables[5].qualifiers, ["patch", "out"]);
+        assert_eq!(variables[5].array.as_deref(), Some("4"));
+        assert!(variables[6].is_const());
+        assert_eq!(variables[6].value.as_deref(), Some("4"));
+        assert_eq!(
+            variables[7].value.as_deref(),
+            Some("ivec3(65535, 65535, 65535)")
+        );
+        assert_eq!(variables[8].ty.to_string(), "gl_DepthRangeParameters");
+
+        assert_eq!(variables[6].minimum_value(), Some(vec![4]));
+        assert_eq!(
+            variables[7].minimum_value(),
+            Some(vec![65535, 65535, 65535])
+        );
+        assert_eq!(variables[8].minimum_value(), None);
+        assert_eq!(variables[0].wgsl_builtin(), None);
+        assert_eq!(variables[2].wgsl_builtin(), Some(BuiltinValue::Position));
+        // the positions of the vertices of the input patch
+        assert_eq!(variables[4].wgsl_builtin(), None);
+    }
+}
@@ -0,0 +1,92 @@
+use std::fmt::Display;
+
+use crate::span::Span;
+
+/// a symbol on the right hand side of a grammar rule
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GrammarSymbol {
+    /// literal source text, e.g. `'fn'` or `'->'`
+    Literal(String),
+    /// a token matching a regular expression, e.g. `/0[xX][0-9a-fA-F]+/`
+    Pattern(String),
+    /// a reference to another rule, e.g. `expression`
+    Rule(String),
+    /// `( a b | c )`, a choice between sequences
+    Group(Vec<Vec<GrammarSymbol>>),
+    /// `a ?`
+    Optional(Box<GrammarSymbol>),
+    /// `a *`
+    ZeroOrMore(Box<GrammarSymbol>),
+    /// `a +`
+    OneOrMore(Box<GrammarSymbol>),
+}
+
+fn write_alternatives(
+    f: &mut std::fmt::Formatter<'_>,
+    alternatives: &[Vec<GrammarSymbol>],
+    separator: &str,
+) -> std::fmt::Result {
+    for (i, sequence) in alternatives.iter().enumerate() {
+        if i != 0 {
+            write!(f, "{separator}")?;
+        }
+        let symbols: Vec<_> = sequence.iter().map(ToString::to_string).collect();
+        write!(f, "{}", symbols.join(" "))?;
+    }
+    Ok(())
+}
+
+impl Display for GrammarSymbol {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GrammarSymbol::Literal(l) => write!(f, "'{l}'"),
+            GrammarSymbol::Pattern(p) => write!(f, "/{p}/"),
+            GrammarSymbol::Rule(r) => write!(f, "{r}"),
+            GrammarSymbol::Group(alternatives) => {
+                write!(f, "( ")?;
+                write_alternatives(f, alternatives, " | ")?;
+                write!(f, " )")
+            }
+            GrammarSymbol::Optional(s) => write!(f, "{s} ?"),
+            GrammarSymbol::ZeroOrMore(s) => write!(f, "{s} *"),
+            GrammarSymbol::OneOrMore(s) => write!(f, "{s} +"),
+        }
+    }
+}
+
+/// a rule of a language grammar, e.g.
+/// ```text
+/// global_directive :
+///     | diagnostic_directive
+///     | enable_directive
+/// ```
+#[derive(Debug, Clone)]
+pub struct GrammarRule {
+    pub name: String,
+    pub alternatives: Vec<Vec<GrammarSymbol>>,
+    pub span: Span,
+}
+
+crate::eq_ignoring_span!(GrammarRule { name, alternatives });
+
+impl Display for GrammarRule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "{} :", self.name)?;
+        write!(f, "    | ")?;
+        write_alternatives(f, &self.alternatives, "\n    | ")?;
+        writeln!(f)
+    }
+}
+
+impl GrammarRule {
+    /// the literals of the rule which are its only alternatives, e.g. the words of a reserved word rule
+    pub fn literal_alternatives(&self) -> Vec<&str> {
+        self.alternatives
+            .iter()
+            .filter_map(|sequence| match sequence.as_slice() {
+                [GrammarSymbol::Literal(l)] => Some(l.as_str()),
+                _ => None,
+            })
+            .collect()
+    }
+}
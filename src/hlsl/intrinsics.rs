@@ -0,0 +1,726 @@
+use std::fmt::Display;
+
+use lazy_static::lazy_static;
+use regex::Regex;
+
+use crate::{
+    fn_name, markdown,
+    nom_prelude::*,
+    span::Span,
+    wgsl::{
+        parametrization::{Bound, BoundKind, OverloadRow, Parametrization, UnionBound},
+        primitives::{FnDecl, Ident, TemplateArg, Ty},
+        resolution::size_arg,
+    },
+};
+
+/// the qualifiers which may precede a parameter of a syntax block, e.g. `in` or `out`
+pub const QUALIFIERS: &[&str] = &["in", "out", "inout", "uniform", "const"];
+
+/// the number of components of vectors and the rows and columns of matrices, e.g. `float1` to `float4`
+pub const SIZES: &[&str] = &["1", "2", "3", "4"];
+
+/// the names of the size parameters of the `i`-th group of parameters sharing their template
+/// type: the vector size, matrix rows and matrix columns.
+/// names containing `N`, `R` or `C` would be taken for implicit WGSL size parameters
+fn size_params(i: usize) -> [String; 3] {
+    match i {
+        0 => ["N", "R", "C"].map(str::to_string),
+        i => ["K", "I", "J"].map(|p| format!("{p}{i}")),
+    }
+}
+
+/// the type parameter of the `i`-th parameter group with several component types
+fn component_param(i: usize) -> String {
+    match i {
+        0 => "T".to_string(),
+        i => format!("T{i}"),
+    }
+}
+
+/// the HLSL name of a type, e.g. `float3` for `vector<float, 3>` and `float2x4` for `matrix<float, 2, 4>`
+pub fn type_name(ty: &Ty) -> String {
+    match (ty.name.as_str(), ty.params.as_slice()) {
+        ("vector", [TemplateArg::Type(component), size]) => format!("{component}{size}"),
+        ("matrix", [TemplateArg::Type(component), rows, columns]) => {
+            format!("{component}{rows}x{columns}")
+        }
+        _ => ty.to_string(),
+    }
+}
+
+/// the type named like `float`, `float3` or `float2x4`, with vectors and matrices
+/// written as `vector<float, 3>` and `matrix<float, 2, 4>`
+pub fn parse_type(name: &str) -> Ty {
+    lazy_static! {
+        static ref SIZED: Regex =
+            Regex::new(r"^([A-Za-z_][A-Za-z0-9_]*?)([1-4])(?:x([1-4]))?$").unwrap();
+    }
+    let Some(captures) = SIZED.captures(name) else {
+        return Ty::new(name.into(), vec![]);
+    };
+    let component = TemplateArg::Type(Ty::new(captures[1].into(), vec![]));
+    match captures.get(3) {
+        Some(columns) => Ty::with_args(
+            "matrix".into(),
+            vec![
+                component,
+                size_arg(&captures[2]),
+                size_arg(columns.as_str()),
+            ],
+        ),
+        None => Ty::with_args("vector".into(), vec![component, size_arg(&captures[2])]),
+    }
+}
+
+/// the kinds of the "Template Type" column of a "Type Description" table
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Shape {
+    Scalar,
+    Vector,
+    Matrix,
+}
+
+/// a "Template Type" cell, e.g. "scalar, vector, or matrix" or "same as input x"
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Template {
+    Same(String),
+    Shapes(Vec<Shape>),
+}
+
+/// a "Component Type" cell, e.g. "float, int" or "same as input x"
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Component {
+    Same(String),
+    OneOf(Vec<String>),
+}
+
+/// a "Size" cell, e.g. "any", "3" or "same dimensions as input x"
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Size {
+    Any,
+    Fixed(String),
+    Same(String),
+}
+
+/// the type of a parameter or of the result, either written in the syntax block
+/// or described by a row of the "Type Description" table
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum TypeDescription {
+    Explicit(Ty),
+    Row {
+        template: Template,
+        component: Component,
+        size: Size,
+    },
+}
+
+/// the parameter name of a "same as input x" cell, in lowercase
+fn same_as(cell: &str) -> Option<String> {
+    lazy_static! {
+        static ref SAME: Regex = Regex::new(
+            r"same (?:dimensions |type |size )?as (?:the )?(?:input )?(?:parameter )?(\w+)"
+        )
+        .unwrap();
+    }
+    SAME.captures(&cell.to_lowercase())
+        .map(|c| c[1].to_string())
+}
+
+impl TypeDescription {
+    /// a row of the "Type Description" table, without the "Name" cell
+    fn from_cells(template: &str, component: &str, size: &str) -> Result<Self, String> {
+        lazy_static! {
+            static ref COMPONENTS: Regex = Regex::new(
+                r"\b(float|int|uint|bool|half|double|dword|min16float|min10float|min16int|min12int|min16uint|u?int(?:16|32|64)_t|float(?:16|32|64)_t)\b"
+            )
+            .unwrap();
+        }
+        let template = match same_as(template) {
+            Some(param) => Template::Same(param),
+            None => {
+                let template = template.to_lowercase();
+                let shapes = [
+                    ("scalar", Shape::Scalar),
+                    ("vector", Shape::Vector),
+                    ("matrix", Shape::Matrix),
+                ];
+                let shapes = shapes.iter().filter(|(word, _)| template.contains(word));
+                let shapes: Vec<_> = shapes.map(|(_, shape)| *shape).collect();
+                if shapes.is_empty() {
+                    return Err(format!("unknown template type `{template}`"));
+                }
+                Template::Shapes(shapes)
+            }
+        };
+        let component = match same_as(component) {
+            Some(param) => Component::Same(param),
+            None => {
+                let mut components: Vec<String> = vec![];
+                for m in COMPONENTS.find_iter(component) {
+                    if !components.iter().any(|c| c == m.as_str()) {
+                        components.push(m.as_str().to_string());
+                    }
+                }
+                if components.is_empty() {
+                    return Err(format!("unknown component type `{component}`"));
+                }
+                Component::OneOf(components)
+            }
+        };
+        let size = match same_as(size) {
+            Some(param) => Size::Same(param),
+            None if size.trim().eq_ignore_ascii_case("any") || size.trim().is_empty() => Size::Any,
+            None => Size::Fixed(size.trim().to_string()),
+        };
+        Ok(TypeDescription::Row {
+            template,
+            component,
+            size,
+        })
+    }
+}
+
+/// the type, if written, and the name of a parameter of a syntax block
+type Param<'a> = (Option<&'a str>, &'a str);
+
+/// the result type, the name and the parameters of a syntax block
+type Syntax<'a> = (&'a str, &'a str, Vec<Param<'a>>);
+
+/// a parameter of a syntax block like `in float3 x`, with an optional type
+fn parse_param(s: &str) -> NomResult<&str, Param<'_>> {
+    let qualifier = terminated(
+        nom::combinator::verify(identifier, |q: &str| QUALIFIERS.contains(&q)),
+        ws1,
+    );
+    let typed = separated_pair(identifier, ws1, identifier);
+    let parser = preceded(
+        many0(ws0_then(qualifier)),
+        alt((
+            map(typed, |(ty, name)| (Some(ty), name)),
+            map(identifier, |name| (None, name)),
+        )),
+    );
+    context(fn_name!(), ws0_then(parser))(s)
+}
+
+/// a syntax block like `ret lerp(x, y, s)` or `float3 cross(in float3 x, in float3 y)`.
+/// the result type is `ret` if the "Type Description" table describes it
+fn parse_syntax(s: &str) -> NomResult<&str, Syntax<'_>> {
+    let void = terminated(ws0_then(tag("void")), peek(ws0_then(tag(")"))));
+    let params = alt((
+        map(void, |_| vec![]),
+        separated_list0(ws0_then(tag(",")), parse_param),
+    ));
+    let parser = tuple((
+        identifier,
+        ws1_then(identifier),
+        delimited(ws0_then(tag("(")), params, ws0_then(tag(")"))),
+    ));
+    context(fn_name!(), parser)(s)
+}
+
+/// an entry of the table of the overview page
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OverviewEntry {
+    pub name: String,
+    /// the file name of the page of the intrinsic, e.g. `dx-graphics-hlsl-abs.md`
+    pub file: String,
+    pub description: String,
+    /// the "Minimum shader model" column, e.g. `"1"`, `"4"` or `"6.0"`, without footnote marks
+    pub min_shader_model: Option<String>,
+}
+
+/// the entries of the tables of the overview page `dx-graphics-hlsl-intrinsic-functions.md`
+/// whose first cell links to the page of an intrinsic
+pub fn overview(text: &str) -> Vec<OverviewEntry> {
+    lazy_static! {
+        static ref LINK: Regex = Regex::new(r"\]\(([^)#\s]+\.md)").unwrap();
+        static ref MODEL: Regex = Regex::new(r"^\d+(?:\.\d+)?").unwrap();
+    }
+    let mut entries = vec![];
+    let tables = markdown::tables(text);
+    for table in tables.iter().filter(|t| t.has_column("shader model")) {
+        let model_column = table.header.iter().position(|h| {
+            let h = h.to_lowercase();
+            h.contains("shader model")
+        });
+        for row in &table.rows {
+            let Some(file) = row.first().and_then(|c| LINK.captures(c)) else {
+                continue;
+            };
+            let model = model_column.and_then(|i| row.get(i));
+            let model = model.map(|m| markdown::strip_markup(m));
+            entries.push(OverviewEntry {
+                name: markdown::strip_markup(row[0]),
+                file: file[1].to_string(),
+                description: row
+                    .get(1)
+                    .map(|d| markdown::strip_markup(d))
+                    .unwrap_or_default(),
+                min_shader_model: model
+                    .and_then(|m| MODEL.find(&m).map(|m| m.as_str().to_string())),
+            });
+        }
+    }
+    entries
+}
+
+/// an intrinsic function, with one overload for each combination of the template types
+/// (scalar, vector or matrix) of its parameters
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Intrinsic {
+    pub name: String,
+    pub description: String,
+    pub min_shader_model: Option<String>,
+    /// the file name of the page the overloads were read from
+    pub file: String,
+    pub overloads: Vec<OverloadRow>,
+}
+
+impl Display for Intrinsic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for overload in &self.overloads {
+            write!(f, "{overload}")?;
+        }
+        Ok(())
+    }
+}
+
+/// an intrinsic of the overview whose page could not be read or parsed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnparsedIntrinsic {
+    pub name: String,
+    pub file: String,
+    pub reason: String,
+}
+
+impl Display for UnparsedIntrinsic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} ({}): {}", self.name, self.file, self.reason)
+    }
+}
+
+/// the "Type Description" table rows by lowercase parameter name, `ret` for the result
+fn type_descriptions(text: &str) -> Result<Vec<(String, TypeDescription)>, String> {
+    let tables = markdown::tables(text);
+    let Some(table) = tables.iter().find(|t| t.has_column("template type")) else {
+        return Ok(vec![]);
+    };
+    let mut descriptions = vec![];
+    for row in &table.rows {
+        let cells: Vec<_> = row.iter().map(|c| markdown::strip_markup(c)).collect();
+        let [name, template, component, rest @ ..] = cells.as_slice() else {
+            continue;
+        };
+        let size = rest.first().map_or("any", String::as_str);
+        let description = TypeDescription::from_cells(template, component, size)
+            .map_err(|e| format!("{name}: {e}"))?;
+        descriptions.push((name.to_lowercase(), description));
+    }
+    Ok(descriptions)
+}
+
+/// the syntax block of the page of intrinsic `name`: the first `type name(...)` of the text
+/// before the "Parameters" section
+fn find_syntax<'a>(text: &'a str, name: &str) -> Option<Syntax<'a>> {
+    let end = text.find("## Parameters").unwrap_or(text.len());
+    let text = &text[..end];
+    let starts = text.match_indices(name).filter_map(|(i, _)| {
+        let before = text[..i].trim_end_matches(|c: char| c.is_whitespace());
+        let word = before.rfind(|c: char| !(c.is_alphanumeric() || c == '_'));
+        Some(word.map_or(0, |w| w + 1)).filter(|&w| w < before.len())
+    });
+    starts
+        .filter_map(|start| parse_syntax(&text[start..]).ok())
+        .map(|(_, syntax)| syntax)
+        .find(|(_, found, _)| *found == name)
+}
+
+/// resolves the "same as" references of the type descriptions
+struct Signature<'a> {
+    /// the parameters and the result (named `ret`) with their lowercase names
+    descriptions: &'a [(String, TypeDescription)],
+}
+
+impl<'a> Signature<'a> {
+    fn get(&self, name: &str) -> Result<&'a TypeDescription, String> {
+        let description = self.descriptions.iter().find(|(n, _)| n == name);
+        description
+            .map(|(_, d)| d)
+            .ok_or_else(|| format!("no type description for `{name}`"))
+    }
+
+    /// follows the references `same` returns from `name`, guarding against cycles
+    fn follow(
+        &self,
+        name: &str,
+        same: impl Fn(&TypeDescription) -> Option<&String>,
+    ) -> Result<String, String> {
+        let mut name = name.to_string();
+        for _ in 0..=self.descriptions.len() {
+            match same(self.get(&name)?) {
+                Some(next) => name = next.clone(),
+                None => return Ok(name),
+            }
+        }
+        Err(format!("cyclic type description for `{name}`"))
+    }
+
+    /// the parameter whose template type `name` shares
+    fn template_root(&self, name: &str) -> Result<String, String> {
+        self.follow(name, |d| match d {
+            TypeDescription::Row {
+                template: Template::Same(next),
+                ..
+            } => Some(next),
+            _ => None,
+        })
+    }
+
+    /// the parameter whose component type `name` shares
+    fn component_root(&self, name: &str) -> Result<String, String> {
+        self.follow(name, |d| match d {
+            TypeDescription::Row {
+                component: Component::Same(next),
+                ..
+            } => Some(next),
+            _ => None,
+        })
+    }
+}
+
+/// true if `ty` is or contains the type parameter `param`
+fn mentions(ty: &Ty, param: &Ident) -> bool {
+    &ty.name == param || ty.param_tys().any(|t| mentions(t, param))
+}
+
+/// the overloads of a page, one for each combination of the shapes of the parameters
+/// not sharing the template type of another one
+fn overloads(
+    name: &str,
+    out: &str,
+    params: &[Param<'_>],
+    descriptions: Vec<(String, TypeDescription)>,
+) -> Result<Vec<OverloadRow>, String> {
+    let mut descriptions = descriptions;
+    for (ty, param) in params {
+        if let Some(ty) = ty {
+            let param = param.to_lowercase();
+            descriptions.retain(|(n, _)| *n != param);
+            descriptions.push((param, TypeDescription::Explicit(parse_type(ty))));
+        }
+    }
+    let out = match out {
+        "ret" => Some("ret".to_string()),
+        "void" => None,
+        ty => {
+            descriptions.retain(|(n, _)| n != "ret");
+            descriptions.push(("ret".to_string(), TypeDescription::Explicit(parse_type(ty))));
+            Some("ret".to_string())
+        }
+    };
+    let signature = Signature {
+        descriptions: &descriptions,
+    };
+    let names: Vec<String> = params
+        .iter()
+        .map(|(_, p)| p.to_lowercase())
+        .chain(out.clone())
+        .collect();
+
+    let mut roots: Vec<(String, Vec<Shape>)> = vec![];
+    let mut components: Vec<(String, Vec<String>)> = vec![];
+    for name in &names {
+        let root = signature.template_root(name)?;
+        if let TypeDescription::Row {
+            template: Template::Shapes(shapes),
+            ..
+        } = signature.get(&root)?
+        {
+            if !roots.iter().any(|(r, _)| *r == root) {
+                roots.push((root, shapes.clone()));
+            }
+        }
+        let root = signature.component_root(name)?;
+        if let TypeDescription::Row {
+            component: Component::OneOf(types),
+            ..
+        } = signature.get(&root)?
+        {
+            if types.len() > 1 && !components.iter().any(|(r, _)| *r == root) {
+                components.push((root, types.clone()));
+            }
+        }
+    }
+
+    let mut combinations: Vec<Vec<Shape>> = vec![vec![]];
+    for (_, shapes) in &roots {
+        combinations = combinations
+            .iter()
+            .flat_map(|c| shapes.iter().map(move |s| [c.clone(), vec![*s]].concat()))
+            .collect();
+    }
+
+    let mut rows = vec![];
+    for combination in combinations {
+        let mut sizes: Vec<String> = vec![];
+        let mut ty_of = |name: &str| -> Result<Ty, String> {
+            let root = signature.template_root(name)?;
+            let (i, shape) = match signature.get(&root)? {
+                TypeDescription::Explicit(ty) => return Ok(ty.clone()),
+                TypeDescription::Row { .. } => {
+                    let i = roots.iter().position(|(r, _)| *r == root).unwrap();
+                    (i, combination[i])
+                }
+            };
+            let component_root = signature.component_root(name)?;
+            let component = match signature.get(&component_root)? {
+                TypeDescription::Explicit(ty) => match ty.params.first() {
+                    Some(TemplateArg::Type(component)) => component.clone(),
+                    _ => ty.clone(),
+                },
+                TypeDescription::Row {
+                    component: Component::OneOf(types),
+                    ..
+                } => match components.iter().position(|(r, _)| *r == component_root) {
+                    Some(i) => Ty::new(component_param(i).as_str().into(), vec![]),
+                    None => Ty::new(types[0].as_str().into(), vec![]),
+                },
+                TypeDescription::Row {
+                    component: Component::Same(_),
+                    ..
+                } => return Err(format!("unresolved component type of `{name}`")),
+            };
+            let size = match signature.get(name)? {
+                TypeDescription::Row { size, .. } => size,
+                TypeDescription::Explicit(_) => &Size::Any,
+            };
+            let [vector, rows, columns] = match size {
+                Size::Same(other) => {
+                    let other = signature.template_root(other)?;
+                    let other = roots.iter().position(|(r, _)| *r == other);
+                    size_params(other.unwrap_or(i)).map(Some)
+                }
+                Size::Any => size_params(i).map(Some),
+                Size::Fixed(_) => [None, None, None],
+            };
+            let mut arg = |param: Option<String>, fixed: Option<&str>| match param {
+                Some(param) => {
+                    if !sizes.contains(&param) {
+                        sizes.push(param.clone());
+                    }
+                    TemplateArg::Type(Ty::new(param.as_str().into(), vec![]))
+                }
+                None => size_arg(fixed.unwrap_or("1")),
+            };
+            let fixed = match size {
+                Size::Fixed(fixed) => Some(fixed.as_str()),
+                _ => None,
+            };
+            let (fixed_rows, fixed_columns) = match fixed.and_then(|f| f.split_once('x')) {
+                Some((r, c)) => (Some(r.trim()), Some(c.trim())),
+                None => (fixed, fixed),
+            };
+            Ok(match shape {
+                Shape::Scalar => component,
+                Shape::Vector => Ty::with_args(
+                    "vector".into(),
+                    vec![TemplateArg::Type(component), arg(vector, fixed)],
+                ),
+                Shape::Matrix => Ty::with_args(
+                    "matrix".into(),
+                    vec![
+                        TemplateArg::Type(component),
+                        arg(rows, fixed_rows),
+                        arg(columns, fixed_columns),
+                    ],
+                ),
+            })
+        };
+        let args = params
+            .iter()
+            .map(|(_, p)| Ok(((*p).into(), ty_of(&p.to_lowercase())?)))
+            .collect::<Result<Vec<(Ident, Ty)>, String>>()?;
+        let out = match &out {
+            Some(out) => ty_of(out)?,
+            None => Ty::flatten(None),
+        };
+        let union = |type_param: &str, types: &[&str]| Bound {
+            type_param: type_param.into(),
+            bound_kind: BoundKind::Union(UnionBound {
+                is_one_of: types.iter().map(|t| Ty::new((*t).into(), vec![])).collect(),
+            }),
+            span: Span::default(),
+        };
+        let mut bounds = vec![];
+        for (i, (_, types)) in components.iter().enumerate() {
+            let types: Vec<&str> = types.iter().map(String::as_str).collect();
+            bounds.push(union(&component_param(i), &types));
+        }
+        for size in &sizes {
+            bounds.push(union(size, SIZES));
+        }
+        let fn_decl = FnDecl {
+            name: name.into(),
+            args,
+            out,
+            span: Span::default(),
+        };
+        // a type parameter only used by other shapes is not a parameter of this overload
+        let tys: Vec<&Ty> = fn_decl.args.iter().map(|(_, ty)| ty).collect();
+        let tys = [tys, vec![&fn_decl.out]].concat();
+        bounds.retain(|b| tys.iter().any(|ty| mentions(ty, &b.type_param)));
+        rows.push(OverloadRow {
+            algorithm_attr: name.to_string(),
+            parametrization: Parametrization(bounds),
+            attributes: vec![],
+            fn_decl,
+            span: Span::default(),
+        });
+    }
+    Ok(rows)
+}
+
+impl Intrinsic {
+    /// reads the overloads of the page of an intrinsic from its syntax block and
+    /// its "Type Description" table
+    pub fn parse_page(entry: &OverviewEntry, text: &str) -> Result<Self, String> {
+        let text = markdown::without_front_matter(text);
+        let (out, _, params) =
+            find_syntax(text, &entry.name).ok_or_else(|| "no syntax block".to_string())?;
+        let overloads = overloads(&entry.name, out, &params, type_descriptions(text)?)?;
+        Ok(Intrinsic {
+            name: entry.name.clone(),
+            description: entry.description.clone(),
+            min_shader_model: entry.min_shader_model.clone(),
+            file: entry.file.clone(),
+            overloads,
+        })
+    }
+}
+
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_intrinsics() {
+        let overview_text = r#"---
+title: Intrinsic Functions
+ms.date: 08/27/2019
+---
+
+# Intrinsic Functions
+
+| Name | Description | Minimum shader model |
+|------|-------------|----------------------|
+| [**abs**](dx-graphics-hlsl-abs.md) | Absolute value (per component). | 1¹ |
+| [**cross**](dx-graphics-hlsl-cross.md) | Returns the cross product of two 3D vectors. | 1¹ |
+| [**isnan**](dx-graphics-hlsl-isnan.md) | Returns true if x is NAN or QNAN. | 2 |
+| [**WaveGetLaneCount**](wavegetlanecount.md) | Returns the number of lanes in a wave. | 6.0 |
+"#;
+        let abs_text = r#"---
+title: abs
+---
+
+# abs
+
+Absolute value (per component).
+
+| ret abs(x) |
+|------------|
+
+## Parameters
+
+| Item | Description |
+|------|-------------|
+| <span id="x"></span><span id="X"></span>*x*<br/> | \[in\] The specified value.<br/> |
+
+## Type Description
+
+| Name  | [**Template Type**](dx-graphics-hlsl-intrinsic-functions.md) | [**Component Type**](dx-graphics-hlsl-intrinsic-functions.md) | Size |
+|-------|----------------------------------|-----------------------------|------|
+| *x*   | [**scalar**](x.md), **vector**, or **matrix** | [**float**](y.md), [**int**](y.md) | any |
+| *ret* | same as input *x* | same as input *x* | same dimensions as input *x* |
+"#;
+        let cross_text = r#"# cross
+
+Returns the cross product of two floating-point, 3D vectors.
+
+```syntax
+float3 cross(
+  in float3 x,
+  in float3 y
+);
+```
+"#;
+        let isnan_text = r#"# isnan
+
+| ret isnan(x) |
+|--------------|
+
+## Type Description
+
+| Name | Template Type | Component Type | Size |
+|------|---------------|----------------|------|
+| *x* | scalar, vector, or matrix | float | any |
+| *ret* | same as input *x* | bool | same dimensions as input *x* |
+"#;
+        let entries = overview(overview_text);
+        let names: Vec<_> = entries.iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(names, ["abs", "cross", "isnan", "WaveGetLaneCount"]);
+        assert_eq!(entries[0].file, "dx-graphics-hlsl-abs.md");
+        assert_eq!(entries[0].min_shader_model.as_deref(), Some("1"));
+        assert_eq!(entries[3].min_shader_model.as_deref(), Some("6.0"));
+
+        let abs = Intrinsic::parse_page(&entries[0], abs_text).unwrap();
+        assert_eq!(abs.description, "Absolute value (per component).");
+        let decls: Vec<_> = abs.overloads.iter().map(|o| &o.fn_decl).collect();
+        let decls: Vec<_> = decls
+            .iter()
+            .map(|d| format!("{} -> {}", d.args[0].1, d.out))
+            .collect();
+        assert_eq!(
+            decls,
+            [
+                "T -> T",
+                "vector<T, N> -> vector<T, N>",
+                "matrix<T, R, C> -> matrix<T, R, C>"
+            ]
+        );
+        assert_eq!(
+            abs.overloads[1].parametrization.to_string(),
+            "    T: is float | int,\n    N: is 1 | 2 | 3 | 4\n"
+        );
+        assert_eq!(abs.overloads[0].instances().len(), 2);
+        assert_eq!(abs.overloads[1].instances().len(), 8);
+        assert_eq!(abs.overloads[2].instances().len(), 32);
+        let float3 = abs.overloads[1]
+            .instances()
+            .into_iter()
+            .map(|d| type_name(&d.out));
+        assert!(float3.collect::<Vec<_>>().contains(&"int3".to_string()));
+
+        let cross = Intrinsic::parse_page(&entries[1], cross_text).unwrap();
+        assert_eq!(cross.overloads.len(), 1);
+        let decl = &cross.overloads[0].fn_decl;
+        assert_eq!(decl.args[1].0.as_str(), "y");
+        assert_eq!(type_name(&decl.args[1].1), "float3");
+        assert_eq!(type_name(&decl.out), "float3");
+
+        let isnan = Intrinsic::parse_page(&entries[2], isnan_text).unwrap();
+        let matrix = &isnan.overloads[2];
+        assert_eq!(matrix.fn_decl.out.to_string(), "matrix<bool, R, C>");
+        assert_eq!(matrix.instances().len(), 16);
+        let instance = &matrix.instances()[5];
+        assert_eq!(type_name(&instance.args[0].1), "float2x2");
+        assert_eq!(type_name(&instance.out), "bool2x2");
+
+        assert_eq!(
+            parse_type("min16float4x4").to_string(),
+            "matrix<min16float, 4, 4>"
+        );
+        assert_eq!(parse_type("uint").to_string(), "uint");
+        assert!(Intrinsic::parse_page(&entries[3], "# WaveGetLaneCount\n").is_err());
+    }
+}
@@ -0,0 +1,113 @@
+use std::path::Path;
+
+use crate::{
+    diagnostic::SpecError,
+    grammar::GrammarRule,
+    markdown,
+    spec::{BuiltinSignature, ShaderLangSpec},
+};
+
+use self::intrinsics::{Intrinsic, UnparsedIntrinsic};
+
+pub mod intrinsics;
+
+/// the directory of the HLSL reference in a checkout of https://github.com/MicrosoftDocs/win32
+pub const DOCS_DIR: &str = "desktop-src/direct3dhlsl";
+
+/// the page listing the intrinsic functions with their minimum shader model
+pub const OVERVIEW_FILE: &str = "dx-graphics-hlsl-intrinsic-functions.md";
+
+pub struct HlslSpec {
+    /// the overview page
+    pub text: String,
+    pub intrinsics: Vec<Intrinsic>,
+    /// the intrinsics of the overview whose page is missing or could not be parsed,
+    /// e.g. ones operating on textures
+    pub unparsed_intrinsics: Vec<UnparsedIntrinsic>,
+    /// the `ms.date` of the overview page, if any
+    pub revision: Option<String>,
+}
+
+impl HlslSpec {
+    /// reads the intrinsics from a local checkout of the Win32 documentation
+    pub fn from_checkout(dir: impl AsRef<Path>) -> Result<Self, SpecError> {
+        Self::from_docs_dir(dir.as_ref().join(DOCS_DIR))
+    }
+
+    /// reads the overview page and the pages of the intrinsics it links to from `dir`
+    pub fn from_docs_dir(dir: impl AsRef<Path>) -> Result<Self, SpecError> {
+        let dir = dir.as_ref();
+        let path = dir.join(OVERVIEW_FILE);
+        let text = std::fs::read_to_string(&path).map_err(|e| SpecError::Read {
+            path: path.display().to_string(),
+            message: e.to_string(),
+        })?;
+        Ok(Self::parse_docs(&text, |file| {
+            std::fs::read_to_string(dir.join(file)).ok()
+        }))
+    }
+
+    /// parses the overview page, reading the page of each intrinsic by its file name with `read`
+    pub fn parse_docs(text: &str, read: impl Fn(&str) -> Option<String>) -> Self {
+        let mut intrinsics = vec![];
+        let mut unparsed_intrinsics = vec![];
+        for entry in intrinsics::overview(text) {
+            let parsed = match read(&entry.file) {
+                Some(page) => Intrinsic::parse_page(&entry, &page),
+                None => Err("page not found".to_string()),
+            };
+            match parsed {
+                Ok(intrinsic) => intrinsics.push(intrinsic),
+                Err(reason) => unparsed_intrinsics.push(UnparsedIntrinsic {
+                    name: entry.name,
+                    file: entry.file,
+                    reason,
+                }),
+            }
+        }
+        HlslSpec {
+            text: text.to_string(),
+            intrinsics,
+            unparsed_intrinsics,
+            revision: markdown::front_matter(text, "ms.date").map(str::to_string),
+        }
+    }
+
+    pub fn intrinsic(&self, name: &str) -> Option<&Intrinsic> {
+        self.intrinsics.iter().find(|i| i.name == name)
+    }
+}
+
+impl ShaderLangSpec for HlslSpec {
+    fn language(&self) -> &'static str {
+        "HLSL"
+    }
+
+    fn revision(&self) -> Option<&str> {
+        self.revision.as_deref()
+    }
+
+    fn source_text(&self) -> &str {
+        &self.text
+    }
+
+    fn builtin_functions(&self) -> Vec<BuiltinSignature> {
+        let overloads = self.intrinsics.iter().flat_map(|i| &i.overloads);
+        overloads
+            .flat_map(|o| o.signatures(intrinsics::type_name))
+            .collect()
+    }
+
+    /// the intrinsic pages do not list keywords
+    fn keywords(&self) -> Vec<&str> {
+        vec![]
+    }
+
+    fn reserved_words(&self) -> Vec<&str> {
+        vec![]
+    }
+
+    fn grammar(&self) -> &[GrammarRule] {
+        &[]
+    }
+}
@@ -4,9 +4,21 @@ pub mod misc;
 use misc::*;
 use std::error::Error;
 
+pub mod asciidoc;
+pub mod bikeshed;
+pub mod diagnostic;
+pub mod glsl;
+pub mod grammar;
+pub mod hlsl;
+pub mod mapping;
+pub mod markdown;
 pub mod nom_prelude;
+pub mod span;
+pub mod spec;
+pub mod spirv;
+pub mod webgpu;
 pub mod wgsl;
 
-pub fn wgsl_download_and_parse() -> Result<wgsl::WgslSpec, Box<dyn Error>> {
+pub fn wgsl_download_and_parse() -> Result<wgsl::WgslSpec, diagnostic::SpecError> {
     wgsl::WgslSpec::from_download()
 }
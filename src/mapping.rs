@@ -0,0 +1,582 @@
+use std::{collections::BTreeSet, fmt::Display};
+
+use lazy_static::lazy_static;
+use regex::Regex;
+
+use crate::spec::{ShaderLangSpec, SignatureType};
+
+/// names which mean the same builtin in different languages, after [`canonical_name`]
+/// lowercased them and removed their underscores and prefixes. the first name of a group is its key
+const ALIASES: &[&[&str]] = &[
+    &["inversesqrt", "rsqrt"],
+    &["fract", "frac"],
+    &["mix", "lerp"],
+    &["fma", "mad"],
+    &["inverse", "matrixinverse"],
+    &["dpdx", "dfdx", "ddx"],
+    &["dpdy", "dfdy", "ddy"],
+    &["dpdxfine", "dfdxfine", "ddxfine"],
+    &["dpdyfine", "dfdyfine", "ddyfine"],
+    &["dpdxcoarse", "dfdxcoarse", "ddxcoarse"],
+    &["dpdycoarse", "dfdycoarse", "ddycoarse"],
+    &["countonebits", "bitcount", "countbits"],
+    &["reversebits", "bitfieldreverse", "bitreverse"],
+    &[
+        "firstleadingbit",
+        "findmsb",
+        "firstbithigh",
+        "findsmsb",
+        "findumsb",
+    ],
+    &["firsttrailingbit", "findlsb", "firstbitlow", "findilsb"],
+    &[
+        "extractbits",
+        "bitfieldextract",
+        "bitfieldsextract",
+        "bitfielduextract",
+    ],
+    &["insertbits", "bitfieldinsert"],
+    &[
+        "workgroupbarrier",
+        "barrier",
+        "groupmemorybarrierwithgroupsync",
+        "controlbarrier",
+    ],
+    &["pack4x8snorm", "packsnorm4x8"],
+    &["pack4x8unorm", "packunorm4x8"],
+    &["pack2x16snorm", "packsnorm2x16"],
+    &["pack2x16unorm", "packunorm2x16"],
+    &["pack2x16float", "packhalf2x16"],
+    &["unpack4x8snorm", "unpacksnorm4x8"],
+    &["unpack4x8unorm", "unpackunorm4x8"],
+    &["unpack2x16snorm", "unpacksnorm2x16"],
+    &["unpack2x16unorm", "unpackunorm2x16"],
+    &["unpack2x16float", "unpackhalf2x16"],
+    &["atomicadd", "interlockedadd", "atomiciadd"],
+    &["atomicsub", "atomicisub"],
+    &["atomicmin", "interlockedmin", "atomicsmin", "atomicumin"],
+    &["atomicmax", "interlockedmax", "atomicsmax", "atomicumax"],
+    &["atomicand", "interlockedand"],
+    &["atomicor", "interlockedor"],
+    &["atomicxor", "interlockedxor"],
+    &["atomicexchange", "interlockedexchange"],
+];
+
+/// the name builtins of all languages are matched by, e.g. `inversesqrt` for `inverseSqrt`,
+/// `rsqrt` and `InverseSqrt`. the `Op` of SPIR-V instructions and the `F`, `S`, `U`, `I` or `N`
+/// of their typed variants are removed, e.g. `OpFMul` becomes `mul`
+pub fn canonical_name(language: &str, name: &str) -> String {
+    let mut name = name;
+    if language == "SPIR-V" {
+        name = name.strip_prefix("Op").unwrap_or(name);
+        let mut chars = name.chars();
+        let typed = matches!(chars.next(), Some('F' | 'S' | 'U' | 'I' | 'N'));
+        if typed && chars.next().is_some_and(|c| c.is_ascii_uppercase()) {
+            name = &name[1..];
+        }
+    }
+    let name = name.to_lowercase().replace('_', "");
+    let group = ALIASES.iter().find(|group| group.contains(&name.as_str()));
+    group.map_or(name, |group| group[0].to_string())
+}
+
+/// a coarse class of the type of an argument, which is comparable across languages
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum TypeClass {
+    Float,
+    SignedInt,
+    UnsignedInt,
+    Bool,
+    /// e.g. a texture, pointer or array
+    Other,
+}
+
+impl Display for TypeClass {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            TypeClass::Float => "float",
+            TypeClass::SignedInt => "signed int",
+            TypeClass::UnsignedInt => "unsigned int",
+            TypeClass::Bool => "bool",
+            TypeClass::Other => "other",
+        };
+        write!(f, "{name}")
+    }
+}
+
+impl TypeClass {
+    /// the class of the components of a scalar, vector or matrix type of any language,
+    /// e.g. [`TypeClass::Float`] for `vec3<f32>`, `dmat2` and `float3`
+    pub fn of(ty: &SignatureType) -> Self {
+        lazy_static! {
+            static ref GLSL_VECTOR: Regex = Regex::new(r"^([dibu]?)(?:vec|mat)\d").unwrap();
+        }
+        let mut names = ty.names.iter().map(String::as_str);
+        let mut name = names.next().unwrap_or_default();
+        let is_vector = ["vec", "mat", "vector", "matrix"]
+            .iter()
+            .any(|v| name.starts_with(v));
+        if is_vector {
+            name = names.next().unwrap_or(name);
+        }
+        if let Some(captures) = GLSL_VECTOR.captures(name) {
+            return match &captures[1] {
+                "i" => TypeClass::SignedInt,
+                "u" => TypeClass::UnsignedInt,
+                "b" => TypeClass::Bool,
+                _ => TypeClass::Float,
+            };
+        }
+        let name = name.to_lowercase();
+        let has = |words: &[&str]| words.iter().any(|w| name.contains(w));
+        match () {
+            _ if has(&["uint", "u32", "u64", "dword"]) => TypeClass::UnsignedInt,
+            _ if has(&["bool"]) => TypeClass::Bool,
+            _ if has(&["float", "half", "double", "f16", "f32", "f64"]) => TypeClass::Float,
+            _ if has(&["int", "i32", "i64"]) => TypeClass::SignedInt,
+            _ => TypeClass::Other,
+        }
+    }
+}
+
+/// the parameters of one overload of a builtin
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SignatureShape {
+    /// the lowercase parameter names
+    pub params: Vec<String>,
+    /// the classes of the types each parameter takes in the instances of the overload.
+    /// empty if they are not known, e.g. for SPIR-V instructions
+    pub classes: Vec<BTreeSet<TypeClass>>,
+}
+
+impl SignatureShape {
+    /// true if the argument classes of `self` and `other` overlap at each position
+    fn same_types(&self, other: &SignatureShape) -> bool {
+        let (a, b) = (&self.classes, &other.classes);
+        a.is_empty()
+            || b.is_empty()
+            || a.iter()
+                .zip(b)
+                .all(|(a, b)| a.intersection(b).next().is_some())
+    }
+
+    /// true if the parameters of `self` and `other` take the same classes of types, but in a
+    /// different order. the names are only compared if the classes of either are not known,
+    /// since languages name parameters differently, e.g. `e1` and `e2` in WGSL and `x` and `y` in GLSL
+    fn is_permutation_of(&self, other: &SignatureShape) -> bool {
+        let (a, b) = (&self.classes, &other.classes);
+        if !a.is_empty() && !b.is_empty() {
+            return !self.same_types(other) && overlap_in_some_order(a, b);
+        }
+        let mut a = self.params.clone();
+        let mut b = other.params.clone();
+        let reordered = a != b;
+        a.sort();
+        b.sort();
+        reordered && a == b
+    }
+}
+
+/// true if every class set of `a` overlaps a different one of `b`
+fn overlap_in_some_order(a: &[BTreeSet<TypeClass>], b: &[BTreeSet<TypeClass>]) -> bool {
+    let Some((first, rest)) = a.split_first() else {
+        return b.is_empty();
+    };
+    (0..b.len()).any(|i| {
+        let mut b = b.to_vec();
+        let other = b.remove(i);
+        first.intersection(&other).next().is_some() && overlap_in_some_order(rest, &b)
+    })
+}
+
+/// a builtin function or instruction of a language, with the shapes of its overloads
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Builtin {
+    /// e.g. `"WGSL"`
+    pub language: &'static str,
+    /// the name as written in the language, e.g. `inverseSqrt` or `InverseSqrt`
+    pub name: String,
+    pub signatures: Vec<SignatureShape>,
+}
+
+impl Display for Builtin {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}", self.language, self.name)
+    }
+}
+
+impl Builtin {
+    /// the builtin functions of a spec grouped by name. the instances with the same parameter
+    /// names are merged into one signature shape
+    pub fn from_spec(spec: &dyn ShaderLangSpec) -> Vec<Builtin> {
+        let mut builtins: Vec<Builtin> = vec![];
+        for signature in spec.builtin_functions() {
+            let params: Vec<_> = signature
+                .params
+                .iter()
+                .map(|(p, _)| p.to_lowercase())
+                .collect();
+            let classes: Option<Vec<_>> = (signature.params.iter())
+                .map(|(_, ty)| ty.as_ref().map(TypeClass::of))
+                .collect();
+            let index = builtins.iter().position(|b| b.name == signature.name);
+            let index = index.unwrap_or_else(|| {
+                builtins.push(Builtin {
+                    language: spec.language(),
+                    name: signature.name.clone(),
+                    signatures: vec![],
+                });
+                builtins.len() - 1
+            });
+            let shapes = &mut builtins[index].signatures;
+            let Some(shape) = shapes.iter_mut().find(|s| s.params == params) else {
+                let classes = classes.unwrap_or_default().into_iter();
+                shapes.push(SignatureShape {
+                    params,
+                    classes: classes.map(|c| BTreeSet::from([c])).collect(),
+                });
+                continue;
+            };
+            // the classes stay unknown once an instance without types was merged
+            match classes {
+                Some(_) if shape.classes.is_empty() => {}
+                Some(classes) => {
+                    let merged = shape.classes.iter_mut().zip(classes);
+                    merged.for_each(|(set, class)| _ = set.insert(class));
+                }
+                None => shape.classes.clear(),
+            }
+        }
+        builtins
+    }
+
+    /// how `self` corresponds to `other`, using the pair of overloads with the fewest differences
+    pub fn compare(&self, other: &Builtin) -> Equivalence {
+        let mut fewest: Option<Vec<Difference>> = None;
+        for a in &self.signatures {
+            for b in &other.signatures {
+                let mut differences = vec![];
+                if a.params.len() != b.params.len() {
+                    differences.push(Difference::ArgumentCount);
+                } else {
+                    if a.is_permutation_of(b) {
+                        differences.push(Difference::ArgumentOrder);
+                    } else if !a.same_types(b) {
+                        differences.push(Difference::ArgumentTypes);
+                    }
+                }
+                if differences.is_empty() {
+                    return Equivalence::Direct;
+                }
+                if fewest.as_ref().is_none_or(|f| differences.len() < f.len()) {
+                    fewest = Some(differences);
+                }
+            }
+        }
+        Equivalence::Near(fewest.unwrap_or(vec![Difference::ArgumentCount]))
+    }
+}
+
+/// a way two matched builtins differ
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Difference {
+    /// no overloads have the same number of parameters
+    ArgumentCount,
+    /// the parameters take the same classes of types in a different order
+    ArgumentOrder,
+    /// the parameters take different kinds of types, e.g. only floats and only integers
+    ArgumentTypes,
+}
+
+impl Display for Difference {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Difference::ArgumentCount => "argument count",
+            Difference::ArgumentOrder => "argument order",
+            Difference::ArgumentTypes => "argument types",
+        };
+        write!(f, "{name}")
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Equivalence {
+    /// an overload of one builtin can be lowered to an overload of the other one as is
+    Direct,
+    Near(Vec<Difference>),
+}
+
+/// the builtins of all languages with the same [`canonical_name`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Mapping {
+    /// e.g. `inversesqrt`
+    pub key: String,
+    pub builtins: Vec<Builtin>,
+    /// the comparison of each pair of builtins of different languages, by their indices in `builtins`
+    pub pairs: Vec<(usize, usize, Equivalence)>,
+}
+
+impl Mapping {
+    /// the languages having a builtin of this mapping, in the order they were given
+    pub fn languages(&self) -> Vec<&'static str> {
+        let mut languages = vec![];
+        for builtin in &self.builtins {
+            if !languages.contains(&builtin.language) {
+                languages.push(builtin.language);
+            }
+        }
+        languages
+    }
+
+    /// true if every two languages have a pair of directly equivalent builtins
+    pub fn is_direct(&self) -> bool {
+        let languages = self.languages();
+        let direct = |a: &str, b: &str| {
+            self.pairs.iter().any(|(i, j, equivalence)| {
+                let pair = [self.builtins[*i].language, self.builtins[*j].language];
+                *equivalence == Equivalence::Direct && (pair == [a, b] || pair == [b, a])
+            })
+        };
+        languages.len() > 1
+            && languages
+                .iter()
+                .enumerate()
+                .all(|(i, a)| languages[i + 1..].iter().all(|b| direct(a, b)))
+    }
+
+    /// true if the builtins are all of the same language
+    pub fn is_unmatched(&self) -> bool {
+        self.languages().len() == 1
+    }
+}
+
+/// the builtins of several languages matched by name
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MappingReport {
+    /// sorted by key
+    pub mappings: Vec<Mapping>,
+}
+
+impl MappingReport {
+    /// matches `builtins`, e.g. the [`Builtin::from_spec`] of several specs
+    pub fn new(builtins: impl IntoIterator<Item = Builtin>) -> Self {
+        let mut mappings: Vec<Mapping> = vec![];
+        for builtin in builtins {
+            let key = canonical_name(builtin.language, &builtin.name);
+            match mappings.iter_mut().find(|m| m.key == key) {
+                Some(mapping) => mapping.builtins.push(builtin),
+                None => mappings.push(Mapping {
+                    key,
+                    builtins: vec![builtin],
+                    pairs: vec![],
+                }),
+            }
+        }
+        for mapping in &mut mappings {
+            let builtins = &mapping.builtins;
+            for (i, a) in builtins.iter().enumerate() {
+                for (j, b) in builtins.iter().enumerate().skip(i + 1) {
+                    if a.language != b.language {
+                        mapping.pairs.push((i, j, a.compare(b)));
+                    }
+                }
+            }
+        }
+        mappings.sort_by(|a, b| a.key.cmp(&b.key));
+        MappingReport { mappings }
+    }
+
+    /// builtins of several languages which can be lowered into each other
+    pub fn direct(&self) -> impl Iterator<Item = &Mapping> {
+        self.mappings.iter().filter(|m| m.is_direct())
+    }
+
+    /// builtins of several languages which differ in their arguments
+    pub fn near(&self) -> impl Iterator<Item = &Mapping> {
+        let matched = self.mappings.iter().filter(|m| !m.is_unmatched());
+        matched.filter(|m| !m.is_direct())
+    }
+
+    /// builtins without a counterpart in another language
+    pub fn unmatched(&self) -> impl Iterator<Item = &Mapping> {
+        self.mappings.iter().filter(|m| m.is_unmatched())
+    }
+}
+
+impl Display for MappingReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let names = |m: &Mapping| {
+            let names = m.builtins.iter().map(Builtin::to_string);
+            names.collect::<Vec<_>>().join(", ")
+        };
+        writeln!(f, "direct equivalents:")?;
+        for mapping in self.direct() {
+            writeln!(f, "    {}: {}", mapping.key, names(mapping))?;
+        }
+        writeln!(f, "near equivalents:")?;
+        for mapping in self.near() {
+            writeln!(f, "    {}: {}", mapping.key, names(mapping))?;
+            for (i, j, equivalence) in &mapping.pairs {
+                if let Equivalence::Near(differences) = equivalence {
+                    let differences = differences.iter().map(Difference::to_string);
+                    let differences = differences.collect::<Vec<_>>().join(", ");
+                    let (a, b) = (&mapping.builtins[*i], &mapping.builtins[*j]);
+                    writeln!(f, "        {a} / {b}: {differences}")?;
+                }
+            }
+        }
+        writeln!(f, "without counterpart:")?;
+        for mapping in self.unmatched() {
+            writeln!(f, "    {}", names(mapping))?;
+        }
+        Ok(())
+    }
+}
+
+mod tests {
+    use super::*;
+    use crate::{spirv::SpirvSpec, wgsl::WgslSpec};
+
+    #[test]
+    fn test_mapping() {
+        let shape = |params: &[&str], classes: &[&[TypeClass]]| SignatureShape {
+            params: params.iter().map(|p| p.to_string()).collect(),
+            classes: classes
+                .iter()
+                .map(|c| c.iter().copied().collect())
+                .collect(),
+        };
+        let builtin = |language, name: &str, signatures| Builtin {
+            language,
+            name: name.to_string(),
+            signatures,
+        };
+        use TypeClass::*;
+        let builtins = vec![
+            builtin("WGSL", "inverseSqrt", vec![shape(&["e"], &[&[Float]])]),
+            builtin(
+                "WGSL",
+                "fma",
+                vec![shape(&["e1", "e2", "e3"], &[&[Float], &[Float], &[Float]])],
+            ),
+            builtin("WGSL", "quantizeToF16", vec![shape(&["e"], &[&[Float]])]),
+            builtin(
+                "WGSL",
+                "ldexp",
+                vec![shape(&["e1", "e2"], &[&[Float], &[SignedInt]])],
+            ),
+            builtin(
+                "WGSL",
+                "pow",
+                vec![shape(&["e1", "e2"], &[&[Float], &[Float]])],
+            ),
+            builtin(
+                "GLSL",
+                "pow",
+                vec![shape(&["x", "y"], &[&[Float], &[Float]])],
+            ),
+            builtin("GLSL", "inversesqrt", vec![shape(&["x"], &[&[Float]])]),
+            builtin(
+                "GLSL",
+                "ldexp",
+                vec![shape(&["exp", "x"], &[&[SignedInt], &[Float]])],
+            ),
+            builtin("HLSL", "rsqrt", vec![shape(&["x"], &[&[Float]])]),
+            builtin(
+                "HLSL",
+                "mad",
+                vec![shape(
+                    &["m", "a", "b"],
+                    &[&[SignedInt], &[SignedInt], &[SignedInt]],
+                )],
+            ),
+            builtin("SPIR-V", "InverseSqrt", vec![shape(&["x"], &[])]),
+            builtin("SPIR-V", "Fma", vec![shape(&["a", "b"], &[])]),
+        ];
+        let report = MappingReport::new(builtins);
+        let direct: Vec<_> = report.direct().map(|m| m.key.as_str()).collect();
+        assert_eq!(direct, ["inversesqrt", "pow"]);
+        assert_eq!(report.direct().next().unwrap().languages().len(), 4);
+        let near: Vec<_> = report.near().map(|m| m.key.as_str()).collect();
+        assert_eq!(near, ["fma", "ldexp"]);
+        let ldexp = &report.near().nth(1).unwrap().pairs[0].2;
+        assert_eq!(*ldexp, Equivalence::Near(vec![Difference::ArgumentOrder]));
+        let unmatched: Vec<_> = report.unmatched().map(|m| m.key.as_str()).collect();
+        assert_eq!(unmatched, ["quantizetof16"]);
+        assert!(report
+            .to_string()
+            .contains("        WGSL fma / SPIR-V Fma: argument count\n"));
+
+        assert_eq!(canonical_name("SPIR-V", "OpFMul"), "mul");
+        assert_eq!(canonical_name("SPIR-V", "FindUMsb"), "firstleadingbit");
+        assert_eq!(canonical_name("SPIR-V", "Floor"), "floor");
+        assert_eq!(canonical_name("HLSL", "ddx_fine"), "dpdxfine");
+        assert_eq!(canonical_name("GLSL", "dFdx"), "dpdx");
+
+        let ty = |names: &[&str]| SignatureType {
+            text: names[0].to_string(),
+            names: names.iter().map(|n| n.to_string()).collect(),
+        };
+        let vec3_f32 = ty(&["vec3", "f32"]);
+        assert_eq!(TypeClass::of(&vec3_f32), Float);
+        assert_eq!(TypeClass::of(&ty(&["uvec2"])), UnsignedInt);
+        assert_eq!(TypeClass::of(&ty(&["min16uint"])), UnsignedInt);
+        assert_eq!(TypeClass::of(&ty(&["i32"])), SignedInt);
+        assert_eq!(TypeClass::of(&ty(&["sampler2D"])), Other);
+    }
+
+    #[test]
+    fn test_from_spec() {
+        let wgsl = r#"<table class='data builtin'>
+<tr algorithm="sqrt">
+    <td>|T| is [=f32=] or vec|N|&lt;[=f32=]&gt;
+    <td><xmp highlight=rust>@const @must_use fn sqrt(e: T ) -> T</xmp>
+</table>"#;
+        let (_, wgsl) = WgslSpec::parse_bs(wgsl).unwrap();
+        let signatures = wgsl.builtin_functions();
+        assert_eq!(signatures.len(), 4);
+        assert_eq!(signatures[1].to_string(), "sqrt(e: vec2<f32>) -> vec2<f32>");
+        assert_eq!(wgsl.types(), ["f32", "vec2", "vec3", "vec4"]);
+
+        let core = r#"{
+  "magic_number": "0x07230203", "major_version": 1, "minor_version": 6, "revision": 4,
+  "instructions": [
+    {
+      "opname": "OpFMul", "class": "Arithmetic", "opcode": 133,
+      "operands": [
+        { "kind": "IdResultType" }, { "kind": "IdResult" },
+        { "kind": "IdRef", "name": "'Operand 1'" }, { "kind": "IdRef", "name": "'Operand 2'" }
+      ]
+    },
+    {
+      "opname": "OpLoad", "class": "Memory", "opcode": 61,
+      "operands": [ { "kind": "IdResultType" }, { "kind": "IdResult" }, { "kind": "IdRef", "name": "'Pointer'" } ]
+    }
+  ]
+}"#;
+        let glsl_std_450 = r#"{
+  "instructions": [
+    { "opname": "Sqrt", "opcode": 31, "operands": [ { "kind": "IdRef", "name": "'x'" } ] }
+  ]
+}"#;
+        let mut spirv = SpirvSpec::parse_json(core.to_string(), "core").unwrap();
+        let json = serde_json::from_str(glsl_std_450).unwrap();
+        spirv
+            .add_ext_inst_set("GLSL.std.450", &json, "ext")
+            .unwrap();
+        let signatures = spirv.builtin_functions();
+        let names: Vec<_> = signatures.iter().map(ToString::to_string).collect();
+        assert_eq!(names, ["OpFMul(Operand 1, Operand 2)", "Sqrt(x)"]);
+
+        let builtins = Builtin::from_spec(&wgsl).into_iter();
+        let report = MappingReport::new(builtins.chain(Builtin::from_spec(&spirv)));
+        let direct: Vec<_> = report.direct().map(|m| m.key.as_str()).collect();
+        assert_eq!(direct, ["sqrt"]);
+        let sqrt = &report.mappings[1].builtins[0];
+        assert_eq!(sqrt.signatures.len(), 1);
+        assert_eq!(
+            sqrt.signatures[0].classes,
+            [BTreeSet::from([TypeClass::Float])]
+        );
+    }
+}
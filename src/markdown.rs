@@ -0,0 +1,164 @@
+use lazy_static::lazy_static;
+use regex::Regex;
+
+use crate::misc::normalize_whitespace;
+
+/// removes inline markdown/html markup from a piece of text, e.g.
+/// `[**abs**](dx-graphics-hlsl-abs.md)<br/>` becomes `abs`
+pub fn strip_markup(s: &str) -> String {
+    lazy_static! {
+        static ref LINK: Regex = Regex::new(r"!?\[([^\]]*)\]\([^)]*\)").unwrap();
+        static ref STRONG: Regex = Regex::new(r"\*\*([^*]*)\*\*|__([^_]*)__").unwrap();
+        static ref EMPHASIS: Regex = Regex::new(r"\*([^*]*)\*").unwrap();
+        static ref TAG: Regex =
+            Regex::new(r"</?(?:a|b|br|code|em|i|p|span|strong|sup)\b[^>]*>").unwrap();
+        static ref ESCAPE: Regex = Regex::new(r"\\([\\`*_{}\[\]()#+\-.!|])").unwrap();
+    }
+    let s = LINK.replace_all(s, "$1");
+    let s = STRONG.replace_all(&s, "$1$2");
+    let s = EMPHASIS.replace_all(&s, "$1");
+    let s = TAG.replace_all(&s, " ");
+    let s = ESCAPE.replace_all(&s, "$1");
+    normalize_whitespace(
+        &s.replace('`', "")
+            .replace("&nbsp;", " ")
+            .replace("&lt;", "<")
+            .replace("&gt;", ">")
+            .replace("&amp;", "&"),
+    )
+}
+
+/// a pipe table of a markdown document
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Table<'a> {
+    /// byte offset of the table in the document
+    pub offset: usize,
+    /// the cells of the first row, with markup stripped
+    pub header: Vec<String>,
+    /// the raw (still marked up) cells of the rows after the `|---|` line
+    pub rows: Vec<Vec<&'a str>>,
+}
+
+impl<'a> Table<'a> {
+    /// true if the header contains a column whose name contains `name`, ignoring case
+    pub fn has_column(&self, name: &str) -> bool {
+        let name = name.to_lowercase();
+        self.header.iter().any(|h| h.to_lowercase().contains(&name))
+    }
+}
+
+/// the cells of a `| a | b |` line, `|` may be escaped as `\|`
+fn cells(line: &str) -> Vec<&str> {
+    let line = line.trim();
+    let line = line.strip_prefix('|').unwrap_or(line);
+    let line = line.strip_suffix('|').unwrap_or(line);
+    let bytes = line.as_bytes();
+    let mut starts = vec![0];
+    for (i, _) in line.match_indices('|') {
+        if i == 0 || bytes[i - 1] != b'\\' {
+            starts.push(i + 1);
+        }
+    }
+    starts.push(line.len() + 1);
+    starts
+        .windows(2)
+        .map(|w| line[w[0]..w[1] - 1].trim())
+        .collect()
+}
+
+/// extracts all pipe tables of a markdown document, i.e. runs of lines starting with `|`
+/// whose second line is a `|---|---|` separator
+pub fn tables(text: &str) -> Vec<Table<'_>> {
+    let mut tables = vec![];
+    let mut lines = vec![];
+    let mut offset = 0;
+    for line in text.split_inclusive('\n') {
+        let start = offset;
+        offset += line.len();
+        if line.trim_start().starts_with('|') {
+            lines.push((start, line.trim_end()));
+            continue;
+        }
+        tables.extend(table(&lines));
+        lines.clear();
+    }
+    tables.extend(table(&lines));
+    tables
+}
+
+fn table<'a>(lines: &[(usize, &'a str)]) -> Option<Table<'a>> {
+    lazy_static! {
+        static ref SEPARATOR: Regex =
+            Regex::new(r"^\|?\s*:?-+:?\s*(?:\|\s*:?-+:?\s*)*\|?$").unwrap();
+    }
+    let [(offset, header), (_, separator), rows @ ..] = lines else {
+        return None;
+    };
+    if !SEPARATOR.is_match(separator.trim()) {
+        return None;
+    }
+    Some(Table {
+        offset: *offset,
+        header: cells(header).into_iter().map(strip_markup).collect(),
+        rows: rows.iter().map(|(_, row)| cells(row)).collect(),
+    })
+}
+
+/// the value of a `key: value` line of the front matter block, e.g. `ms.date`
+pub fn front_matter<'a>(text: &'a str, key: &str) -> Option<&'a str> {
+    let body = without_front_matter(text);
+    let front = &text[..text.len() - body.len()];
+    front.lines().find_map(|line| {
+        let (k, value) = line.split_once(':')?;
+        (k.trim() == key).then(|| value.trim())
+    })
+}
+
+/// the document without a leading `---` front matter block
+pub fn without_front_matter(text: &str) -> &str {
+    let Some(rest) = text.trim_start().strip_prefix("---\n") else {
+        return text;
+    };
+    match rest.find("\n---") {
+        Some(end) => rest[end + 4..].trim_start_matches(['\r', '\n']),
+        None => text,
+    }
+}
+
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_markdown() {
+        let text = "---
+title: abs function
+ms.date: 08/20/2019
+---
+
+# abs
+
+| Name | Template&nbsp;Type | Size |
+|------|:------------------:|------|
+| *x* | [**scalar**](dx-graphics-hlsl-intrinsic-functions.md), **vector** | any |
+| ret | `a \\| b`<br/>c | same as input *x* |
+
+| not | a table |
+| a | row |
+";
+        assert_eq!(front_matter(text, "ms.date"), Some("08/20/2019"));
+        assert_eq!(front_matter(text, "abs"), None);
+        assert!(without_front_matter(text).starts_with("# abs"));
+
+        let tables = tables(text);
+        assert_eq!(tables.len(), 1);
+        let table = &tables[0];
+        assert_eq!(&text[table.offset..table.offset + 6], "| Name");
+        assert_eq!(table.header, ["Name", "Template Type", "Size"]);
+        assert!(table.has_column("template type"));
+        assert_eq!(table.rows.len(), 2);
+        assert_eq!(table.rows[1][1], "`a \\| b`<br/>c");
+        assert_eq!(strip_markup(table.rows[0][1]), "scalar, vector");
+        assert_eq!(strip_markup(table.rows[1][1]), "a | b c");
+        assert_eq!(strip_markup(table.rows[1][2]), "same as input x");
+    }
+}
@@ -1,4 +1,6 @@
 pub use nom;
+
+use crate::diagnostic::Diagnostic;
 pub use nom::branch::alt;
 pub use nom::bytes::complete::take_till;
 use nom::bytes::complete::take_until;
@@ -45,7 +47,7 @@ where
 {
     move |input: &str| {
         let (input, _) = ws1.parse(input)?;
-        f.parse(input).map(|(i, o)| (i, o))
+        f.parse(input)
     }
 }
 
@@ -55,7 +57,7 @@ where
 {
     move |input: &str| {
         let (input, _) = ws0.parse(input)?;
-        f.parse(input).map(|(i, o)| (i, o))
+        f.parse(input)
     }
 }
 
@@ -112,16 +114,13 @@ impl<I: core::ops::Deref<Target = str>> NomReportError for Err<NomError<I>> {
     type Input = I;
     type Output = Self;
     fn report(self, input: Self::Input) -> Self {
-        let text = match self {
-            Err::Incomplete(e) => unreachable!(),
-            Err::Error(e) | Err::Failure(e) => convert_error(input, e),
-        };
+        let text = self.report_into_string(input);
         panic!("{text}")
     }
     fn report_into_string(self, input: Self::Input) -> String {
         match self {
             Err::Incomplete(e) => unreachable!(),
-            Err::Error(e) | Err::Failure(e) => convert_error(input, e),
+            Err::Error(e) | Err::Failure(e) => Diagnostic::from_nom(&input, &e).to_string(),
         }
     }
 }
@@ -0,0 +1,209 @@
+use std::{cell::RefCell, fmt::Display, ops::Range};
+
+use crate::nom_prelude::*;
+
+/// the location of a parsed node in the source text, e.g. in `WgslSpec::text`.
+/// nodes which were not parsed from the source, or from a copy of a part of it
+/// (e.g. a table cell with its markup removed), have an unknown span.
+///
+/// nodes implement equality with [`eq_ignoring_span!`](crate::eq_ignoring_span),
+/// so a parsed `vec3<f32>` equals a constructed one
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Span {
+    pub byte_range: Range<usize>,
+    /// starting at 1, 0 if the span is unknown
+    pub line: usize,
+    /// in characters, starting at 1
+    pub column: usize,
+}
+
+/// implements `PartialEq` and `Eq` for a struct with a `span` field, comparing all other fields.
+/// the fields must be listed exhaustively, a field that is added later is a compile error
+#[macro_export]
+macro_rules! eq_ignoring_span {
+    ($ty: ident { $($field: ident),* $(,)? }) => {
+        impl PartialEq for $ty {
+            fn eq(&self, other: &Self) -> bool {
+                let $ty { $($field,)* span: _ } = self;
+                true $(&& *$field == other.$field)*
+            }
+        }
+
+        impl Eq for $ty {}
+    };
+}
+
+impl Display for Span {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.is_known() {
+            true => write!(f, "{}:{}", self.line, self.column),
+            false => write!(f, "?"),
+        }
+    }
+}
+
+/// the text spans are created for, see [`with_source`]. the text itself is not kept,
+/// only its address and what is needed to compute lines and columns
+struct Source {
+    /// the address of the text, to find the offsets of the slices parsers are called with
+    start: usize,
+    len: usize,
+    line_starts: Vec<usize>,
+    /// the offsets of the UTF-8 continuation bytes, which do not start a character
+    continuation_bytes: Vec<usize>,
+}
+
+impl Source {
+    fn new(text: &str) -> Self {
+        let bytes = text.bytes().enumerate();
+        Source {
+            start: text.as_ptr() as usize,
+            len: text.len(),
+            line_starts: std::iter::once(0)
+                .chain(text.match_indices('\n').map(|(i, _)| i + 1))
+                .collect(),
+            continuation_bytes: bytes
+                .filter(|(_, b)| b & 0b1100_0000 == 0b1000_0000)
+                .map(|(i, _)| i)
+                .collect(),
+        }
+    }
+
+    /// the byte offset of `s` in the text, `None` if `s` is not a slice of the text
+    fn offset_of(&self, s: &str) -> Option<usize> {
+        let offset = (s.as_ptr() as usize).checked_sub(self.start)?;
+        (offset + s.len() <= self.len).then_some(offset)
+    }
+
+    /// the number of characters between the byte offsets `start` and `end`
+    fn chars_between(&self, start: usize, end: usize) -> usize {
+        let continuation = &self.continuation_bytes;
+        let skipped = continuation.partition_point(|&i| i < end)
+            - continuation.partition_point(|&i| i < start);
+        end - start - skipped
+    }
+
+    /// the span between the byte offsets `start` and `end`
+    fn span(&self, start: usize, end: usize) -> Span {
+        let line = self.line_starts.partition_point(|&l| l <= start);
+        let line_start = self.line_starts[line - 1];
+        let column = self.chars_between(line_start, start) + 1;
+        Span {
+            byte_range: start..end,
+            line,
+            column,
+        }
+    }
+}
+
+thread_local! {
+    static SOURCE: RefCell<Option<Source>> = const { RefCell::new(None) };
+}
+
+/// restores the source of an enclosing [`with_source`] when dropped, also if `f` panics
+struct RestoreSource(Option<Source>);
+
+impl Drop for RestoreSource {
+    fn drop(&mut self) {
+        SOURCE.with(|s| s.replace(self.0.take()));
+    }
+}
+
+/// parsers called by `f` create spans for the parts of `text` they consume.
+/// all other input produces unknown spans
+pub fn with_source<T>(text: &str, f: impl FnOnce() -> T) -> T {
+    let outer = SOURCE.with(|s| s.replace(Some(Source::new(text))));
+    let _restore = RestoreSource(outer);
+    f()
+}
+
+impl Span {
+    pub fn is_known(&self) -> bool {
+        self.line != 0
+    }
+
+    /// the span of the input a parser consumed, from the start of `input` to the start of `rest`
+    pub fn consumed(input: &str, rest: &str) -> Self {
+        SOURCE.with(|source| {
+            let source = source.borrow();
+            let Some(source) = source.as_ref() else {
+                return Span::default();
+            };
+            let (Some(start), Some(end)) = (source.offset_of(input), source.offset_of(rest)) else {
+                return Span::default();
+            };
+            if start > end {
+                return Span::default();
+            }
+            source.span(start, end)
+        })
+    }
+
+    /// the span of `part`, which must be a slice of `text`. unlike [`Span::consumed`]
+    /// this does not need a [`with_source`] context
+    pub fn of(text: &str, part: &str) -> Self {
+        let source = Source::new(text);
+        match source.offset_of(part) {
+            Some(start) => source.span(start, start + part.len()),
+            None => Span::default(),
+        }
+    }
+
+    /// the spanned part of `text`, which must be the source the span was created for
+    pub fn source<'a>(&self, text: &'a str) -> &'a str {
+        text.get(self.byte_range.clone()).unwrap_or_default()
+    }
+}
+
+/// runs `parser` and also returns the span of the input it consumed
+pub fn spanned<'a, O, F>(mut parser: F) -> impl FnMut(&'a str) -> NomResult<&'a str, (O, Span)>
+where
+    F: Parser<&'a str, O, NomError<&'a str>>,
+{
+    move |input: &'a str| {
+        let (rest, o) = parser.parse(input)?;
+        Ok((rest, (o, Span::consumed(input, rest))))
+    }
+}
+
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_span() {
+        let text = "first line\n  ä `vec3` x";
+        let (span, outside) = with_source(text, || {
+            let input = &text[text.find('`').unwrap() + 1..];
+            let (_, (_, span)) = spanned(identifier)(input).unwrap();
+            let copy = input.to_string();
+            let (_, (_, outside)) = spanned(identifier)(copy.as_str()).unwrap();
+            (span, outside)
+        });
+        assert_eq!(span.source(text), "vec3");
+        assert_eq!((span.line, span.column), (2, 6));
+        assert_eq!(span.to_string(), "2:6");
+        assert!(!outside.is_known());
+        assert_ne!(span, outside);
+
+        let (_, (_, span)) = spanned(identifier)(text).unwrap();
+        assert!(!span.is_known());
+
+        // the source of the enclosing call is restored, also after a panic
+        let inner = "inner";
+        let (outer, after_panic) = with_source(text, || {
+            let panicked = std::panic::catch_unwind(|| with_source(inner, || panic!()));
+            assert!(panicked.is_err());
+            let (_, (_, outer)) = spanned(identifier)(text).unwrap();
+            let (_, (_, inner)) = spanned(identifier)(inner).unwrap();
+            (outer, inner)
+        });
+        assert_eq!(outer.source(text), "first");
+        assert!(!after_panic.is_known());
+
+        let start = text.find('`').unwrap();
+        let span = Span::of(text, &text[start..start + 6]);
+        assert_eq!((span.line, span.column), (2, 5));
+        assert_eq!(span.source(text), "`vec3`");
+        assert!(!Span::of(text, "first").is_known());
+    }
+}
@@ -0,0 +1,91 @@
+use std::{collections::BTreeSet, fmt::Display};
+
+use crate::grammar::GrammarRule;
+
+/// a type of a parameter or result of a builtin function, independent of the language
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SignatureType {
+    /// as written in the language, e.g. `vec3<f32>`, `vec3` or `float3`
+    pub text: String,
+    /// the name of the type followed by the names of the types it is parametrized with,
+    /// e.g. `["vec3", "f32"]` for `vec3<f32>`
+    pub names: Vec<String>,
+}
+
+impl Display for SignatureType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.text)
+    }
+}
+
+/// an instance of a builtin function with concrete types, or a SPIR-V instruction
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BuiltinSignature {
+    /// as written in the language, e.g. `inverseSqrt`, `rsqrt` or `OpFMul`
+    pub name: String,
+    /// the names and types of the parameters. a type is `None` if the spec does not state a
+    /// concrete one, e.g. for the operands of SPIR-V instructions
+    pub params: Vec<(String, Option<SignatureType>)>,
+    pub result: Option<SignatureType>,
+}
+
+impl Display for BuiltinSignature {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}(", self.name)?;
+        for (i, (name, ty)) in self.params.iter().enumerate() {
+            let comma = if i + 1 != self.params.len() { ", " } else { "" };
+            match ty {
+                Some(ty) => write!(f, "{name}: {ty}{comma}")?,
+                None => write!(f, "{name}{comma}")?,
+            }
+        }
+        write!(f, ")")?;
+        match &self.result {
+            Some(result) => write!(f, " -> {result}"),
+            None => Ok(()),
+        }
+    }
+}
+
+/// the parts of a shading language specification that tools like exporters work with,
+/// independent of the language
+pub trait ShaderLangSpec {
+    /// a short name of the language, e.g. `"WGSL"`
+    fn language(&self) -> &'static str;
+
+    /// the revision of the document the spec was read from, e.g. a date or version, if it states one
+    fn revision(&self) -> Option<&str>;
+
+    /// the document the spec was read from. the spans of the parsed nodes point into it
+    fn source_text(&self) -> &str;
+
+    /// every instance of every builtin function overload, or the instructions of SPIR-V
+    fn builtin_functions(&self) -> Vec<BuiltinSignature>;
+
+    /// the words with a meaning in the grammar, e.g. `fn` and `let`
+    fn keywords(&self) -> Vec<&str>;
+
+    /// the words which cannot be used as identifiers, but have no meaning yet
+    fn reserved_words(&self) -> Vec<&str>;
+
+    /// the rules of the grammar, empty if the spec does not state one in a form that can be parsed
+    fn grammar(&self) -> &[GrammarRule];
+
+    /// the names of the types of the arguments and results of all builtin function instances,
+    /// e.g. `vec3` and `f32`
+    fn types(&self) -> Vec<String> {
+        let mut names = BTreeSet::new();
+        for signature in self.builtin_functions() {
+            let params = signature.params.into_iter().filter_map(|(_, ty)| ty);
+            for ty in params.chain(signature.result) {
+                names.extend(ty.names);
+            }
+        }
+        names.into_iter().collect()
+    }
+
+    /// the rule named `name`
+    fn grammar_rule(&self, name: &str) -> Option<&GrammarRule> {
+        self.grammar().iter().find(|r| r.name == name)
+    }
+}
@@ -0,0 +1,151 @@
+use serde_json::Value;
+
+use super::grammar::{Instruction, OperandKind};
+
+/// the names `OpExtInstImport` uses, by the part of the grammar file name between
+/// `extinst.` and `.grammar.json`
+const IMPORT_NAMES: &[(&str, &str)] = &[
+    ("glsl.std.450", "GLSL.std.450"),
+    ("opencl.std.100", "OpenCL.std"),
+    ("opencl.debuginfo.100", "OpenCL.DebugInfo.100"),
+    ("debuginfo", "DebugInfo"),
+    ("spv-amd-gcn-shader", "SPV_AMD_gcn_shader"),
+    ("spv-amd-shader-ballot", "SPV_AMD_shader_ballot"),
+    (
+        "spv-amd-shader-explicit-vertex-parameter",
+        "SPV_AMD_shader_explicit_vertex_parameter",
+    ),
+    (
+        "spv-amd-shader-trinary-minmax",
+        "SPV_AMD_shader_trinary_minmax",
+    ),
+    (
+        "nonsemantic.shader.debuginfo.100",
+        "NonSemantic.Shader.DebugInfo.100",
+    ),
+    ("nonsemantic.clspvreflection", "NonSemantic.ClspvReflection"),
+    ("nonsemantic.debugprintf", "NonSemantic.DebugPrintf"),
+    ("nonsemantic.debugbreak", "NonSemantic.DebugBreak"),
+    ("nonsemantic.vkspreflection", "NonSemantic.VkspReflection"),
+];
+
+/// an extended instruction set grammar like `extinst.glsl.std.450.grammar.json`, whose
+/// instructions are called with `OpExtInst`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExtInstSet {
+    /// the name `OpExtInstImport` uses, e.g. `GLSL.std.450`
+    pub name: String,
+    pub version: Option<u32>,
+    pub revision: Option<u32>,
+    /// the operands do not include the result type, result id, set and instruction number
+    /// operands of `OpExtInst`
+    pub instructions: Vec<Instruction>,
+    /// the operand kinds the set defines in addition to the core ones, e.g. `DebugInfoFlags`
+    pub operand_kinds: Vec<OperandKind>,
+}
+
+impl ExtInstSet {
+    /// the import name for a file name like `extinst.nonsemantic.debugprintf.grammar.json`,
+    /// `None` if the file is not an extended instruction set grammar
+    pub fn name_of_file(file_name: &str) -> Option<String> {
+        let name = file_name.strip_prefix("extinst.")?;
+        let name = name.strip_suffix(".grammar.json")?;
+        let known = IMPORT_NAMES.iter().find(|(file, _)| *file == name);
+        Some(known.map_or(name, |(_, import)| import).to_string())
+    }
+
+    pub fn from_json(name: &str, json: &Value) -> Result<Self, String> {
+        let number = |key| json.get(key).and_then(Value::as_u64).map(|n| n as u32);
+        let instructions = json
+            .get("instructions")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten();
+        let operand_kinds = json
+            .get("operand_kinds")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten();
+        let instructions = instructions.map(Instruction::from_json);
+        let operand_kinds = operand_kinds.map(OperandKind::from_json);
+        Ok(ExtInstSet {
+            name: name.to_string(),
+            version: number("version"),
+            revision: number("revision"),
+            instructions: instructions.collect::<Result<_, _>>()?,
+            operand_kinds: operand_kinds.collect::<Result<_, _>>()?,
+        })
+    }
+
+    pub fn instruction(&self, opname: &str) -> Option<&Instruction> {
+        self.instructions.iter().find(|i| i.opname == opname)
+    }
+
+    /// the instructions with a name like `name`, ignoring case and a leading `F`, `S` or `U` for the
+    /// float, signed and unsigned variants, e.g. `FAbs` and `SAbs` for `abs`
+    pub fn instructions_like<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a Instruction> {
+        self.instructions.iter().filter(move |i| {
+            let opname = i.opname.as_str();
+            let unprefixed = match opname.strip_prefix(['F', 'S', 'U']) {
+                Some(rest) if rest.starts_with(char::is_uppercase) => rest,
+                _ => opname,
+            };
+            [opname, unprefixed]
+                .iter()
+                .any(|n| n.eq_ignore_ascii_case(name))
+        })
+    }
+}
+
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ext_inst_set() {
+        let text = r#"{
+  "copyright": ["..."],
+  "version": 100,
+  "revision": 2,
+  "instructions": [
+    { "opname": "Round", "opcode": 1, "operands": [ { "kind": "IdRef", "name": "'x'" } ] },
+    { "opname": "FAbs", "opcode": 4, "operands": [ { "kind": "IdRef", "name": "'x'" } ] },
+    { "opname": "SAbs", "opcode": 5, "operands": [ { "kind": "IdRef", "name": "'x'" } ] },
+    { "opname": "Sqrt", "opcode": 31, "operands": [ { "kind": "IdRef", "name": "'x'" } ] },
+    {
+      "opname": "InterpolateAtOffset", "opcode": 78,
+      "operands": [ { "kind": "IdRef", "name": "'interpolant'" }, { "kind": "IdRef", "name": "'offset'" } ],
+      "capabilities": [ "InterpolationFunction" ]
+    }
+  ],
+  "operand_kinds": [
+    {
+      "category": "BitEnum", "kind": "DebugInfoFlags",
+      "enumerants": [ { "enumerant": "FlagIsProtected", "value": "0x01" } ]
+    }
+  ]
+}"#;
+        let json: Value = serde_json::from_str(text).unwrap();
+        let name = ExtInstSet::name_of_file("extinst.glsl.std.450.grammar.json").unwrap();
+        let set = ExtInstSet::from_json(&name, &json).unwrap();
+        assert_eq!(set.name, "GLSL.std.450");
+        assert_eq!((set.version, set.revision), (Some(100), Some(2)));
+        assert_eq!(set.instructions.len(), 5);
+        let interpolate = set.instruction("InterpolateAtOffset").unwrap();
+        assert_eq!(interpolate.operands[1].name.as_deref(), Some("offset"));
+        assert_eq!(
+            interpolate.availability.capabilities,
+            ["InterpolationFunction"]
+        );
+        let abs: Vec<_> = set.instructions_like("abs").map(|i| i.opcode).collect();
+        assert_eq!(abs, [4, 5]);
+        let sqrt: Vec<_> = set.instructions_like("sqrt").map(|i| i.opcode).collect();
+        assert_eq!(sqrt, [31]);
+        assert_eq!(set.operand_kinds[0].enumerants[0].value, 1);
+
+        assert_eq!(
+            ExtInstSet::name_of_file("extinst.nonsemantic.debugprintf.grammar.json").as_deref(),
+            Some("NonSemantic.DebugPrintf")
+        );
+        assert_eq!(ExtInstSet::name_of_file("spirv.core.grammar.json"), None);
+    }
+}
@@ -0,0 +1,496 @@
+use std::fmt::Display;
+
+use serde_json::Value;
+
+/// a SPIR-V version, e.g. `1.3`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Version {
+    pub major: u32,
+    pub minor: u32,
+}
+
+impl Display for Version {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}.{}", self.major, self.minor)
+    }
+}
+
+impl Version {
+    /// parses `"1.3"`. `"None"` (only available through an extension) is `None`
+    pub fn parse(s: &str) -> Result<Option<Self>, String> {
+        if s == "None" {
+            return Ok(None);
+        }
+        let invalid = || format!("invalid version `{s}`");
+        let (major, minor) = s.split_once('.').ok_or_else(invalid)?;
+        let major = major.parse().map_err(|_| invalid())?;
+        let minor = minor.parse().map_err(|_| invalid())?;
+        Ok(Some(Version { major, minor }))
+    }
+}
+
+/// when an instruction or enumerant can be used
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Availability {
+    /// the first core version, `None` if it is only available through one of the [`Self::extensions`]
+    pub version: Option<Version>,
+    /// the last core version, if it was removed later
+    pub last_version: Option<Version>,
+    /// one of these capabilities must be declared
+    pub capabilities: Vec<String>,
+    /// one of these extensions must be declared, unless the version is at least [`Self::version`]
+    pub extensions: Vec<String>,
+}
+
+impl Display for Availability {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match (&self.version, &self.last_version) {
+            (Some(v), Some(last)) => write!(f, "{v}..={last}")?,
+            (Some(v), None) => write!(f, "{v}")?,
+            (None, _) => write!(f, "extension")?,
+        }
+        if !self.capabilities.is_empty() {
+            write!(f, ", capabilities: {}", self.capabilities.join(" | "))?;
+        }
+        if !self.extensions.is_empty() {
+            write!(f, ", extensions: {}", self.extensions.join(" | "))?;
+        }
+        Ok(())
+    }
+}
+
+/// the strings of an array member, e.g. `"capabilities": ["Shader"]`
+fn strings(json: &Value, key: &str) -> Vec<String> {
+    let items = json
+        .get(key)
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten();
+    items
+        .filter_map(Value::as_str)
+        .map(str::to_string)
+        .collect()
+}
+
+impl Availability {
+    pub fn from_json(json: &Value) -> Result<Self, String> {
+        let version = |key| match json.get(key).and_then(Value::as_str) {
+            Some(v) => Version::parse(v),
+            None => Ok(None),
+        };
+        let mut availability = Availability {
+            version: version("version")?,
+            last_version: version("lastVersion")?,
+            capabilities: strings(json, "capabilities"),
+            extensions: strings(json, "extensions"),
+        };
+        // entries without a version are part of the first version, unless they need an extension
+        if json.get("version").is_none() && availability.extensions.is_empty() {
+            availability.version = Some(Version { major: 1, minor: 0 });
+        }
+        Ok(availability)
+    }
+
+    /// true if the core `version` has it without declaring an extension
+    pub fn is_core_in(&self, version: Version) -> bool {
+        let since = self.version.is_some_and(|v| v <= version);
+        since && self.last_version.is_none_or(|last| version <= last)
+    }
+}
+
+/// how often an operand occurs
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Quantifier {
+    One,
+    /// `?`
+    Optional,
+    /// `*`, zero or more
+    Variadic,
+}
+
+impl Display for Quantifier {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Quantifier::One => Ok(()),
+            Quantifier::Optional => write!(f, "?"),
+            Quantifier::Variadic => write!(f, "*"),
+        }
+    }
+}
+
+/// an operand of an instruction or a parameter of an enumerant, e.g. `IdRef 'Pointer'`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Operand {
+    /// the name of an [`OperandKind`], e.g. `IdResultType`
+    pub kind: String,
+    /// e.g. `'Pointer'`, with the quotes of the grammar removed
+    pub name: Option<String>,
+    pub quantifier: Quantifier,
+}
+
+impl Display for Operand {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}{}", self.kind, self.quantifier)?;
+        match &self.name {
+            Some(name) => write!(f, " '{name}'"),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Operand {
+    pub fn from_json(json: &Value) -> Result<Self, String> {
+        let kind = json.get("kind").and_then(Value::as_str);
+        let kind = kind.ok_or_else(|| format!("operand without a kind: {json}"))?;
+        let name = json.get("name").and_then(Value::as_str);
+        let name = name.map(|n| n.trim_matches('\'').to_string());
+        let quantifier = match json.get("quantifier").and_then(Value::as_str) {
+            None => Quantifier::One,
+            Some("?") => Quantifier::Optional,
+            Some("*") => Quantifier::Variadic,
+            Some(q) => return Err(format!("invalid quantifier `{q}`")),
+        };
+        Ok(Operand {
+            kind: kind.to_string(),
+            name,
+            quantifier,
+        })
+    }
+
+    /// the operands of an `"operands"` or `"parameters"` member
+    pub fn list_from_json(json: &Value, key: &str) -> Result<Vec<Self>, String> {
+        let operands = json
+            .get(key)
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten();
+        operands.map(Operand::from_json).collect()
+    }
+}
+
+/// a number written as a JSON number or as a hexadecimal string, e.g. `"0x0004"`
+fn number(json: &Value, key: &str) -> Result<u32, String> {
+    let invalid = || format!("invalid `{key}` in {json}");
+    match json.get(key).ok_or_else(invalid)? {
+        Value::Number(n) => n.as_u64().map(|n| n as u32).ok_or_else(invalid),
+        Value::String(s) => {
+            let hex = s.strip_prefix("0x").ok_or_else(invalid)?;
+            u32::from_str_radix(hex, 16).map_err(|_| invalid())
+        }
+        _ => Err(invalid()),
+    }
+}
+
+/// an instruction, e.g. `OpLoad`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Instruction {
+    pub opname: String,
+    /// the instruction printing class, e.g. `Memory`
+    pub class: Option<String>,
+    pub opcode: u32,
+    pub operands: Vec<Operand>,
+    pub availability: Availability,
+}
+
+impl Display for Instruction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} = {}", self.opname, self.opcode)?;
+        for operand in &self.operands {
+            write!(f, ", {operand}")?;
+        }
+        write!(f, " ({})", self.availability)
+    }
+}
+
+impl Instruction {
+    pub fn from_json(json: &Value) -> Result<Self, String> {
+        let opname = json.get("opname").and_then(Value::as_str);
+        let opname = opname.ok_or_else(|| format!("instruction without an opname: {json}"))?;
+        Ok(Instruction {
+            opname: opname.to_string(),
+            class: json
+                .get("class")
+                .and_then(Value::as_str)
+                .map(str::to_string),
+            opcode: number(json, "opcode")?,
+            operands: Operand::list_from_json(json, "operands")?,
+            availability: Availability::from_json(json)?,
+        })
+    }
+
+    /// true if the instruction has a result id
+    pub fn has_result(&self) -> bool {
+        self.operands.iter().any(|o| o.kind == "IdResult")
+    }
+}
+
+/// the category of an [`OperandKind`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OperandCategory {
+    /// flags that can be combined, e.g. `MemoryAccess`
+    BitEnum,
+    /// e.g. `StorageClass`
+    ValueEnum,
+    /// e.g. `IdRef`
+    Id,
+    /// e.g. `LiteralInteger`
+    Literal,
+    /// a sequence of other kinds, e.g. `PairLiteralIntegerIdRef`
+    Composite,
+}
+
+impl OperandCategory {
+    fn from_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "BitEnum" => OperandCategory::BitEnum,
+            "ValueEnum" => OperandCategory::ValueEnum,
+            "Id" => OperandCategory::Id,
+            "Literal" => OperandCategory::Literal,
+            "Composite" => OperandCategory::Composite,
+            _ => return None,
+        })
+    }
+}
+
+/// a value of a `BitEnum` or `ValueEnum` operand kind, e.g. `Workgroup` of `StorageClass`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Enumerant {
+    pub name: String,
+    pub value: u32,
+    /// the operands following the enumerant, e.g. the `LiteralInteger` of `Aligned`
+    pub parameters: Vec<Operand>,
+    pub availability: Availability,
+}
+
+impl Enumerant {
+    pub fn from_json(json: &Value) -> Result<Self, String> {
+        let name = json.get("enumerant").and_then(Value::as_str);
+        let name = name.ok_or_else(|| format!("enumerant without a name: {json}"))?;
+        Ok(Enumerant {
+            name: name.to_string(),
+            value: number(json, "value")?,
+            parameters: Operand::list_from_json(json, "parameters")?,
+            availability: Availability::from_json(json)?,
+        })
+    }
+}
+
+/// a kind of operand, e.g. `IdRef`, `LiteralInteger` or the `StorageClass` enum
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OperandKind {
+    pub kind: String,
+    pub category: OperandCategory,
+    pub doc: Option<String>,
+    /// the enumerants of an enum category
+    pub enumerants: Vec<Enumerant>,
+    /// the kinds a composite is made of
+    pub bases: Vec<String>,
+}
+
+impl OperandKind {
+    pub fn from_json(json: &Value) -> Result<Self, String> {
+        let kind = json.get("kind").and_then(Value::as_str);
+        let kind = kind.ok_or_else(|| format!("operand kind without a name: {json}"))?;
+        let category = json.get("category").and_then(Value::as_str);
+        let category = category.and_then(OperandCategory::from_name);
+        let category = category.ok_or_else(|| format!("invalid category of `{kind}`"))?;
+        let enumerants = json
+            .get("enumerants")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten();
+        Ok(OperandKind {
+            kind: kind.to_string(),
+            category,
+            doc: json.get("doc").and_then(Value::as_str).map(str::to_string),
+            enumerants: enumerants
+                .map(Enumerant::from_json)
+                .collect::<Result<_, _>>()?,
+            bases: strings(json, "bases"),
+        })
+    }
+
+    pub fn enumerant(&self, name: &str) -> Option<&Enumerant> {
+        self.enumerants.iter().find(|e| e.name == name)
+    }
+}
+
+/// the machine readable grammar `spirv.core.grammar.json`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CoreGrammar {
+    pub magic_number: u32,
+    /// the version the grammar describes
+    pub version: Version,
+    pub revision: u32,
+    pub instructions: Vec<Instruction>,
+    pub operand_kinds: Vec<OperandKind>,
+}
+
+impl CoreGrammar {
+    pub fn from_json(json: &Value) -> Result<Self, String> {
+        let instructions = json
+            .get("instructions")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten();
+        let operand_kinds = json
+            .get("operand_kinds")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten();
+        Ok(CoreGrammar {
+            magic_number: number(json, "magic_number")?,
+            version: Version {
+                major: number(json, "major_version")?,
+                minor: number(json, "minor_version")?,
+            },
+            revision: number(json, "revision")?,
+            instructions: instructions
+                .map(Instruction::from_json)
+                .collect::<Result<_, _>>()?,
+            operand_kinds: operand_kinds
+                .map(OperandKind::from_json)
+                .collect::<Result<_, _>>()?,
+        })
+    }
+
+    pub fn instruction(&self, opname: &str) -> Option<&Instruction> {
+        self.instructions.iter().find(|i| i.opname == opname)
+    }
+
+    pub fn operand_kind(&self, kind: &str) -> Option<&OperandKind> {
+        self.operand_kinds.iter().find(|k| k.kind == kind)
+    }
+
+    /// the enumerants of the `Capability` operand kind
+    pub fn capabilities(&self) -> &[Enumerant] {
+        let capability = self.operand_kind("Capability");
+        capability.map_or(&[], |k| k.enumerants.as_slice())
+    }
+
+    /// every extension an instruction or enumerant depends on, sorted
+    pub fn extensions(&self) -> Vec<&str> {
+        let instructions = self.instructions.iter().map(|i| &i.availability);
+        let enumerants = self.operand_kinds.iter().flat_map(|k| &k.enumerants);
+        let availabilities = instructions.chain(enumerants.map(|e| &e.availability));
+        let mut extensions: Vec<&str> = availabilities
+            .flat_map(|a| a.extensions.iter().map(String::as_str))
+            .collect();
+        extensions.sort_unstable();
+        extensions.dedup();
+        extensions
+    }
+
+    /// the instructions the core `version` has without declaring an extension
+    pub fn instructions_in(&self, version: Version) -> impl Iterator<Item = &Instruction> {
+        let instructions = self.instructions.iter();
+        instructions.filter(move |i| i.availability.is_core_in(version))
+    }
+}
+
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_core_grammar() {
+        let text = r#"{
+  "copyright": ["..."],
+  "magic_number": "0x07230203",
+  "major_version": 1,
+  "minor_version": 6,
+  "revision": 4,
+  "instructions": [
+    { "opname": "OpNop", "class": "Miscellaneous", "opcode": 0 },
+    {
+      "opname": "OpLoad", "class": "Memory", "opcode": 61,
+      "operands": [
+        { "kind": "IdResultType" },
+        { "kind": "IdResult" },
+        { "kind": "IdRef", "name": "'Pointer'" },
+        { "kind": "MemoryAccess", "quantifier": "?" }
+      ]
+    },
+    {
+      "opname": "OpGroupNonUniformElect", "class": "Non-Uniform", "opcode": 333,
+      "operands": [ { "kind": "IdResultType" }, { "kind": "IdResult" }, { "kind": "IdScope", "name": "'Execution'" } ],
+      "capabilities": [ "GroupNonUniform" ],
+      "version": "1.3"
+    },
+    {
+      "opname": "OpTerminateInvocation", "class": "Control-Flow", "opcode": 4416,
+      "extensions": [ "SPV_KHR_terminate_invocation" ],
+      "capabilities": [ "Shader" ],
+      "version": "1.6"
+    },
+    {
+      "opname": "OpDemoteToHelperInvocation", "class": "Control-Flow", "opcode": 5380,
+      "capabilities": [ "DemoteToHelperInvocation" ],
+      "extensions": [ "SPV_EXT_demote_to_helper_invocation" ],
+      "version": "None"
+    }
+  ],
+  "operand_kinds": [
+    {
+      "category": "BitEnum", "kind": "MemoryAccess",
+      "enumerants": [
+        { "enumerant": "None", "value": "0x0000" },
+        { "enumerant": "Aligned", "value": "0x0002", "parameters": [ { "kind": "LiteralInteger" } ], "version": "1.0" }
+      ]
+    },
+    {
+      "category": "ValueEnum", "kind": "Capability",
+      "enumerants": [
+        { "enumerant": "Shader", "value": 1, "capabilities": [ "Matrix" ], "version": "1.0" },
+        { "enumerant": "GroupNonUniform", "value": 61, "version": "1.3" }
+      ]
+    },
+    { "category": "Id", "kind": "IdRef", "doc": "Reference to an <id>" },
+    { "category": "Composite", "kind": "PairLiteralIntegerIdRef", "bases": [ "LiteralInteger", "IdRef" ] }
+  ]
+}"#;
+        let json: Value = serde_json::from_str(text).unwrap();
+        let grammar = CoreGrammar::from_json(&json).unwrap();
+        assert_eq!(grammar.magic_number, 0x07230203);
+        assert_eq!(grammar.version.to_string(), "1.6");
+        assert_eq!(grammar.instructions.len(), 5);
+
+        let load = grammar.instruction("OpLoad").unwrap();
+        assert!(load.has_result());
+        assert_eq!(
+            load.to_string(),
+            "OpLoad = 61, IdResultType, IdResult, IdRef 'Pointer', MemoryAccess? (1.0)"
+        );
+        let elect = grammar.instruction("OpGroupNonUniformElect").unwrap();
+        assert_eq!(
+            elect.availability.to_string(),
+            "1.3, capabilities: GroupNonUniform"
+        );
+        let demote = grammar.instruction("OpDemoteToHelperInvocation").unwrap();
+        assert_eq!(demote.availability.version, None);
+
+        let v1_3 = Version::parse("1.3").unwrap().unwrap();
+        let core: Vec<_> = grammar.instructions_in(v1_3).map(|i| i.opcode).collect();
+        assert_eq!(core, [0, 61, 333]);
+        assert_eq!(
+            grammar.extensions(),
+            [
+                "SPV_EXT_demote_to_helper_invocation",
+                "SPV_KHR_terminate_invocation"
+            ]
+        );
+
+        let memory_access = grammar.operand_kind("MemoryAccess").unwrap();
+        assert_eq!(memory_access.category, OperandCategory::BitEnum);
+        let aligned = memory_access.enumerant("Aligned").unwrap();
+        assert_eq!(aligned.value, 2);
+        assert_eq!(aligned.parameters[0].kind, "LiteralInteger");
+        assert_eq!(grammar.capabilities().len(), 2);
+        let pair = grammar.operand_kind("PairLiteralIntegerIdRef").unwrap();
+        assert_eq!(pair.bases, ["LiteralInteger", "IdRef"]);
+
+        let broken = r#"{"magic_number": 1, "major_version": 1, "minor_version": 0, "revision": 1,
+            "instructions": [{"opcode": 1}]}"#;
+        let error = CoreGrammar::from_json(&broken.parse().unwrap()).unwrap_err();
+        assert_eq!(error, r#"instruction without an opname: {"opcode":1}"#);
+    }
+}
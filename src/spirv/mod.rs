@@ -0,0 +1,203 @@
+use std::path::Path;
+
+use serde_json::Value;
+
+use crate::{
+    diagnostic::SpecError,
+    grammar::GrammarRule,
+    spec::{BuiltinSignature, ShaderLangSpec},
+};
+
+use self::{
+    extinst::ExtInstSet,
+    grammar::{CoreGrammar, Instruction, Quantifier},
+};
+
+pub mod extinst;
+pub mod grammar;
+
+/// the machine readable core grammar in https://github.com/KhronosGroup/SPIRV-Headers
+pub const CORE_GRAMMAR_URL: &str = "https://raw.githubusercontent.com/KhronosGroup/SPIRV-Headers/main/include/spirv/unified1/spirv.core.grammar.json";
+
+/// the `GLSL.std.450` extended instruction set grammar in SPIRV-Headers
+pub const GLSL_STD_450_URL: &str = "https://raw.githubusercontent.com/KhronosGroup/SPIRV-Headers/main/include/spirv/unified1/extinst.glsl.std.450.grammar.json";
+
+/// the directory of the grammars in a checkout of SPIRV-Headers
+pub const GRAMMAR_DIR: &str = "include/spirv/unified1";
+
+/// the classes of the core instructions which correspond to builtin functions
+pub const BUILTIN_CLASSES: &[&str] = &[
+    "Arithmetic",
+    "Bit",
+    "Relational_and_Logical",
+    "Derivative",
+    "Atomic",
+    "Barrier",
+];
+
+fn read_file(path: &Path) -> Result<String, SpecError> {
+    std::fs::read_to_string(path).map_err(|e| SpecError::Read {
+        path: path.display().to_string(),
+        message: e.to_string(),
+    })
+}
+
+fn download(url: &str) -> Result<String, SpecError> {
+    crate::misc::download_text(url).map_err(|e| SpecError::Download {
+        url: url.to_string(),
+        message: e.to_string(),
+    })
+}
+
+fn parse_document(text: &str, file: &str) -> Result<Value, SpecError> {
+    serde_json::from_str(text).map_err(|e| SpecError::Invalid {
+        file: file.to_string(),
+        message: e.to_string(),
+    })
+}
+
+pub struct SpirvSpec {
+    /// the JSON document
+    pub text: String,
+    pub core: CoreGrammar,
+    /// e.g. `1.6 revision 4`
+    pub revision: String,
+    /// the extended instruction sets which were loaded, e.g. `GLSL.std.450`
+    pub ext_inst_sets: Vec<ExtInstSet>,
+}
+
+impl SpirvSpec {
+    /// the core grammar and `GLSL.std.450`
+    pub fn from_download() -> Result<Self, SpecError> {
+        let mut spec = Self::parse_json(download(CORE_GRAMMAR_URL)?, CORE_GRAMMAR_URL)?;
+        let text = download(GLSL_STD_450_URL)?;
+        let json = parse_document(&text, GLSL_STD_450_URL)?;
+        spec.add_ext_inst_set("GLSL.std.450", &json, GLSL_STD_450_URL)?;
+        Ok(spec)
+    }
+
+    /// reads `spirv.core.grammar.json`, e.g. from a checkout of SPIRV-Headers
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, SpecError> {
+        let path = path.as_ref();
+        Self::parse_json(read_file(path)?, &path.display().to_string())
+    }
+
+    /// reads the core grammar and all `extinst.*.grammar.json` extended instruction sets
+    /// of a checkout of SPIRV-Headers
+    pub fn from_checkout(dir: impl AsRef<Path>) -> Result<Self, SpecError> {
+        let dir = dir.as_ref().join(GRAMMAR_DIR);
+        let mut spec = Self::from_file(dir.join("spirv.core.grammar.json"))?;
+        let entries = std::fs::read_dir(&dir).map_err(|e| SpecError::Read {
+            path: dir.display().to_string(),
+            message: e.to_string(),
+        })?;
+        let mut files: Vec<_> = entries.filter_map(|e| e.ok()).map(|e| e.path()).collect();
+        files.sort();
+        for path in files {
+            let file_name = path
+                .file_name()
+                .and_then(|n| n.to_str())
+                .unwrap_or_default();
+            if let Some(name) = ExtInstSet::name_of_file(file_name) {
+                let file = path.display().to_string();
+                let json = parse_document(&read_file(&path)?, &file)?;
+                spec.add_ext_inst_set(&name, &json, &file)?;
+            }
+        }
+        Ok(spec)
+    }
+
+    /// `file` names the document in errors
+    pub fn parse_json(text: String, file: &str) -> Result<Self, SpecError> {
+        let json = parse_document(&text, file)?;
+        let core = CoreGrammar::from_json(&json).map_err(|message| SpecError::Invalid {
+            file: file.to_string(),
+            message,
+        })?;
+        let revision = format!("{} revision {}", core.version, core.revision);
+        Ok(SpirvSpec {
+            text,
+            core,
+            revision,
+            ext_inst_sets: vec![],
+        })
+    }
+
+    /// adds the extended instruction set `name` read from the grammar `json`
+    pub fn add_ext_inst_set(
+        &mut self,
+        name: &str,
+        json: &Value,
+        file: &str,
+    ) -> Result<(), SpecError> {
+        let set = ExtInstSet::from_json(name, json).map_err(|message| SpecError::Invalid {
+            file: file.to_string(),
+            message,
+        })?;
+        self.ext_inst_sets.push(set);
+        Ok(())
+    }
+
+    /// the extended instruction set `OpExtInstImport` imports as `name`, e.g. `GLSL.std.450`
+    pub fn ext_inst_set(&self, name: &str) -> Option<&ExtInstSet> {
+        self.ext_inst_sets.iter().find(|s| s.name == name)
+    }
+
+    pub fn instruction(&self, opname: &str) -> Option<&Instruction> {
+        self.core.instruction(opname)
+    }
+}
+
+/// SPIR-V has no builtin functions or grammar rules in the sense of a shading language.
+/// the builtin functions are the instructions of the extended instruction sets and the core
+/// instructions of the [`BUILTIN_CLASSES`], with their operands as parameters of unknown type.
+/// optional and variadic operands are left out. the keywords are the opnames of the
+/// instructions, as written in SPIR-V assembly
+impl ShaderLangSpec for SpirvSpec {
+    fn language(&self) -> &'static str {
+        "SPIR-V"
+    }
+
+    fn revision(&self) -> Option<&str> {
+        Some(&self.revision)
+    }
+
+    fn source_text(&self) -> &str {
+        &self.text
+    }
+
+    fn builtin_functions(&self) -> Vec<BuiltinSignature> {
+        let sets = self.ext_inst_sets.iter().flat_map(|s| &s.instructions);
+        let core = self.core.instructions.iter().filter(|i| {
+            let class = i.class.as_deref().unwrap_or_default();
+            BUILTIN_CLASSES.contains(&class)
+        });
+        let instructions = core.chain(sets);
+        let signatures = instructions.map(|instruction| {
+            let operands = instruction.operands.iter().filter(|o| {
+                let result = ["IdResultType", "IdResult"].contains(&o.kind.as_str());
+                o.quantifier == Quantifier::One && !result
+            });
+            let params = operands.map(|o| (o.name.clone().unwrap_or(o.kind.clone()), None));
+            BuiltinSignature {
+                name: instruction.opname.clone(),
+                params: params.collect(),
+                result: None,
+            }
+        });
+        signatures.collect()
+    }
+
+    fn keywords(&self) -> Vec<&str> {
+        let instructions = self.core.instructions.iter();
+        instructions.map(|i| i.opname.as_str()).collect()
+    }
+
+    fn reserved_words(&self) -> Vec<&str> {
+        vec![]
+    }
+
+    fn grammar(&self) -> &[GrammarRule] {
+        &[]
+    }
+}
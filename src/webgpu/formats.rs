@@ -0,0 +1,411 @@
+use std::fmt::Display;
+
+use lazy_static::lazy_static;
+use regex::Regex;
+
+use crate::{
+    bikeshed,
+    misc::normalize_whitespace,
+    wgsl::{enumerants::TexelFormat, WgslSpec},
+};
+
+use super::WebGpuSpec;
+
+/// whether a format supports a capability
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Support {
+    No,
+    Yes,
+    /// only if the feature is enabled, e.g. `bgra8unorm-storage`
+    WithFeature(String),
+}
+
+impl Support {
+    fn from_cell(cell: &str) -> Self {
+        lazy_static! {
+            static ref FEATURE: Regex = Regex::new(r#"GPUFeatureName/"?([\w-]+)"#).unwrap();
+        }
+        if let Some(feature) = FEATURE.captures(cell) {
+            return Support::WithFeature(feature[1].to_string());
+        }
+        match cell.contains("&checkmark;") || cell.contains('✓') {
+            true => Support::Yes,
+            false => Support::No,
+        }
+    }
+
+    pub fn is_supported(&self) -> bool {
+        *self != Support::No
+    }
+}
+
+/// a row of a texture format capabilities table
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FormatCapabilities {
+    /// a `GPUTextureFormat` value, e.g. `rgba8unorm`
+    pub format: String,
+    /// the `GPUTextureSampleType`s, e.g. `float` and `unfilterable-float`
+    pub sample_types: Vec<String>,
+    /// the support of `STORAGE_BINDING` for each `GPUStorageTextureAccess` column,
+    /// e.g. `write-only`, or for a single column without access
+    pub storage: Vec<(Option<String>, Support)>,
+}
+
+/// a `<th>` or `<td>` of a table, with its `colspan` and `rowspan`
+struct Cell<'a> {
+    header: bool,
+    content: &'a str,
+    colspan: usize,
+    rowspan: usize,
+}
+
+fn cells(row: &str) -> Vec<Cell<'_>> {
+    lazy_static! {
+        static ref OPEN: Regex = Regex::new(r"<(t[hd])\b([^>]*)>").unwrap();
+        static ref SPAN: Regex = Regex::new(r#"\b(col|row)span=["']?(\d+)"#).unwrap();
+    }
+    let row = &row[..row.find("</tr>").unwrap_or(row.len())];
+    let opens: Vec<_> = OPEN.captures_iter(row).collect();
+    let ends = opens
+        .iter()
+        .skip(1)
+        .map(|c| c.get(0).unwrap().start())
+        .chain([row.len()]);
+    opens
+        .iter()
+        .zip(ends)
+        .map(|(open, end)| {
+            let content = &row[open.get(0).unwrap().end()..end];
+            let content = content.split("</t").next().unwrap_or_default();
+            let span = |kind| {
+                let spans = SPAN.captures_iter(&open[2]);
+                let mut spans = spans.filter(|s| &s[1] == kind);
+                spans.next().and_then(|s| s[2].parse().ok()).unwrap_or(1)
+            };
+            Cell {
+                header: &open[1] == "th",
+                content,
+                colspan: span("col"),
+                rowspan: span("row"),
+            }
+        })
+        .collect()
+}
+
+/// the names of the columns of the header rows, with the names of the cells spanning several
+/// header rows joined, e.g. `STORAGE_BINDING "write-only"`
+fn column_names(rows: &[Vec<Cell>]) -> Vec<String> {
+    let mut names: Vec<Vec<String>> = vec![];
+    let mut pending: Vec<usize> = vec![];
+    for row in rows {
+        let mut column = 0;
+        for cell in row {
+            while pending.get(column).is_some_and(|&p| p > 0) {
+                column += 1;
+            }
+            let name = normalize_whitespace(&bikeshed::strip_markup(cell.content));
+            for c in column..column + cell.colspan {
+                if names.len() <= c {
+                    names.resize(c + 1, vec![]);
+                    pending.resize(c + 1, 0);
+                }
+                names[c].push(name.clone());
+                pending[c] = cell.rowspan;
+            }
+            column += cell.colspan;
+        }
+        pending.iter_mut().for_each(|p| *p = p.saturating_sub(1));
+    }
+    names.into_iter().map(|n| n.join(" ")).collect()
+}
+
+impl FormatCapabilities {
+    /// the rows of the tables with a `STORAGE_BINDING` column, whose first cell names a `GPUTextureFormat`
+    pub fn extract(text: &str) -> Vec<Self> {
+        lazy_static! {
+            static ref FORMAT: Regex = Regex::new(r#"GPUTextureFormat/"?([\w-]+)"#).unwrap();
+            static ref SAMPLE_TYPE: Regex =
+                Regex::new(r#"GPUTextureSampleType/"?([\w-]+)"#).unwrap();
+            static ref ACCESS: Regex = Regex::new(r"(write-only|read-only|read-write)").unwrap();
+        }
+        let mut capabilities = vec![];
+        for (offset, _) in text.match_indices("<table") {
+            let table = &text[offset..];
+            let table = &table[..table.find("</table>").unwrap_or(table.len())];
+            let rows: Vec<_> = table.split("<tr").skip(1).map(cells).collect();
+            let (header, rows): (Vec<_>, Vec<_>) =
+                rows.into_iter().partition(|r| r.iter().all(|c| c.header));
+            let columns = column_names(&header);
+            if !columns.iter().any(|c| c.contains("STORAGE_BINDING")) {
+                continue;
+            }
+            for row in rows {
+                let Some(format) = row.first().and_then(|c| FORMAT.captures(c.content)) else {
+                    continue;
+                };
+                let mut sample_types = vec![];
+                let mut storage = vec![];
+                let mut column = 0;
+                for cell in &row {
+                    let name = columns.get(column).map_or("", String::as_str);
+                    if name.contains("GPUTextureSampleType") {
+                        let types = SAMPLE_TYPE.captures_iter(cell.content);
+                        sample_types.extend(types.map(|t| t[1].to_string()));
+                    }
+                    if name.contains("STORAGE_BINDING") {
+                        let access = ACCESS.find(name).map(|a| a.as_str().to_string());
+                        storage.push((access, Support::from_cell(cell.content)));
+                    }
+                    column += cell.colspan;
+                }
+                capabilities.push(FormatCapabilities {
+                    format: format[1].to_string(),
+                    sample_types,
+                    storage,
+                });
+            }
+        }
+        capabilities
+    }
+
+    /// true if the format can be used for a storage texture with some access, maybe only with a feature
+    pub fn supports_storage(&self) -> bool {
+        self.storage
+            .iter()
+            .any(|(_, support)| support.is_supported())
+    }
+
+    /// the WGSL channel types of the sample types, e.g. `f32` for `unfilterable-float`
+    pub fn channel_types(&self) -> Vec<&'static str> {
+        let mut types = vec![];
+        for sample_type in &self.sample_types {
+            let ty = match sample_type.as_str() {
+                "sint" => "i32",
+                "uint" => "u32",
+                _ => "f32",
+            };
+            if !types.contains(&ty) {
+                types.push(ty);
+            }
+        }
+        types
+    }
+}
+
+/// a difference between the WGSL texel formats and the `GPUTextureFormat`s with storage capability
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TexelFormatMismatch {
+    /// a WGSL texel format which is not a `GPUTextureFormat` value
+    NotATextureFormat(String),
+    /// a WGSL texel format which no capabilities table row lists
+    NoCapabilities(String),
+    /// a WGSL texel format whose `GPUTextureFormat` cannot be bound as a storage texture
+    NoStorageCapability(String),
+    /// a WGSL texel format whose channel type is not one of the sample types of the `GPUTextureFormat`
+    ChannelType {
+        format: String,
+        channel_type: &'static str,
+        sample_types: Vec<String>,
+    },
+    /// a `GPUTextureFormat` with storage capability which is not a WGSL texel format
+    NotATexelFormat(String),
+}
+
+impl Display for TexelFormatMismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TexelFormatMismatch::NotATextureFormat(format) => {
+                write!(f, "texel format {format} is not a GPUTextureFormat")
+            }
+            TexelFormatMismatch::NoCapabilities(format) => {
+                write!(f, "texel format {format} has no capabilities table row")
+            }
+            TexelFormatMismatch::NoStorageCapability(format) => {
+                write!(f, "texel format {format} has no STORAGE_BINDING capability")
+            }
+            TexelFormatMismatch::ChannelType {
+                format,
+                channel_type,
+                sample_types,
+            } => write!(
+                f,
+                "texel format {format} has channel type {channel_type}, but sample types {}",
+                sample_types.join(", ")
+            ),
+            TexelFormatMismatch::NotATexelFormat(format) => write!(
+                f,
+                "GPUTextureFormat {format} has STORAGE_BINDING capability, but is not a texel format"
+            ),
+        }
+    }
+}
+
+/// the mismatches found by [`check_texel_formats`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TexelFormatMismatches(pub Vec<TexelFormatMismatch>);
+
+impl Display for TexelFormatMismatches {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for mismatch in &self.0 {
+            writeln!(f, "{mismatch}")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for TexelFormatMismatches {}
+
+/// checks that every texel format of the WGSL predeclared enumerants table is a `GPUTextureFormat`
+/// with `STORAGE_BINDING` capability and a matching sample type, and that every format with that
+/// capability is a texel format. the [`TexelFormat`]s are used if the WGSL spec has no such table
+pub fn check_texel_formats(
+    wgsl: &WgslSpec,
+    webgpu: &WebGpuSpec,
+) -> Result<(), TexelFormatMismatches> {
+    let mut families = wgsl.enumerant_families.iter();
+    let family = families.find(|f| f.name == "texel format");
+    let texel_formats: Vec<String> = match family {
+        Some(family) => family.names.clone(),
+        None => TexelFormat::ALL
+            .iter()
+            .map(|f| f.name().to_string())
+            .collect(),
+    };
+    let enum_values = webgpu.enum_values("GPUTextureFormat").unwrap_or_default();
+    let capabilities = &webgpu.format_capabilities;
+
+    let mut mismatches = vec![];
+    for format in &texel_formats {
+        if !enum_values.contains(format) {
+            mismatches.push(TexelFormatMismatch::NotATextureFormat(format.clone()));
+            continue;
+        }
+        let Some(row) = capabilities.iter().find(|c| c.format == *format) else {
+            mismatches.push(TexelFormatMismatch::NoCapabilities(format.clone()));
+            continue;
+        };
+        if !row.supports_storage() {
+            mismatches.push(TexelFormatMismatch::NoStorageCapability(format.clone()));
+        }
+        let channel_type = TexelFormat::from_name(format).map(|f| f.channel_type());
+        if let Some(channel_type) = channel_type {
+            if !row.sample_types.is_empty() && !row.channel_types().contains(&channel_type) {
+                mismatches.push(TexelFormatMismatch::ChannelType {
+                    format: format.clone(),
+                    channel_type,
+                    sample_types: row.sample_types.clone(),
+                });
+            }
+        }
+    }
+    for row in capabilities.iter().filter(|c| c.supports_storage()) {
+        if !texel_formats.contains(&row.format) {
+            mismatches.push(TexelFormatMismatch::NotATexelFormat(row.format.clone()));
+        }
+    }
+    match mismatches.is_empty() {
+        true => Ok(()),
+        false => Err(TexelFormatMismatches(mismatches)),
+    }
+}
+
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_capabilities() {
+        let text = r#"
+<table class=data>
+    <thead>
+        <tr>
+            <th rowspan=2>Format
+            <th rowspan=2>{{GPUTextureSampleType}}
+            <th rowspan=2>{{GPUTextureUsage/RENDER_ATTACHMENT}}
+            <th colspan=3>{{GPUTextureUsage/STORAGE_BINDING}}
+        <tr>
+            <th>{{GPUStorageTextureAccess/"write-only"}}
+            <th>{{GPUStorageTextureAccess/"read-only"}}
+            <th>{{GPUStorageTextureAccess/"read-write"}}
+    </thead>
+    <tr>
+        <th>{{GPUTextureFormat/r8unorm}}
+        <td>{{GPUTextureSampleType/"float"}},<br>{{GPUTextureSampleType/"unfilterable-float"}}
+        <td>&checkmark;
+        <td><td><td>
+    <tr>
+        <th>{{GPUTextureFormat/r32sint}}
+        <td>{{GPUTextureSampleType/"sint"}}
+        <td>&checkmark;
+        <td>&checkmark;<td>&checkmark;<td>&checkmark;
+    <tr>
+        <th>{{GPUTextureFormat/r32float}}
+        <td>{{GPUTextureSampleType/"unfilterable-float"}}
+        <td>&checkmark;
+        <td>&checkmark;<td>&checkmark;<td>&checkmark;
+    <tr>
+        <th>{{GPUTextureFormat/bgra8unorm}}
+        <td>{{GPUTextureSampleType/"float"}}
+        <td>&checkmark;
+        <td colspan=3>If {{GPUFeatureName/"bgra8unorm-storage"}} is enabled
+    <tr>
+        <th>{{GPUTextureFormat/rg11b10ufloat}}
+        <td>{{GPUTextureSampleType/"float"}}
+        <td>
+        <td>&checkmark;<td><td>
+</table>
+"#;
+        let capabilities = FormatCapabilities::extract(text);
+        let formats: Vec<_> = capabilities.iter().map(|c| c.format.as_str()).collect();
+        assert_eq!(
+            formats,
+            [
+                "r8unorm",
+                "r32sint",
+                "r32float",
+                "bgra8unorm",
+                "rg11b10ufloat"
+            ]
+        );
+        assert_eq!(
+            capabilities[0].sample_types,
+            ["float", "unfilterable-float"]
+        );
+        assert_eq!(capabilities[0].channel_types(), ["f32"]);
+        assert!(!capabilities[0].supports_storage());
+        assert_eq!(
+            capabilities[1].storage[2],
+            (Some("read-write".to_string()), Support::Yes)
+        );
+        assert_eq!(
+            capabilities[3].storage,
+            [(
+                Some("write-only".to_string()),
+                Support::WithFeature("bgra8unorm-storage".to_string())
+            )]
+        );
+
+        let wgsl_text = r#"
+<table class='data'>
+  <thead><tr><th>Enumeration<th>Predeclared enumerant</thead>
+  <tr><td>[=texel format=]<td>[=texel format/r32sint=]<br>[=texel format/r32float=]<br>[=texel format/r8unorm=]<br>[=texel format/bgra8unorm=]<br>[=texel format/rgba8snorm=]
+</table>"#;
+        let (_, wgsl) = WgslSpec::parse_bs(wgsl_text).unwrap();
+        let webgpu_text = format!(
+            "<script type=idl>\nenum GPUTextureFormat {{ \"r8unorm\", \"r32sint\", \"r32float\", \"bgra8unorm\", \"rg11b10ufloat\" }};\n</script>\n{text}"
+        );
+        let webgpu = WebGpuSpec::parse_bs(&webgpu_text);
+        let mismatches = check_texel_formats(&wgsl, &webgpu).unwrap_err();
+        assert_eq!(
+            mismatches.0,
+            [
+                TexelFormatMismatch::NoStorageCapability("r8unorm".to_string()),
+                TexelFormatMismatch::NotATextureFormat("rgba8snorm".to_string()),
+                TexelFormatMismatch::NotATexelFormat("rg11b10ufloat".to_string()),
+            ]
+        );
+        assert_eq!(
+            mismatches.0[2].to_string(),
+            "GPUTextureFormat rg11b10ufloat has STORAGE_BINDING capability, but is not a texel format"
+        );
+    }
+}
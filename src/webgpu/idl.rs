@@ -0,0 +1,694 @@
+use std::fmt::Display;
+
+use lazy_static::lazy_static;
+use nom::{
+    bytes::complete::{take_until, take_while1},
+    combinator::verify,
+    multi::many1,
+};
+use regex::Regex;
+
+use crate::{diagnostic::Diagnostic, fn_name, nom_prelude::*, span::*};
+
+/// whitespace and `//` or `/* */` comments
+fn sp(s: &str) -> NomResult<&str, ()> {
+    let line_comment = preceded(tag("//"), take_till(|c| c == '\n'));
+    let block_comment = delimited(tag("/*"), take_until("*/"), tag("*/"));
+    map(many0_count(alt((ws1, line_comment, block_comment))), |_| ())(s)
+}
+
+fn sp_then<'a, O, F>(f: F) -> impl FnMut(&'a str) -> NomResult<&'a str, O>
+where
+    F: Parser<&'a str, O, NomError<&'a str>>,
+{
+    preceded(sp, f)
+}
+
+fn symbol<'a>(symbol: &'static str) -> impl FnMut(&'a str) -> NomResult<&'a str, &'a str> {
+    sp_then(tag(symbol))
+}
+
+/// an identifier which is exactly `keyword`, so that `long` does not match the start of `longest`
+fn keyword<'a>(keyword: &'static str) -> impl FnMut(&'a str) -> NomResult<&'a str, &'a str> {
+    sp_then(verify(identifier, move |i: &str| i == keyword))
+}
+
+fn name(s: &str) -> NomResult<&str, String> {
+    // a leading `_` escapes a keyword used as a name
+    map(sp_then(identifier), |n: &str| {
+        n.trim_start_matches('_').to_string()
+    })(s)
+}
+
+/// the extended attributes of a definition, member or type, e.g. `[Exposed=(Window, Worker), SecureContext]`
+fn extended_attributes(s: &str) -> NomResult<&str, Vec<String>> {
+    let list = delimited(symbol("["), take_till(|c| c == ']'), tag("]"));
+    let parser = map(opt(list), |list: Option<&str>| {
+        let Some(list) = list else {
+            return vec![];
+        };
+        let mut attributes = vec![];
+        let mut depth = 0;
+        let mut start = 0;
+        for (i, c) in list.char_indices() {
+            match c {
+                '(' => depth += 1,
+                ')' => depth -= 1,
+                ',' if depth == 0 => {
+                    attributes.push(list[start..i].trim().to_string());
+                    start = i + 1;
+                }
+                _ => {}
+            }
+        }
+        attributes.push(list[start..].trim().to_string());
+        attributes.retain(|a| !a.is_empty());
+        attributes
+    });
+    context(fn_name!(), parser)(s)
+}
+
+/// a type, e.g. `unsigned long long`, `Promise<undefined>`, `(GPUBuffer or GPUTexture)?`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IdlType {
+    Named { name: String, params: Vec<IdlType> },
+    Union(Vec<IdlType>),
+    Nullable(Box<IdlType>),
+}
+
+impl Display for IdlType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let list = |types: &[IdlType], separator| {
+            let types = types.iter().map(IdlType::to_string);
+            types.collect::<Vec<_>>().join(separator)
+        };
+        match self {
+            IdlType::Named { name, params } if params.is_empty() => write!(f, "{name}"),
+            IdlType::Named { name, params } => write!(f, "{name}<{}>", list(params, ", ")),
+            IdlType::Union(types) => write!(f, "({})", list(types, " or ")),
+            IdlType::Nullable(ty) => write!(f, "{ty}?"),
+        }
+    }
+}
+
+impl IdlType {
+    pub fn parse(s: &str) -> NomResult<&str, Self> {
+        // `unsigned long long`, `unrestricted double`
+        let prefix = opt(alt((keyword("unsigned"), keyword("unrestricted"))));
+        let primitive = tuple((prefix, sp_then(identifier), opt(keyword("long"))));
+        let primitive = map(primitive, |(prefix, name, long)| {
+            let words = [prefix, Some(name), long];
+            words.into_iter().flatten().collect::<Vec<_>>().join(" ")
+        });
+        let params = delimited(
+            symbol("<"),
+            separated_list1(symbol(","), IdlType::parse),
+            symbol(">"),
+        );
+        let named = map(pair(primitive, opt(params)), |(name, params)| {
+            IdlType::Named {
+                name,
+                params: params.unwrap_or_default(),
+            }
+        });
+        let union = delimited(
+            symbol("("),
+            separated_list1(keyword("or"), IdlType::parse),
+            symbol(")"),
+        );
+        let ty = alt((map(union, IdlType::Union), named));
+        let parser = preceded(
+            extended_attributes,
+            map(
+                pair(ty, opt(symbol("?"))),
+                |(ty, nullable)| match nullable {
+                    Some(_) => IdlType::Nullable(Box::new(ty)),
+                    None => ty,
+                },
+            ),
+        );
+        context(fn_name!(), parser)(s)
+    }
+
+    /// the type without `?`
+    pub fn non_nullable(&self) -> &IdlType {
+        match self {
+            IdlType::Nullable(ty) => ty.non_nullable(),
+            ty => ty,
+        }
+    }
+}
+
+/// a default value or constant, as written, e.g. `1`, `"2d"`, `{}` or `0x0001`
+fn value(s: &str) -> NomResult<&str, String> {
+    let parser = take_while1(|c| !matches!(c, ';' | ',' | ')'));
+    map(context(fn_name!(), sp_then(parser)), |v: &str| {
+        v.trim().to_string()
+    })(s)
+}
+
+/// an argument of an operation or constructor, e.g. `optional GPUSize64 offset = 0`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Argument {
+    pub name: String,
+    pub ty: IdlType,
+    pub optional: bool,
+    /// `...`
+    pub variadic: bool,
+    pub default: Option<String>,
+}
+
+impl Argument {
+    pub fn parse(s: &str) -> NomResult<&str, Self> {
+        let parser = tuple((
+            preceded(extended_attributes, opt(keyword("optional"))),
+            IdlType::parse,
+            opt(symbol("...")),
+            name,
+            opt(preceded(symbol("="), value)),
+        ));
+        map(
+            context(fn_name!(), parser),
+            |(optional, ty, variadic, name, default)| Argument {
+                name,
+                ty,
+                optional: optional.is_some(),
+                variadic: variadic.is_some(),
+                default,
+            },
+        )(s)
+    }
+}
+
+fn arguments(s: &str) -> NomResult<&str, Vec<Argument>> {
+    delimited(
+        symbol("("),
+        separated_list0(symbol(","), Argument::parse),
+        symbol(")"),
+    )(s)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MemberKind {
+    Attribute {
+        readonly: bool,
+    },
+    /// a regular or special (e.g. `getter`) operation, the name of a special operation may be empty
+    Operation {
+        arguments: Vec<Argument>,
+    },
+    Constructor {
+        arguments: Vec<Argument>,
+    },
+    Const {
+        value: String,
+    },
+    /// a dictionary member
+    Field {
+        required: bool,
+        default: Option<String>,
+    },
+    /// e.g. `readonly setlike<DOMString>`, `iterable<T>` or `maplike<K, V>`, as written
+    Declaration(String),
+}
+
+/// a member of an interface, namespace or dictionary
+#[derive(Debug, Clone)]
+pub struct Member {
+    pub name: String,
+    /// the type of an attribute, field or constant, or the result type of an operation
+    pub ty: Option<IdlType>,
+    pub kind: MemberKind,
+    /// e.g. `static` or `getter`
+    pub specials: Vec<String>,
+    pub extended_attributes: Vec<String>,
+    pub span: Span,
+}
+
+crate::eq_ignoring_span!(Member {
+    name,
+    ty,
+    kind,
+    specials,
+    extended_attributes
+});
+
+impl Member {
+    /// a member of an interface, mixin or namespace
+    pub fn parse_interface_member(s: &str) -> NomResult<&str, Self> {
+        let constructor = map(preceded(keyword("constructor"), arguments), |arguments| {
+            (String::new(), None, MemberKind::Constructor { arguments })
+        });
+        let constant = map(
+            tuple((
+                preceded(keyword("const"), IdlType::parse),
+                name,
+                preceded(symbol("="), value),
+            )),
+            |(ty, name, value)| (name, Some(ty), MemberKind::Const { value }),
+        );
+        let declaration = recognize(tuple((
+            opt(keyword("readonly")),
+            alt((keyword("setlike"), keyword("maplike"), keyword("iterable"))),
+            take_till(|c| c == ';'),
+        )));
+        let declaration = map(sp_then(declaration), |d: &str| {
+            let d = crate::misc::normalize_whitespace(d);
+            (String::new(), None, MemberKind::Declaration(d))
+        });
+        let attribute = map(
+            tuple((
+                opt(keyword("readonly")),
+                preceded(keyword("attribute"), IdlType::parse),
+                name,
+            )),
+            |(readonly, ty, name)| {
+                let readonly = readonly.is_some();
+                (name, Some(ty), MemberKind::Attribute { readonly })
+            },
+        );
+        let operation = map(
+            tuple((IdlType::parse, opt(name), arguments)),
+            |(ty, name, arguments)| {
+                let kind = MemberKind::Operation { arguments };
+                (name.unwrap_or_default(), Some(ty), kind)
+            },
+        );
+        let special = alt((
+            keyword("static"),
+            keyword("stringifier"),
+            keyword("inherit"),
+            keyword("getter"),
+            keyword("setter"),
+            keyword("deleter"),
+        ));
+        let member = pair(
+            many0(special),
+            alt((constructor, constant, declaration, attribute, operation)),
+        );
+        let parser = terminated(
+            preceded(sp, spanned(pair(extended_attributes, member))),
+            symbol(";"),
+        );
+        map(
+            context(fn_name!(), parser),
+            |((extended_attributes, (specials, (name, ty, kind))), span)| Member {
+                name,
+                ty,
+                kind,
+                specials: specials.into_iter().map(str::to_string).collect(),
+                extended_attributes,
+                span,
+            },
+        )(s)
+    }
+
+    /// a dictionary member like `GPUSize32 sampleCount = 1;`
+    pub fn parse_dictionary_member(s: &str) -> NomResult<&str, Self> {
+        let member = tuple((
+            extended_attributes,
+            opt(keyword("required")),
+            IdlType::parse,
+            name,
+            opt(preceded(symbol("="), value)),
+        ));
+        let parser = terminated(preceded(sp, spanned(member)), symbol(";"));
+        map(
+            context(fn_name!(), parser),
+            |((extended_attributes, required, ty, name, default), span)| Member {
+                name,
+                ty: Some(ty),
+                kind: MemberKind::Field {
+                    required: required.is_some(),
+                    default,
+                },
+                specials: vec![],
+                extended_attributes,
+                span,
+            },
+        )(s)
+    }
+}
+
+/// the `{ ... }` body of a definition
+fn members<'a>(
+    member: fn(&'a str) -> NomResult<&'a str, Member>,
+) -> impl FnMut(&'a str) -> NomResult<&'a str, Vec<Member>> {
+    delimited(symbol("{"), many0(member), symbol("}"))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DefinitionKind {
+    Interface,
+    InterfaceMixin,
+    CallbackInterface,
+    Namespace,
+    Dictionary,
+    Enum,
+    Typedef,
+    Callback,
+    /// `A includes B;`, with `B` as the [`Definition::inherits`]
+    Includes,
+}
+
+/// a top level definition of a WebIDL block
+#[derive(Debug, Clone)]
+pub struct Definition {
+    pub kind: DefinitionKind,
+    pub name: String,
+    pub partial: bool,
+    /// the parent interface or dictionary, or the mixin of an `includes` statement
+    pub inherits: Option<String>,
+    pub members: Vec<Member>,
+    /// the values of an enum, without quotes
+    pub values: Vec<String>,
+    /// the type of a typedef, or the result type of a callback
+    pub ty: Option<IdlType>,
+    pub extended_attributes: Vec<String>,
+    pub span: Span,
+}
+
+crate::eq_ignoring_span!(Definition {
+    kind,
+    name,
+    partial,
+    inherits,
+    members,
+    values,
+    ty,
+    extended_attributes
+});
+
+impl Definition {
+    fn new(kind: DefinitionKind, name: String) -> Self {
+        Definition {
+            kind,
+            name,
+            partial: false,
+            inherits: None,
+            members: vec![],
+            values: vec![],
+            ty: None,
+            extended_attributes: vec![],
+            span: Span::default(),
+        }
+    }
+
+    pub fn parse(s: &str) -> NomResult<&str, Self> {
+        let inherits = || opt(preceded(symbol(":"), name));
+        let interface_kind = alt((
+            map(pair(keyword("interface"), keyword("mixin")), |_| {
+                DefinitionKind::InterfaceMixin
+            }),
+            map(pair(keyword("callback"), keyword("interface")), |_| {
+                DefinitionKind::CallbackInterface
+            }),
+            map(keyword("interface"), |_| DefinitionKind::Interface),
+            map(keyword("namespace"), |_| DefinitionKind::Namespace),
+        ));
+        let interface = map(
+            tuple((
+                interface_kind,
+                name,
+                inherits(),
+                members(Member::parse_interface_member),
+            )),
+            |(kind, name, inherits, members)| Definition {
+                inherits,
+                members,
+                ..Definition::new(kind, name)
+            },
+        );
+        let dictionary = map(
+            tuple((
+                preceded(keyword("dictionary"), name),
+                inherits(),
+                members(Member::parse_dictionary_member),
+            )),
+            |(name, inherits, members)| Definition {
+                inherits,
+                members,
+                ..Definition::new(DefinitionKind::Dictionary, name)
+            },
+        );
+        let string = delimited(symbol("\""), take_till(|c| c == '"'), tag("\""));
+        let values = terminated(separated_list0(symbol(","), string), opt(symbol(",")));
+        let enumeration = map(
+            pair(
+                preceded(keyword("enum"), name),
+                delimited(symbol("{"), values, symbol("}")),
+            ),
+            |(name, values)| Definition {
+                values: values.into_iter().map(str::to_string).collect(),
+                ..Definition::new(DefinitionKind::Enum, name)
+            },
+        );
+        let typedef = map(
+            pair(preceded(keyword("typedef"), IdlType::parse), name),
+            |(ty, name)| Definition {
+                ty: Some(ty),
+                ..Definition::new(DefinitionKind::Typedef, name)
+            },
+        );
+        let callback = map(
+            tuple((
+                preceded(keyword("callback"), name),
+                preceded(symbol("="), IdlType::parse),
+                arguments,
+            )),
+            |(name, ty, arguments)| {
+                let operation = Member {
+                    name: String::new(),
+                    ty: Some(ty.clone()),
+                    kind: MemberKind::Operation { arguments },
+                    specials: vec![],
+                    extended_attributes: vec![],
+                    span: Span::default(),
+                };
+                Definition {
+                    ty: Some(ty),
+                    members: vec![operation],
+                    ..Definition::new(DefinitionKind::Callback, name)
+                }
+            },
+        );
+        let includes = map(
+            separated_pair(name, keyword("includes"), name),
+            |(name, mixin)| Definition {
+                inherits: Some(mixin),
+                ..Definition::new(DefinitionKind::Includes, name)
+            },
+        );
+        let definition = tuple((
+            extended_attributes,
+            opt(keyword("partial")),
+            alt((
+                interface,
+                dictionary,
+                enumeration,
+                typedef,
+                callback,
+                includes,
+            )),
+        ));
+        let parser = terminated(preceded(sp, spanned(definition)), symbol(";"));
+        map(
+            context(fn_name!(), parser),
+            |((extended_attributes, partial, definition), span)| Definition {
+                partial: partial.is_some(),
+                extended_attributes,
+                span,
+                ..definition
+            },
+        )(s)
+    }
+
+    pub fn member(&self, name: &str) -> Option<&Member> {
+        self.members.iter().find(|m| m.name == name)
+    }
+}
+
+/// the contents of the `<script type=idl>` and `<pre class=idl>` blocks of a bikeshed document
+pub fn idl_blocks(text: &str) -> Vec<&str> {
+    lazy_static! {
+        static ref OPEN: Regex =
+            Regex::new(r#"<(script|pre)\b[^>]*\b(?:type|class)=["']?idl\b[^>]*>"#).unwrap();
+    }
+    OPEN.captures_iter(text)
+        .filter_map(|c| {
+            let open = c.get(0).unwrap();
+            let block = &text[open.end()..];
+            let close = format!("</{}>", &c[1]);
+            block.find(&close).map(|end| &block[..end])
+        })
+        .collect()
+}
+
+/// the definitions of all WebIDL blocks of `text`, and the errors of the blocks which could not
+/// be parsed. the spans point into `text`, if it is the source
+pub fn extract(text: &str) -> (Vec<Definition>, Vec<Diagnostic>) {
+    let mut definitions = vec![];
+    let mut unparsed = vec![];
+    for block in idl_blocks(text) {
+        let parser = terminated(many1(preceded(sp, Definition::parse)), pair(sp, eof));
+        match context(fn_name!(), parser)(block) {
+            Ok((_, parsed)) => definitions.extend(parsed),
+            Err(nom::Err::Error(e) | nom::Err::Failure(e)) => {
+                unparsed.push(Diagnostic::from_nom(text, &e))
+            }
+            Err(nom::Err::Incomplete(_)) => unreachable!(),
+        }
+    }
+    (definitions, unparsed)
+}
+
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_idl() {
+        let text = r#"
+<script type=idl>
+[Exposed=(Window, Worker), SecureContext]
+interface GPUSupportedLimits {
+    readonly attribute unsigned long maxTextureDimension1D;
+    readonly attribute unsigned long long maxBufferSize;
+};
+
+[Exposed=(Window, Worker), SecureContext]
+interface GPUSupportedFeatures {
+    readonly setlike<DOMString>;
+};
+</script>
+
+<script type=idl>
+enum GPUTextureFormat {
+    // 8-bit formats
+    "r8unorm",
+    "r8snorm",
+    /* packed */ "rgb10a2unorm",
+};
+
+dictionary GPUTextureDescriptor
+         : GPUObjectDescriptorBase {
+    required GPUExtent3D size;
+    GPUIntegerCoordinate mipLevelCount = 1;
+    GPUTextureDimension dimension = "2d";
+    sequence<GPUTextureFormat> viewFormats = [];
+};
+
+typedef [EnforceRange] unsigned long GPUBufferDynamicOffset;
+typedef (sequence<double> or GPUColorDict) GPUColor;
+
+interface GPUBuffer {
+    readonly attribute GPUSize64Out size;
+    Promise<undefined> mapAsync(GPUMapModeFlags mode, optional GPUSize64 offset = 0, optional GPUSize64 size);
+    undefined unmap();
+};
+GPUBuffer includes GPUObjectBase;
+
+[Exposed=(Window, Worker), SecureContext]
+namespace GPUBufferUsage {
+    const GPUFlagsConstant MAP_READ = 0x0001;
+};
+
+interface GPUPipelineError : DOMException {
+    constructor(optional DOMString message = "", GPUPipelineErrorInit options);
+};
+</script>
+
+<script type=idl>
+interface Broken {
+    readonly attribute;
+};
+</script>
+"#;
+        let (definitions, unparsed) = with_source(text, || extract(text));
+        let names: Vec<_> = definitions.iter().map(|d| d.name.as_str()).collect();
+        assert_eq!(
+            names,
+            [
+                "GPUSupportedLimits",
+                "GPUSupportedFeatures",
+                "GPUTextureFormat",
+                "GPUTextureDescriptor",
+                "GPUBufferDynamicOffset",
+                "GPUColor",
+                "GPUBuffer",
+                "GPUBuffer",
+                "GPUBufferUsage",
+                "GPUPipelineError",
+            ]
+        );
+        assert_eq!(unparsed.len(), 1);
+
+        let limits = &definitions[0];
+        assert_eq!(
+            limits.extended_attributes,
+            ["Exposed=(Window, Worker)", "SecureContext"]
+        );
+        let max_buffer_size = limits.member("maxBufferSize").unwrap();
+        assert_eq!(
+            max_buffer_size.ty.as_ref().unwrap().to_string(),
+            "unsigned long long"
+        );
+        assert_eq!(max_buffer_size.span.line, 6);
+        assert_eq!(
+            definitions[1].members[0].kind,
+            MemberKind::Declaration("readonly setlike<DOMString>".to_string())
+        );
+
+        assert_eq!(
+            definitions[2].values,
+            ["r8unorm", "r8snorm", "rgb10a2unorm"]
+        );
+
+        let descriptor = &definitions[3];
+        assert_eq!(
+            descriptor.inherits.as_deref(),
+            Some("GPUObjectDescriptorBase")
+        );
+        assert_eq!(
+            descriptor.member("size").unwrap().kind,
+            MemberKind::Field {
+                required: true,
+                default: None
+            }
+        );
+        assert_eq!(
+            descriptor.member("dimension").unwrap().kind,
+            MemberKind::Field {
+                required: false,
+                default: Some("\"2d\"".to_string())
+            }
+        );
+        let view_formats = descriptor.member("viewFormats").unwrap();
+        assert_eq!(
+            view_formats.ty.as_ref().unwrap().to_string(),
+            "sequence<GPUTextureFormat>"
+        );
+
+        assert_eq!(
+            definitions[5].ty.as_ref().unwrap().to_string(),
+            "(sequence<double> or GPUColorDict)"
+        );
+        let MemberKind::Operation { arguments } = &definitions[6].members[1].kind else {
+            panic!("not an operation");
+        };
+        assert_eq!(arguments.len(), 3);
+        assert!(arguments[1].optional);
+        assert_eq!(arguments[1].default.as_deref(), Some("0"));
+        assert_eq!(definitions[7].kind, DefinitionKind::Includes);
+        assert_eq!(definitions[7].inherits.as_deref(), Some("GPUObjectBase"));
+        assert_eq!(
+            definitions[8].members[0].kind,
+            MemberKind::Const {
+                value: "0x0001".to_string()
+            }
+        );
+        let MemberKind::Constructor { arguments } = &definitions[9].members[0].kind else {
+            panic!("not a constructor");
+        };
+        assert_eq!(arguments[0].default.as_deref(), Some("\"\""));
+    }
+}
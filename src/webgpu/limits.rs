@@ -0,0 +1,277 @@
+use std::fmt::Display;
+
+use lazy_static::lazy_static;
+use regex::Regex;
+
+use crate::{
+    bikeshed,
+    misc::normalize_whitespace,
+    wgsl::enumerants::{AddressSpace, BuiltinValue},
+};
+
+/// how the value of a limit is compared with the value requested by an application
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LimitClass {
+    /// a better limit is a higher value
+    Maximum,
+    /// a better limit is a lower value, always a power of 2
+    Alignment,
+}
+
+impl LimitClass {
+    fn from_cell(cell: &str) -> Option<Self> {
+        let cell = bikeshed::strip_markup(cell);
+        match cell.rsplit('/').next()?.trim() {
+            "maximum" => Some(LimitClass::Maximum),
+            "alignment" => Some(LimitClass::Alignment),
+            _ => None,
+        }
+    }
+}
+
+/// a value of the limits table as written, e.g. `2^28 (268,435,456 bytes)`, with the number
+/// it starts with
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LimitValue {
+    pub text: String,
+    pub value: Option<u64>,
+}
+
+impl LimitValue {
+    fn from_cell(cell: &str) -> Self {
+        lazy_static! {
+            static ref NUMBER: Regex = Regex::new(r"^(\d[\d,]*)(?:\s*\^\s*(\d+))?").unwrap();
+        }
+        let text = normalize_whitespace(&bikeshed::strip_markup(cell));
+        let value = NUMBER.captures(&text).and_then(|c| {
+            let base: u64 = c[1].replace(',', "").parse().ok()?;
+            match c.get(2) {
+                Some(exp) => base.checked_pow(exp.as_str().parse().ok()?),
+                None => Some(base),
+            }
+        });
+        LimitValue { text, value }
+    }
+}
+
+impl Display for LimitValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.text)
+    }
+}
+
+/// a piece of WGSL whose validation depends on a limit
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WgslUse {
+    /// an attribute, e.g. `workgroup_size` for `@workgroup_size`
+    Attribute(&'static str),
+    /// a value of the `@builtin` attribute
+    Builtin(BuiltinValue),
+    /// the variables declared in an address space, e.g. `var<workgroup>`
+    AddressSpace(AddressSpace),
+    /// the variables of a type, e.g. `texture_storage_*`
+    Type(&'static str),
+}
+
+impl Display for WgslUse {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WgslUse::Attribute(name) => write!(f, "@{name}"),
+            WgslUse::Builtin(value) => write!(f, "@builtin({value})"),
+            WgslUse::AddressSpace(space) => write!(f, "var<{space}>"),
+            WgslUse::Type(name) => write!(f, "{name}"),
+        }
+    }
+}
+
+const WORKGROUP_SIZE: WgslUse = WgslUse::Attribute("workgroup_size");
+const STORAGE: WgslUse = WgslUse::AddressSpace(AddressSpace::Storage);
+const UNIFORM: WgslUse = WgslUse::AddressSpace(AddressSpace::Uniform);
+const LOCAL_INVOCATION_ID: WgslUse = WgslUse::Builtin(BuiltinValue::LocalInvocationId);
+
+/// the limits which constrain WGSL shader modules and their pipelines
+pub const WGSL_USES: &[(&str, &[WgslUse])] = &[
+    ("maxBindGroups", &[WgslUse::Attribute("group")]),
+    ("maxBindingsPerBindGroup", &[WgslUse::Attribute("binding")]),
+    ("maxSamplersPerShaderStage", &[WgslUse::Type("sampler")]),
+    ("maxStorageBuffersPerShaderStage", &[STORAGE]),
+    (
+        "maxStorageTexturesPerShaderStage",
+        &[WgslUse::Type("texture_storage_*")],
+    ),
+    ("maxUniformBuffersPerShaderStage", &[UNIFORM]),
+    ("maxUniformBufferBindingSize", &[UNIFORM]),
+    ("maxStorageBufferBindingSize", &[STORAGE]),
+    ("maxVertexAttributes", &[WgslUse::Attribute("location")]),
+    (
+        "maxInterStageShaderVariables",
+        &[WgslUse::Attribute("location")],
+    ),
+    ("maxColorAttachments", &[WgslUse::Attribute("location")]),
+    (
+        "maxComputeWorkgroupStorageSize",
+        &[WgslUse::AddressSpace(AddressSpace::Workgroup)],
+    ),
+    (
+        "maxComputeInvocationsPerWorkgroup",
+        &[
+            WORKGROUP_SIZE,
+            WgslUse::Builtin(BuiltinValue::LocalInvocationIndex),
+        ],
+    ),
+    (
+        "maxComputeWorkgroupSizeX",
+        &[WORKGROUP_SIZE, LOCAL_INVOCATION_ID],
+    ),
+    (
+        "maxComputeWorkgroupSizeY",
+        &[WORKGROUP_SIZE, LOCAL_INVOCATION_ID],
+    ),
+    (
+        "maxComputeWorkgroupSizeZ",
+        &[WORKGROUP_SIZE, LOCAL_INVOCATION_ID],
+    ),
+    (
+        "maxComputeWorkgroupsPerDimension",
+        &[
+            WgslUse::Builtin(BuiltinValue::WorkgroupId),
+            WgslUse::Builtin(BuiltinValue::NumWorkgroups),
+            WgslUse::Builtin(BuiltinValue::GlobalInvocationId),
+        ],
+    ),
+];
+
+/// a row of the limits table of the WebGPU spec
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Limit {
+    /// e.g. `maxBindGroups`
+    pub name: String,
+    /// the IDL type, e.g. `GPUSize32`
+    pub ty: String,
+    pub class: Option<LimitClass>,
+    pub default: LimitValue,
+    /// the values of the other columns, e.g. of a compatibility mode or a tier, by column name
+    pub tiers: Vec<(String, LimitValue)>,
+    /// the text of the continuation row following the limit
+    pub description: String,
+    /// byte offset of the row in the document
+    pub offset: usize,
+}
+
+impl Limit {
+    /// extracts the rows of the tables with a "limit class" column
+    pub fn extract(text: &str) -> Vec<Limit> {
+        let mut limits: Vec<Limit> = vec![];
+        for table in bikeshed::tables(text) {
+            if !table.has_column("limit class") {
+                continue;
+            }
+            let column = |name: &str| {
+                let mut header = table.header.iter();
+                header.position(|h| h.to_lowercase().contains(name))
+            };
+            let ty = column("type").unwrap_or(1);
+            let class = column("class").unwrap_or(2);
+            let default = column("default").unwrap_or(3);
+
+            for row in &table.rows {
+                if row.raw.contains("row-continuation") {
+                    if let Some(limit) = limits.last_mut() {
+                        let cells = row.cells.iter().map(|c| bikeshed::strip_markup(c));
+                        let description = cells.collect::<Vec<_>>().join(" ");
+                        limit.description = normalize_whitespace(&description);
+                    }
+                    continue;
+                }
+                let cell = |i: usize| row.cells.get(i).copied().unwrap_or_default();
+                let name = normalize_whitespace(&bikeshed::strip_markup(cell(0)));
+                if name.is_empty() {
+                    continue;
+                }
+                let tiers = (0..row.cells.len())
+                    .filter(|i| ![0, ty, class, default].contains(i))
+                    .map(|i| {
+                        let header = table.header.get(i).cloned().unwrap_or_default();
+                        (header, LimitValue::from_cell(cell(i)))
+                    });
+                limits.push(Limit {
+                    name,
+                    ty: normalize_whitespace(&bikeshed::strip_markup(cell(ty))),
+                    class: LimitClass::from_cell(cell(class)),
+                    default: LimitValue::from_cell(cell(default)),
+                    tiers: tiers.collect(),
+                    description: String::new(),
+                    offset: row.offset,
+                });
+            }
+        }
+        limits
+    }
+
+    /// the pieces of WGSL whose validation depends on the limit
+    pub fn wgsl_uses(&self) -> &'static [WgslUse] {
+        let uses = WGSL_USES.iter().find(|(name, _)| *name == self.name);
+        uses.map(|(_, uses)| *uses).unwrap_or_default()
+    }
+}
+
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_limits() {
+        let text = r#"
+<table class="data no-colspan-center limits-table">
+    <thead>
+        <tr><th>Limit name <th>Type <th>[=Limit class=] <th>Default <th>Compatibility mode default
+    </thead>
+    <tr><td><dfn>maxBindGroups</dfn>
+        <td>{{GPUSize32}} <td>[=limit class/maximum=] <td>4 <td>4
+    <tr class=row-continuation><td colspan=5>
+        The maximum number of {{GPUBindGroupLayout|GPUBindGroupLayouts}}
+        allowed in {{GPUPipelineLayoutDescriptor/bindGroupLayouts}}.
+    <tr><td><dfn>minUniformBufferOffsetAlignment</dfn>
+        <td>{{GPUSize32}} <td>[=limit class/alignment=] <td>256 bytes <td>256 bytes
+    <tr><td><dfn>maxStorageBufferBindingSize</dfn>
+        <td>{{GPUSize64}} <td>[=limit class/maximum=] <td>134217728 bytes (128 MiB)
+        <td>2<sup>27</sup>
+    <tr><td><dfn>maxComputeWorkgroupSizeX</dfn>
+        <td>{{GPUSize32}} <td>[=limit class/maximum=] <td>256 <td>128
+</table>"#;
+        let limits = Limit::extract(text);
+        let names: Vec<_> = limits.iter().map(|l| l.name.as_str()).collect();
+        assert_eq!(
+            names,
+            [
+                "maxBindGroups",
+                "minUniformBufferOffsetAlignment",
+                "maxStorageBufferBindingSize",
+                "maxComputeWorkgroupSizeX"
+            ]
+        );
+
+        let bind_groups = &limits[0];
+        assert_eq!(bind_groups.ty, "GPUSize32");
+        assert_eq!(bind_groups.class, Some(LimitClass::Maximum));
+        assert_eq!(bind_groups.default.value, Some(4));
+        assert!(bind_groups
+            .description
+            .starts_with("The maximum number of GPUBindGroupLayouts"));
+        assert_eq!(bind_groups.wgsl_uses(), [WgslUse::Attribute("group")]);
+
+        assert_eq!(limits[1].class, Some(LimitClass::Alignment));
+        assert_eq!(limits[1].default.text, "256 bytes");
+        assert_eq!(limits[1].description, "");
+        assert_eq!(limits[1].wgsl_uses(), []);
+
+        let storage = &limits[2];
+        assert_eq!(storage.default.value, Some(134217728));
+        assert_eq!(storage.tiers[0].0, "Compatibility mode default");
+        assert_eq!(storage.tiers[0].1.value, Some(1 << 27));
+
+        let size_x = &limits[3];
+        assert_eq!(size_x.tiers[0].1.value, Some(128));
+        let uses: Vec<_> = size_x.wgsl_uses().iter().map(|u| u.to_string()).collect();
+        assert_eq!(uses, ["@workgroup_size", "@builtin(local_invocation_id)"]);
+    }
+}
@@ -0,0 +1,109 @@
+use std::path::Path;
+
+use crate::diagnostic::{Diagnostic, SpecError};
+
+use self::{
+    formats::FormatCapabilities,
+    idl::{Definition, DefinitionKind, Member},
+    limits::Limit,
+};
+
+pub mod formats;
+pub mod idl;
+pub mod limits;
+
+/// the bikeshed source of the WebGPU API specification
+pub const SPEC_URL: &str = "https://raw.githubusercontent.com/gpuweb/gpuweb/main/spec/index.bs";
+
+/// the WebGPU API specification, which the WGSL specification is paired with
+pub struct WebGpuSpec {
+    pub text: String,
+    /// the definitions of the WebIDL blocks, in document order. partial definitions
+    /// and `includes` statements are kept as written
+    pub definitions: Vec<Definition>,
+    /// the errors of the WebIDL blocks which could not be parsed
+    pub unparsed: Vec<Diagnostic>,
+    /// the rows of the texture format capabilities tables
+    pub format_capabilities: Vec<FormatCapabilities>,
+    /// the rows of the limits table, in document order
+    pub limits: Vec<Limit>,
+    /// the `Date:` of the metadata block, if any
+    pub revision: Option<String>,
+}
+
+impl WebGpuSpec {
+    pub fn from_download() -> Result<Self, SpecError> {
+        let text = crate::misc::download_text(SPEC_URL).map_err(|e| SpecError::Download {
+            url: SPEC_URL.to_string(),
+            message: e.to_string(),
+        })?;
+        Ok(Self::parse_bs(&text))
+    }
+
+    /// reads a local copy of the bikeshed source, e.g. `spec/index.bs` of a gpuweb checkout
+    pub fn from_bs_file(path: impl AsRef<Path>) -> Result<Self, SpecError> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path).map_err(|e| SpecError::Read {
+            path: path.display().to_string(),
+            message: e.to_string(),
+        })?;
+        Ok(Self::parse_bs(&text))
+    }
+
+    /// the spans of the parsed nodes point into [`Self::text`]
+    pub fn parse_bs(text: &str) -> Self {
+        crate::span::with_source(text, || {
+            let (definitions, unparsed) = idl::extract(text);
+            WebGpuSpec {
+                text: text.to_string(),
+                definitions,
+                unparsed,
+                format_capabilities: FormatCapabilities::extract(text),
+                limits: Limit::extract(text),
+                revision: crate::bikeshed::metadata(text, "Date").map(str::to_string),
+            }
+        })
+    }
+
+    /// the non-partial definition named `name`, e.g. `GPUTextureFormat`
+    pub fn definition(&self, name: &str) -> Option<&Definition> {
+        let mut definitions = self.definitions.iter();
+        definitions.find(|d| d.name == name && !d.partial && d.kind != DefinitionKind::Includes)
+    }
+
+    /// the values of the enum `name`, e.g. `"r8unorm"` for `GPUTextureFormat`
+    pub fn enum_values(&self, name: &str) -> Option<&[String]> {
+        let definition = self.definition(name)?;
+        (definition.kind == DefinitionKind::Enum).then_some(definition.values.as_slice())
+    }
+
+    /// the capabilities of the `GPUTextureFormat` value `format`, e.g. `rgba8unorm`
+    pub fn format(&self, format: &str) -> Option<&FormatCapabilities> {
+        self.format_capabilities.iter().find(|c| c.format == format)
+    }
+
+    /// the limit `name`, e.g. `maxBindGroups`
+    pub fn limit(&self, name: &str) -> Option<&Limit> {
+        self.limits.iter().find(|l| l.name == name)
+    }
+
+    /// the members of the interface, namespace or dictionary `name`, including the ones of its
+    /// partial definitions, of the mixins it includes and of the dictionaries it inherits from
+    pub fn members(&self, name: &str) -> Vec<&Member> {
+        let mut members = vec![];
+        let mut names = vec![name];
+        while let Some(name) = names.pop() {
+            for definition in self.definitions.iter().filter(|d| d.name == name) {
+                match definition.kind {
+                    DefinitionKind::Includes => names.extend(definition.inherits.as_deref()),
+                    DefinitionKind::Dictionary => {
+                        members.extend(&definition.members);
+                        names.extend(definition.inherits.as_deref());
+                    }
+                    _ => members.extend(&definition.members),
+                }
+            }
+        }
+        members
+    }
+}
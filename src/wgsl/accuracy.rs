@@ -0,0 +1,284 @@
+use std::fmt::Display;
+
+use lazy_static::lazy_static;
+use regex::Regex;
+
+use super::primitives::ExprPattern;
+use crate::{bikeshed, misc::normalize_whitespace, nom_prelude::*};
+
+/// an amount of error, either a number or an expression in the arguments, e.g. `3 + 2 * abs(x)`
+#[derive(Debug, Clone, PartialEq)]
+pub enum Magnitude {
+    Value(f64),
+    Expr(String),
+}
+
+impl Display for Magnitude {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Magnitude::Value(v) => write!(f, "{v}"),
+            Magnitude::Expr(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+impl Magnitude {
+    /// parses numbers written as `2.5`, `2^-11` or `6.77×10^-5`, anything else is kept as an expression
+    pub fn new(s: &str) -> Self {
+        lazy_static! {
+            static ref NUMBER: Regex =
+                Regex::new(r"^(-?[0-9.]+)(?:\s*×\s*10\^(-?[0-9]+))?$").unwrap();
+            static ref POWER: Regex = Regex::new(r"^(-?[0-9.]+)\^(-?[0-9]+)$").unwrap();
+        }
+        let s = s.trim();
+        if let Some(c) = NUMBER.captures(s) {
+            let exponent = c.get(2).map_or("0", |e| e.as_str());
+            if let Ok(v) = format!("{}e{exponent}", &c[1]).parse() {
+                return Magnitude::Value(v);
+            }
+        }
+        if let Some(c) = POWER.captures(s) {
+            if let (Ok(base), Ok(exponent)) = (c[1].parse::<f64>(), c[2].parse::<i32>()) {
+                return Magnitude::Value(base.powi(exponent));
+            }
+        }
+        Magnitude::Expr(s.to_string())
+    }
+}
+
+/// the accuracy of a floating point operation, as stated in the spec's accuracy tables
+#[derive(Debug, Clone, PartialEq)]
+pub enum ErrorBound {
+    CorrectlyRounded,
+    /// units in the last place
+    Ulp {
+        ulp: Magnitude,
+        condition: Option<String>,
+    },
+    Absolute {
+        error: Magnitude,
+        condition: Option<String>,
+    },
+    /// the accuracy of evaluating another expression, e.g. `sin(x) / cos(x)` for `tan(x)`
+    Inherited(ExprPattern),
+    /// the larger error of all bounds
+    WorseOf(Vec<ErrorBound>),
+    /// bounds for different parts of the domain, each with its own condition
+    Piecewise(Vec<ErrorBound>),
+    Prose(String),
+}
+
+impl Display for ErrorBound {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let condition = |f: &mut std::fmt::Formatter<'_>, c: &Option<String>| match c {
+            Some(c) => write!(f, " {c}"),
+            None => Ok(()),
+        };
+        let list = |f: &mut std::fmt::Formatter<'_>, name: &str, bounds: &[ErrorBound]| {
+            let bounds: Vec<_> = bounds.iter().map(ToString::to_string).collect();
+            write!(f, "{name}({})", bounds.join("; "))
+        };
+        match self {
+            ErrorBound::CorrectlyRounded => write!(f, "correctly rounded"),
+            ErrorBound::Ulp { ulp, condition: c } => {
+                write!(f, "{ulp} ULP")?;
+                condition(f, c)
+            }
+            ErrorBound::Absolute {
+                error,
+                condition: c,
+            } => {
+                write!(f, "absolute error {error}")?;
+                condition(f, c)
+            }
+            ErrorBound::Inherited(e) => write!(f, "inherited from `{e}`"),
+            ErrorBound::WorseOf(bounds) => list(f, "worse of", bounds),
+            ErrorBound::Piecewise(bounds) => list(f, "piecewise", bounds),
+            ErrorBound::Prose(s) => write!(f, "\"{s}\""),
+        }
+    }
+}
+
+impl ErrorBound {
+    /// parses the content of an accuracy table cell
+    pub fn from_cell(cell: &str) -> Self {
+        let text = bikeshed::strip_markup(cell);
+        let lines: Vec<String> = text
+            .lines()
+            .map(|l| {
+                normalize_whitespace(l.trim_start_matches(|c: char| c.is_whitespace() || c == '*'))
+            })
+            .filter(|l| !l.is_empty())
+            .collect();
+        match lines.as_slice() {
+            [] => ErrorBound::Prose(String::new()),
+            [line] => Self::from_line(line),
+            [first, rest @ ..] if first.to_lowercase().starts_with("the worse of") => {
+                ErrorBound::WorseOf(rest.iter().map(|l| Self::from_line(l)).collect())
+            }
+            lines => ErrorBound::Piecewise(lines.iter().map(|l| Self::from_line(l)).collect()),
+        }
+    }
+
+    /// parses a single bound, e.g. `2.5 ULP for |y| in the range [2^-126, 2^126]`
+    pub fn from_line(line: &str) -> Self {
+        lazy_static! {
+            static ref ULP: Regex = Regex::new(r"^(.+?)\s+ULPs?\b\.?\s*(.*)$").unwrap();
+            static ref ABSOLUTE: Regex =
+                Regex::new(r"^(?i:absolute error)\s*(?:≤|<=|of)?\s*(\S+(?:\s*×\s*\S+)?)\s*(.*)$")
+                    .unwrap();
+            static ref INHERITED: Regex = Regex::new(r"^(?i:inherited from)\s+(.*)$").unwrap();
+        }
+        let condition = |c: &str| {
+            let c = c.trim().trim_end_matches('.');
+            (!c.is_empty()).then(|| c.to_string())
+        };
+        let line = line.trim();
+        if line.to_lowercase().starts_with("correctly rounded") {
+            return ErrorBound::CorrectlyRounded;
+        }
+        if let Some(c) = INHERITED.captures(line) {
+            if let Ok((_, e)) = ExprPattern::parse(&c[1]) {
+                return ErrorBound::Inherited(e);
+            }
+        }
+        if let Some(c) = ABSOLUTE.captures(line) {
+            return ErrorBound::Absolute {
+                error: Magnitude::new(c[1].trim_end_matches(['.', ','])),
+                condition: condition(&c[2]),
+            };
+        }
+        if let Some(c) = ULP.captures(line) {
+            return ErrorBound::Ulp {
+                ulp: Magnitude::new(&c[1]),
+                condition: condition(&c[2]),
+            };
+        }
+        ErrorBound::Prose(line.to_string())
+    }
+}
+
+/// a row of an accuracy table
+#[derive(Debug, Clone, PartialEq)]
+pub struct Accuracy {
+    /// the builtin function name or operator symbol
+    pub builtin: String,
+    /// the expression the accuracy is stated for, e.g. `x / y` or `acos(x)`
+    pub expr: ExprPattern,
+    pub f32: ErrorBound,
+    pub f16: ErrorBound,
+}
+
+impl Display for Accuracy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "{}:", self.expr)?;
+        writeln!(f, "    f32: {}", self.f32)?;
+        writeln!(f, "    f16: {}", self.f16)
+    }
+}
+
+impl Accuracy {
+    /// a row with one cell for both `f32` and `f16` (`colspan=2`) applies to both
+    pub fn from_row(row: &bikeshed::TableRow) -> Option<Self> {
+        let (expr, f32, f16) = match row.cells.as_slice() {
+            [expr, both] => (expr, both, both),
+            [expr, f32, f16, ..] => (expr, f32, f16),
+            _ => return None,
+        };
+        let expr = bikeshed::strip_markup(expr);
+        let (_, expr) = terminated(ExprPattern::parse, ws0)(&expr).ok()?;
+        let builtin = match &expr {
+            ExprPattern::Call(callee, _) => callee.name.to_string(),
+            ExprPattern::Unary(op, _) | ExprPattern::Binary(_, op, _) => op.symbol().to_string(),
+            expr => expr.to_string(),
+        };
+        Some(Accuracy {
+            builtin,
+            expr,
+            f32: ErrorBound::from_cell(f32),
+            f16: ErrorBound::from_cell(f16),
+        })
+    }
+
+    /// finds the rows of all tables with an "Accuracy" column
+    pub fn extract(text: &str) -> Vec<Self> {
+        bikeshed::tables(text)
+            .iter()
+            .filter(|t| t.has_column("accuracy"))
+            .flat_map(|t| t.rows.iter().filter_map(Self::from_row))
+            .collect()
+    }
+}
+
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_accuracy() {
+        let str = r#"
+<table class='data'>
+  <caption>Accuracy of built-in functions</caption>
+  <thead>
+    <tr><th>Built-in Function<th>Accuracy for f32<th>Accuracy for f16
+  </thead>
+  <tr><td>`abs(x)`<td colspan=2>Correctly rounded
+  <tr><td>`x / y`<td>2.5 ULP for |y| in the range [2<sup>-126</sup>, 2<sup>126</sup>]
+      <td>2.5 ULP for |y| in the range [2<sup>-14</sup>, 2<sup>14</sup>]
+  <tr><td>`acos(x)`<td colspan=2>
+      The worse of:
+      * Absolute error 6.77&times;10<sup>-5</sup>
+      * Inherited from `atan2(sqrt(1.0 - x * x), x)`
+  <tr><td>`cos(x)`<td>Absolute error &le; 2<sup>-11</sup> inside the range of [-&pi;, &pi;]
+      <td>Absolute error &le; 2<sup>-7</sup> inside the range of [-&pi;, &pi;]
+  <tr><td>`exp(x)`<td>3 + 2 * abs(x) ULP<td>1 + 2 * abs(x) ULP
+  <tr><td>`tan(x)`<td colspan=2>Inherited from `sin(x) / cos(x)`
+  <tr><td>`pack4x8snorm(e)`<td colspan=2>Not applicable
+</table>"#;
+        let accuracies = Accuracy::extract(str);
+        assert_eq!(accuracies.len(), 7);
+
+        assert_eq!(accuracies[0].builtin, "abs");
+        assert_eq!(accuracies[0].f16, ErrorBound::CorrectlyRounded);
+
+        assert_eq!(accuracies[1].builtin, "/");
+        assert_eq!(
+            accuracies[1].f16,
+            ErrorBound::Ulp {
+                ulp: Magnitude::Value(2.5),
+                condition: Some("for y in the range [2^-14, 2^14]".to_string()),
+            }
+        );
+
+        let ErrorBound::WorseOf(bounds) = &accuracies[2].f32 else {
+            panic!("{}", accuracies[2]);
+        };
+        assert_eq!(
+            bounds[0],
+            ErrorBound::Absolute {
+                error: Magnitude::Value(6.77e-5),
+                condition: None,
+            }
+        );
+        assert_eq!(
+            bounds[1].to_string(),
+            "inherited from `atan2(sqrt(1.0 - x * x), x)`"
+        );
+
+        assert_eq!(
+            accuracies[3].f32,
+            ErrorBound::Absolute {
+                error: Magnitude::Value(2f64.powi(-11)),
+                condition: Some("inside the range of [-π, π]".to_string()),
+            }
+        );
+        assert_eq!(accuracies[4].f16.to_string(), "1 + 2 * abs(x) ULP");
+        assert_eq!(
+            accuracies[5].f32.to_string(),
+            "inherited from `sin(x) / cos(x)`"
+        );
+        assert_eq!(
+            accuracies[6].f32,
+            ErrorBound::Prose("Not applicable".to_string())
+        );
+    }
+}
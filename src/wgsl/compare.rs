@@ -0,0 +1,293 @@
+use std::{collections::HashMap, fmt::Display};
+
+use lazy_static::lazy_static;
+use regex::Regex;
+use serde_json::Value;
+
+use super::primitives::*;
+use crate::{fn_name, nom_prelude::*, span::*};
+
+/// expands the predeclared type aliases, e.g. `vec3f` becomes `vec3<f32>` and `mat2x4h` becomes `mat2x4<f16>`
+pub fn expand_alias(ty: &Ty) -> Ty {
+    lazy_static! {
+        static ref ALIAS: Regex = Regex::new(r"^(vec[234]|mat[234]x[234])([fhiu])$").unwrap();
+    }
+    let scalar = |suffix: &str| match suffix {
+        "f" => "f32",
+        "h" => "f16",
+        "i" => "i32",
+        _ => "u32",
+    };
+    match ALIAS.captures(&ty.name) {
+        Some(c) if ty.params.is_empty() => Ty {
+            span: ty.span.clone(),
+            ..Ty::new(c[1].into(), vec![Ty::new(scalar(&c[2]).into(), vec![])])
+        },
+        _ => Ty {
+            span: ty.span.clone(),
+            ..Ty::with_args(
+                ty.name.clone(),
+                ty.params.iter().map(|p| p.map_ty(expand_alias)).collect(),
+            )
+        },
+    }
+}
+
+/// `name(T1, T2) -> R`, the part of a declaration that is compared
+pub fn signature_string(decl: &FnDecl) -> String {
+    let args: Vec<_> = decl.args.iter().map(|(_, ty)| ty.to_string()).collect();
+    format!("{}({}) -> {}", decl.name, args.join(", "), decl.out)
+}
+
+fn key(decl: &FnDecl) -> String {
+    let args: Vec<_> = decl.args.iter().map(|(_, ty)| ty.to_string()).collect();
+    format!("{}({})", decl.name, args.join(", "))
+}
+
+fn normalize(decl: FnDecl) -> FnDecl {
+    FnDecl {
+        name: decl.name,
+        args: decl
+            .args
+            .into_iter()
+            .map(|(arg, ty)| (arg, expand_alias(&ty)))
+            .collect(),
+        out: expand_alias(&decl.out),
+        span: decl.span,
+    }
+}
+
+/// parses a signature without argument names, e.g. `abs(vec3<f32>) -> vec3<f32>`
+fn parse_short_signature(s: &str) -> NomResult<&str, FnDecl> {
+    let parser = tuple((
+        ws0_then(Ident::parse),
+        delimited(
+            ws0_then(tag("(")),
+            separated_list0(ws0_then(tag(",")), ws0_then(Ty::parse)),
+            ws0_then(tag(")")),
+        ),
+        map(
+            opt(preceded(ws0_then(tag("->")), ws0_then(Ty::parse))),
+            Ty::flatten,
+        ),
+    ));
+    let parser = spanned(parser);
+    map(context(fn_name!(), parser), |((name, tys, out), span)| {
+        FnDecl {
+            name,
+            args: tys
+                .into_iter()
+                .enumerate()
+                .map(|(i, ty)| (format!("arg{i}").as_str().into(), ty))
+                .collect(),
+            out,
+            span,
+        }
+    })(s)
+}
+
+/// reads one signature per line, either a declaration like `fn abs(e: f32) -> f32`
+/// or just the types like `abs(f32) -> f32`. empty lines and lines starting with `#` or `//` are skipped
+pub fn signatures_from_text(text: &str) -> Result<Vec<FnDecl>, String> {
+    let lines = text.lines().enumerate().map(|(i, l)| (i + 1, l.trim()));
+    let lines = lines.filter(|(_, l)| !(l.is_empty() || l.starts_with('#') || l.starts_with("//")));
+    lines
+        .map(|(number, line)| {
+            let parser = alt((FnDecl::parse, parse_short_signature));
+            let (_, decl) = terminated(parser, ws0_then(eof))(line)
+                .map_err(|e| format!("line {number}: {}", e.report_into_string(line)))?;
+            Ok(normalize(decl))
+        })
+        .collect()
+}
+
+/// reads an array of signatures like
+/// `[{"name": "abs", "args": ["f32"], "result": "f32"}]`.
+/// arguments may also be objects like `{"name": "e", "ty": "f32"}`, a missing `"result"` means `void`
+pub fn signatures_from_json(text: &str) -> Result<Vec<FnDecl>, String> {
+    let json: Value = serde_json::from_str(text).map_err(|e| e.to_string())?;
+    let ty = |s: &str| match terminated(ws0_then(Ty::parse), ws0_then(eof))(s) {
+        Ok((_, ty)) => Ok(expand_alias(&ty)),
+        Err(_) => Err(format!("invalid type `{s}`")),
+    };
+    let signatures = json.as_array().ok_or("expected an array of signatures")?;
+    signatures
+        .iter()
+        .map(|s| {
+            let name = s.get("name").and_then(Value::as_str);
+            let name = name.ok_or_else(|| format!("signature without a name: {s}"))?;
+            let args: &[Value] = match s.get("args") {
+                None => &[],
+                Some(args) => args
+                    .as_array()
+                    .ok_or_else(|| format!("`args` is not an array: {s}"))?,
+            };
+            let args = args
+                .iter()
+                .enumerate()
+                .map(|(i, arg)| {
+                    let (arg_name, arg_ty) = match arg {
+                        Value::String(ty) => (format!("arg{i}"), ty.as_str()),
+                        arg => {
+                            let arg_name = arg.get("name").and_then(Value::as_str);
+                            let arg_ty = arg.get("ty").and_then(Value::as_str);
+                            let arg_ty =
+                                arg_ty.ok_or_else(|| format!("argument without a type: {arg}"))?;
+                            (
+                                arg_name.map_or(format!("arg{i}"), ToString::to_string),
+                                arg_ty,
+                            )
+                        }
+                    };
+                    Ok((arg_name.as_str().into(), ty(arg_ty)?))
+                })
+                .collect::<Result<Vec<_>, String>>()?;
+            let out = match s.get("result") {
+                Some(Value::String(out)) => ty(out)?,
+                Some(_) => return Err(format!("`result` is not a string: {s}")),
+                None => make_ty!(void),
+            };
+            Ok(FnDecl {
+                name: name.into(),
+                args,
+                out,
+                span: Span::default(),
+            })
+        })
+        .collect()
+}
+
+/// the differences between the builtin function instances of the spec and of another source, e.g. a compiler.
+/// signatures are matched by name and argument types, argument names are ignored
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct SignatureComparison {
+    /// in the spec, but not in the other source
+    pub missing_in_other: Vec<FnDecl>,
+    /// in the other source, but not in the spec
+    pub missing_in_spec: Vec<FnDecl>,
+    /// the same arguments but different result types, as `(spec, other)`
+    pub result_mismatches: Vec<(FnDecl, FnDecl)>,
+}
+
+impl SignatureComparison {
+    pub fn new(spec: &[FnDecl], other: &[FnDecl]) -> Self {
+        let index = |decls: &[FnDecl]| -> HashMap<String, usize> {
+            decls.iter().enumerate().map(|(i, d)| (key(d), i)).collect()
+        };
+        let (spec_index, other_index) = (index(spec), index(other));
+        let mut comparison = SignatureComparison::default();
+        for decl in spec {
+            match other_index.get(&key(decl)).map(|&i| &other[i]) {
+                None => comparison.missing_in_other.push(decl.clone()),
+                Some(o) if o.out != decl.out => {
+                    comparison.result_mismatches.push((decl.clone(), o.clone()))
+                }
+                Some(_) => {}
+            }
+        }
+        comparison.missing_in_spec = other
+            .iter()
+            .filter(|o| !spec_index.contains_key(&key(o)))
+            .cloned()
+            .collect();
+        comparison
+    }
+
+    /// true if both sources have the same signatures
+    pub fn is_match(&self) -> bool {
+        self.missing_in_other.is_empty()
+            && self.missing_in_spec.is_empty()
+            && self.result_mismatches.is_empty()
+    }
+}
+
+impl Display for SignatureComparison {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "missing in other ({}):", self.missing_in_other.len())?;
+        for decl in &self.missing_in_other {
+            writeln!(f, "    {}", signature_string(decl))?;
+        }
+        writeln!(f, "missing in spec ({}):", self.missing_in_spec.len())?;
+        for decl in &self.missing_in_spec {
+            writeln!(f, "    {}", signature_string(decl))?;
+        }
+        writeln!(
+            f,
+            "different result types ({}):",
+            self.result_mismatches.len()
+        )?;
+        for (spec, other) in &self.result_mismatches {
+            writeln!(f, "    {} vs {}", signature_string(spec), other.out)?;
+        }
+        Ok(())
+    }
+}
+
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compare() {
+        let ty = |s| Ty::parse(s).unwrap().1;
+        assert_eq!(expand_alias(&ty("vec3f")), ty("vec3<f32>"));
+        assert_eq!(expand_alias(&ty("mat2x4h")), ty("mat2x4<f16>"));
+        assert_eq!(
+            expand_alias(&ty("ptr<function, vec2u>")),
+            ty("ptr<function, vec2<u32>>")
+        );
+        assert_eq!(expand_alias(&ty("texture_2d<f32>")), ty("texture_2d<f32>"));
+        assert_eq!(expand_alias(&ty("vec5f")), ty("vec5f"));
+
+        let spec = signatures_from_text(
+            "fn abs(e: f32) -> f32
+            fn abs(e: vec2<f32>) -> vec2<f32>
+            fn length(e: vec2<f32>) -> f32
+            fn workgroupBarrier()",
+        )
+        .unwrap();
+        let text = signatures_from_text(
+            "# exported from a compiler
+            abs(f32) -> f32
+            length(vec2f) -> vec2f
+            workgroupBarrier()
+            abs(i32) -> i32",
+        )
+        .unwrap();
+        let json = signatures_from_json(
+            r#"[
+            {"name": "abs", "args": ["f32"], "result": "f32"},
+            {"name": "length", "args": [{"name": "v", "ty": "vec2f"}], "result": "vec2f"},
+            {"name": "workgroupBarrier"},
+            {"name": "abs", "args": ["i32"], "result": "i32"}
+        ]"#,
+        )
+        .unwrap();
+        assert_eq!(json.len(), 4);
+        assert_eq!(json[1].args[0].0.as_str(), "v");
+
+        for other in [text, json] {
+            let comparison = SignatureComparison::new(&spec, &other);
+            assert!(!comparison.is_match());
+            assert_eq!(comparison.missing_in_other, vec![spec[1].clone()]);
+            assert_eq!(comparison.missing_in_spec.len(), 1);
+            assert_eq!(
+                signature_string(&comparison.missing_in_spec[0]),
+                "abs(i32) -> i32"
+            );
+            assert_eq!(comparison.result_mismatches.len(), 1);
+            assert_eq!(
+                comparison.to_string().lines().last(),
+                Some("    length(vec2<f32>) -> f32 vs vec2<f32>")
+            );
+        }
+
+        assert!(signatures_from_text("abs(f32 -> f32").is_err());
+        assert!(signatures_from_json(r#"[{"args": []}]"#).is_err());
+        assert!(signatures_from_json(r#"[{"name": "abs", "args": [NaN]}]"#).is_err());
+        assert!(signatures_from_json(r#"[{"name": "abs", "args": "f32"}]"#).is_err());
+        assert!(signatures_from_json(r#"[{"name": "abs", "result": ["f32"]}]"#).is_err());
+        // a surrogate pair is one character
+        let json = signatures_from_json(r#"[{"name": "\ud835\udc65", "args": []}]"#).unwrap();
+        assert_eq!(json[0].name.as_str(), "\u{1d465}");
+    }
+}
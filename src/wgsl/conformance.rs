@@ -0,0 +1,417 @@
+use std::{collections::BTreeSet, fmt::Display, path::Path};
+
+use super::enumerants::AddressSpace;
+use super::eval::{self, Scalar, Value};
+use super::parametrization::OverloadRow;
+use super::primitives::*;
+use super::resolution::resolve;
+
+/// builtins that may only be called from a fragment shader
+pub const FRAGMENT_ONLY: &[&str] = &[
+    "dpdx",
+    "dpdxCoarse",
+    "dpdxFine",
+    "dpdy",
+    "dpdyCoarse",
+    "dpdyFine",
+    "fwidth",
+    "fwidthCoarse",
+    "fwidthFine",
+    "textureSample",
+    "textureSampleBias",
+    "textureSampleCompare",
+];
+
+/// argument types for negative tests, the first one that no overload accepts is used
+pub const WRONG_TYPES: &[&str] = &["bool", "vec4<bool>", "i32", "f32", "vec4<u32>"];
+
+/// the values arguments are derived from. if a const builtin cannot be evaluated
+/// for the arguments of one seed, e.g. `atanh(1)`, the next one is tried
+const SEEDS: &[f64] = &[1.0, 0.5, 2.0];
+
+fn arg_seed(seed: f64, i: usize) -> f64 {
+    seed + i as f64
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Expectation {
+    Pass,
+    Fail,
+}
+
+impl Display for Expectation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Expectation::Pass => write!(f, "pass"),
+            Expectation::Fail => write!(f, "fail"),
+        }
+    }
+}
+
+/// a WGSL shader which calls one builtin with arguments of the given types
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConformanceTest {
+    /// usable as a file name, e.g. `abs_vec3_f32`
+    pub name: String,
+    pub expect: Expectation,
+    pub builtin: Ident,
+    pub arg_tys: Vec<Ty>,
+    pub source: String,
+}
+
+/// a scalar or vector value of type `ty`
+pub fn value(ty: &Ty, seed: f64) -> Option<Value> {
+    if let Some(s) = Scalar::new(&ty.name, seed) {
+        return ty.params.is_empty().then_some(Value::Scalar(s));
+    }
+    let n: usize = ty.name.strip_prefix("vec")?.parse().ok()?;
+    let [TemplateArg::Type(elem)] = ty.params.as_slice() else {
+        return None;
+    };
+    let s = Scalar::new(&elem.name, seed)?;
+    Some(Value::Vector(vec![s; n]))
+}
+
+/// a WGSL expression constructing a value of type `ty`, e.g. `vec2<f32>(1f, 1f)` or `array<i32, 2>(1i, 1i)`
+pub fn value_expr(ty: &Ty, seed: f64) -> Option<String> {
+    if let Some(value) = value(ty, seed) {
+        return Some(value.to_string());
+    }
+    // abstract types cannot be written, so their constructors infer them
+    let callee = match ty.param_tys().any(|p| p.name.starts_with("Abstract")) {
+        true => ty.name.to_string(),
+        false => ty.to_string(),
+    };
+    let (elem, n) = match (ty.name.strip_prefix("mat"), ty.params.as_slice()) {
+        (Some(size), [TemplateArg::Type(elem)]) => {
+            let (cols, rows) = size.split_once('x')?;
+            let col = Ty::new(format!("vec{rows}").as_str().into(), vec![elem.clone()]);
+            (col, cols.parse::<usize>().ok()?)
+        }
+        (None, [TemplateArg::Type(elem), n]) if ty.name.as_str() == "array" => {
+            (elem.clone(), n.to_string().parse().ok()?)
+        }
+        _ => return None,
+    };
+    let elem = value_expr(&elem, seed)?;
+    Some(format!("{callee}({})", vec![elem; n].join(", ")))
+}
+
+/// a test shader under construction
+#[derive(Default)]
+struct Shader {
+    enables: BTreeSet<&'static str>,
+    globals: Vec<String>,
+    locals: Vec<String>,
+    bindings: u32,
+}
+
+impl Shader {
+    fn binding(&mut self) -> String {
+        self.bindings += 1;
+        format!("@group(0) @binding({})", self.bindings - 1)
+    }
+
+    /// an expression to pass as the `i`th argument, declaring the variables and resources it refers to
+    fn argument(&mut self, i: usize, ty: &Ty, seed: f64) -> Option<String> {
+        if ty.to_string().contains("f16") {
+            self.enables.insert("f16");
+        }
+        if let Some(expr) = value_expr(ty, seed) {
+            return Some(expr);
+        }
+        let var = format!("arg{i}");
+        match (ty.name.as_str(), ty.params.as_slice()) {
+            ("ptr", [_, store, ..]) => {
+                let space = ty.address_space()?;
+                let decl = match space {
+                    AddressSpace::Function => {
+                        self.locals.push(format!("var {var}: {store};"));
+                        return Some(format!("&{var}"));
+                    }
+                    AddressSpace::Private | AddressSpace::Workgroup => {
+                        format!("var<{space}> {var}: {store};")
+                    }
+                    AddressSpace::Uniform => {
+                        format!("{} var<uniform> {var}: {store};", self.binding())
+                    }
+                    AddressSpace::Storage => {
+                        let access = ty.access_mode()?;
+                        format!("{} var<storage, {access}> {var}: {store};", self.binding())
+                    }
+                    AddressSpace::Handle => return None,
+                };
+                self.globals.push(decl);
+                Some(format!("&{var}"))
+            }
+            (name, _) if name.starts_with("texture") || name.starts_with("sampler") => {
+                let decl = format!("{} var {var}: {ty};", self.binding());
+                self.globals.push(decl);
+                Some(var)
+            }
+            _ => None,
+        }
+    }
+
+    /// a shader calling `builtin` with arguments of the given types, from an entry point of the stage it requires
+    fn build(
+        builtin: &str,
+        arg_tys: &[Ty],
+        out: &Ty,
+        seed: f64,
+        expect: Expectation,
+    ) -> Result<String, String> {
+        let mut shader = Shader::default();
+        let args = arg_tys
+            .iter()
+            .enumerate()
+            .map(|(i, ty)| {
+                shader
+                    .argument(i, ty, arg_seed(seed, i))
+                    .ok_or_else(|| format!("cannot construct an argument of type `{ty}`"))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut source = vec![];
+        let tys: Vec<_> = arg_tys.iter().map(ToString::to_string).collect();
+        source.push(format!("// expect: {expect}"));
+        source.push(format!("// {builtin}({}) -> {out}", tys.join(", ")));
+        source.extend(shader.enables.iter().map(|e| format!("enable {e};")));
+        source.push(String::new());
+        if !shader.globals.is_empty() {
+            source.extend(shader.globals);
+            source.push(String::new());
+        }
+        match FRAGMENT_ONLY.contains(&builtin) {
+            true => source.push("@fragment".to_string()),
+            false => source.push("@compute @workgroup_size(1)".to_string()),
+        }
+        source.push("fn main() {".to_string());
+        source.extend(shader.locals.iter().map(|l| format!("    {l}")));
+        let call = format!("{builtin}({})", args.join(", "));
+        match out.name.as_str() {
+            "void" => source.push(format!("    {call};")),
+            _ => source.push(format!("    _ = {call};")),
+        }
+        source.push("}\n".to_string());
+        Ok(source.join("\n"))
+    }
+}
+
+/// the first seed for which a const builtin can be evaluated, so that the call is a valid const-expression
+fn pick_seed(overload: &OverloadRow, decl: &FnDecl) -> f64 {
+    let evaluates = |&seed: &f64| {
+        let args = decl
+            .args
+            .iter()
+            .enumerate()
+            .map(|(i, (_, ty))| value(ty, arg_seed(seed, i)))
+            .collect::<Option<Vec<_>>>();
+        match args {
+            Some(args) => !matches!(eval::eval_builtin(&decl.name, &args), Some(Err(_))),
+            None => true,
+        }
+    };
+    match overload.is_const() {
+        true => SEEDS.iter().copied().find(evaluates).unwrap_or(SEEDS[0]),
+        false => SEEDS[0],
+    }
+}
+
+/// turns a builtin name and a list of types into an identifier, e.g. `abs_vec3_f32`
+fn test_name(parts: &[String]) -> String {
+    let name: String = parts
+        .join("_")
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    let parts: Vec<_> = name.split('_').filter(|p| !p.is_empty()).collect();
+    parts.join("_")
+}
+
+/// positive and negative test shaders for the builtin function overloads
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct ConformanceSuite {
+    pub tests: Vec<ConformanceTest>,
+    /// the overloads or instances no shader could be generated for, and why
+    pub skipped: Vec<(String, String)>,
+}
+
+impl ConformanceSuite {
+    /// a positive test for every concrete instance of every overload, and for every argument of
+    /// every builtin a negative test with an argument type that no overload accepts
+    pub fn generate<'a>(overloads: impl IntoIterator<Item = &'a OverloadRow>) -> Self {
+        let overloads: Vec<&OverloadRow> = overloads.into_iter().collect();
+        let mut suite = ConformanceSuite::default();
+        // builtin name and arity of the generated negative tests
+        let mut negatives = BTreeSet::new();
+        for overload in &overloads {
+            let instances = overload.instances();
+            if instances.is_empty() {
+                let reason = "no concrete instance of its type parameters".to_string();
+                suite
+                    .skipped
+                    .push((overload.fn_decl.name.to_string(), reason));
+            }
+            for decl in &instances {
+                let arg_tys: Vec<Ty> = decl.args.iter().map(|(_, ty)| ty.clone()).collect();
+                let seed = pick_seed(overload, decl);
+                suite.push(
+                    &decl.name,
+                    arg_tys.clone(),
+                    &decl.out,
+                    seed,
+                    Expectation::Pass,
+                );
+
+                if !negatives.insert((decl.name.to_string(), arg_tys.len())) {
+                    continue;
+                }
+                for i in 0..arg_tys.len() {
+                    let wrong = WRONG_TYPES.iter().find_map(|w| {
+                        let mut tys = arg_tys.clone();
+                        tys[i] = Ty::parse(w).unwrap().1;
+                        let rejected =
+                            resolve(overloads.iter().copied(), &decl.name, &tys).is_empty();
+                        rejected.then_some(tys)
+                    });
+                    if let Some(tys) = wrong {
+                        suite.push(&decl.name, tys, &decl.out, seed, Expectation::Fail);
+                    }
+                }
+            }
+        }
+        suite
+    }
+
+    fn push(
+        &mut self,
+        builtin: &Ident,
+        arg_tys: Vec<Ty>,
+        out: &Ty,
+        seed: f64,
+        expect: Expectation,
+    ) {
+        let tys: Vec<String> = arg_tys.iter().map(ToString::to_string).collect();
+        let signature = format!("{builtin}({})", tys.join(", "));
+        let source = match Shader::build(builtin, &arg_tys, out, seed, expect) {
+            Ok(source) => source,
+            Err(reason) => return self.skipped.push((signature, reason)),
+        };
+        let mut parts = vec![builtin.to_string()];
+        if expect == Expectation::Fail {
+            parts.push("fail".to_string());
+        }
+        parts.extend(tys);
+        let base = test_name(&parts);
+        let mut name = base.clone();
+        for n in 2.. {
+            if !self.tests.iter().any(|t| t.name == name) {
+                break;
+            }
+            name = format!("{base}_{n}");
+        }
+        self.tests.push(ConformanceTest {
+            name,
+            expect,
+            builtin: builtin.clone(),
+            arg_tys,
+            source,
+        });
+    }
+
+    /// writes every test to `dir/pass/<name>.wgsl` or `dir/fail/<name>.wgsl`
+    pub fn write_to(&self, dir: &Path) -> std::io::Result<()> {
+        for test in &self.tests {
+            let dir = dir.join(test.expect.to_string());
+            std::fs::create_dir_all(&dir)?;
+            std::fs::write(dir.join(format!("{}.wgsl", test.name)), &test.source)?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for ConformanceSuite {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let count = |e| self.tests.iter().filter(|t| t.expect == e).count();
+        writeln!(
+            f,
+            "{} positive tests, {} negative tests, {} skipped",
+            count(Expectation::Pass),
+            count(Expectation::Fail),
+            self.skipped.len()
+        )?;
+        for (signature, reason) in &self.skipped {
+            writeln!(f, "    skipped {signature}: {reason}")?;
+        }
+        Ok(())
+    }
+}
+
+mod tests {
+    use super::*;
+    use crate::nom_prelude::*;
+
+    #[test]
+    fn test_conformance() {
+        let rows = [
+            r#"<tr algorithm="atanh">
+    <td>|S| is [=type/abstract|AbstractFloat=], [=f32=], or [=f16=]<br>
+        |T| is |S| or vec|N|&lt;|S|&gt;
+    <td><xmp highlight=rust>@const @must_use fn atanh(e: T ) -> T</xmp>"#,
+            r#"<tr algorithm="atomicAdd">
+    <td>|AS| is storage or workgroup<br>
+        |T| is [=i32=] or [=u32=]
+    <td><xmp highlight=rust>fn atomicAdd(atomic_ptr: ptr<AS, atomic<T>, read_write>, v: T) -> T</xmp>"#,
+            r#"<tr algorithm="textureDimensions">
+    <td>|F| is a [=texel format=]
+    <td><xmp highlight=rust>@must_use fn textureDimensions(t: texture_storage_1d<F, A>) -> u32</xmp>"#,
+        ];
+        let rows: Vec<OverloadRow> = rows
+            .iter()
+            .map(|s| OverloadRow::parse(s).report(s).unwrap().1)
+            .collect();
+        let suite = ConformanceSuite::generate(&rows);
+
+        let pass = suite.tests.iter().filter(|t| t.expect == Expectation::Pass);
+        assert_eq!(pass.count(), 12 + 4);
+        assert_eq!(suite.skipped.len(), 1, "{suite}");
+
+        let test = |name: &str| match suite.tests.iter().find(|t| t.name == name) {
+            Some(test) => test,
+            None => panic!(
+                "no test {name} in {:?}",
+                suite.tests.iter().map(|t| &t.name).collect::<Vec<_>>()
+            ),
+        };
+        // atanh(1) is infinite, which is an error in a const-expression
+        assert!(test("atanh_vec2_f16").source.contains("enable f16;"));
+        assert!(test("atanh_vec2_f16")
+            .source
+            .contains("_ = atanh(vec2<f16>(0.5h, 0.5h));"));
+        assert!(test("atanh_AbstractFloat").source.contains("atanh(0.5)"));
+
+        let atomic = test("atomicAdd_ptr_storage_atomic_u32_read_write_u32");
+        assert_eq!(
+            atomic.source,
+            "// expect: pass
+// atomicAdd(ptr<storage, atomic<u32>, read_write>, u32) -> u32
+
+@group(0) @binding(0) var<storage, read_write> arg0: atomic<u32>;
+
+@compute @workgroup_size(1)
+fn main() {
+    _ = atomicAdd(&arg0, 2u);
+}
+"
+        );
+
+        let fail = test("atanh_fail_bool");
+        assert_eq!(fail.expect, Expectation::Fail);
+        assert!(fail.source.contains("atanh(true)"));
+        assert_eq!(
+            test("atomicAdd_fail_ptr_storage_atomic_i32_read_write_bool").arg_tys[1].to_string(),
+            "bool"
+        );
+    }
+}
@@ -0,0 +1,214 @@
+use std::fmt::Display;
+
+use super::parametrization::*;
+use super::primitives::*;
+use super::resolution::{is_type_variable_name, Signature, IMPLICIT_TYPE_PARAMS};
+use super::type_rules::*;
+use crate::span::Span;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ConstructorKind {
+    /// e.g. `vec3<f32>()`
+    ZeroValue,
+    /// e.g. `vec3<f32>(e1, e2, e3)` or `vec3(e1, e2, e3)`
+    Construction,
+    /// e.g. `f32(e)` or `vec3<f32>(e)` where `e` is a `vec3<i32>`
+    Conversion,
+    /// e.g. `bitcast<T>(e)`
+    Bitcast,
+}
+
+impl ConstructorKind {
+    /// classifies a type rule by the caption of its table, e.g. "Scalar conversion to f32"
+    pub fn classify(caption: Option<&str>, callee: &Ty, arg_count: usize) -> Self {
+        let caption = caption.unwrap_or_default().to_lowercase();
+        if callee.name.as_str() == "bitcast" || caption.contains("reinterpretation") {
+            ConstructorKind::Bitcast
+        } else if arg_count == 0 || caption.contains("zero value") {
+            ConstructorKind::ZeroValue
+        } else if caption.contains("conversion") {
+            ConstructorKind::Conversion
+        } else {
+            ConstructorKind::Construction
+        }
+    }
+}
+
+impl Display for ConstructorKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            ConstructorKind::ZeroValue => "zero value",
+            ConstructorKind::Construction => "construction",
+            ConstructorKind::Conversion => "conversion",
+            ConstructorKind::Bitcast => "bitcast",
+        };
+        write!(f, "{s}")
+    }
+}
+
+/// a value constructor or conversion, extracted from a type rule like `vec3<T>(e1, e2, e3): vec3<T>`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Constructor {
+    pub kind: ConstructorKind,
+    /// e.g. `vecN<T>`, or `vecN` for forms which infer the element type
+    pub callee: Ty,
+    pub args: Vec<(Ident, Ty)>,
+    pub rule: TypeRule,
+}
+
+impl Constructor {
+    /// succeeds for rules whose conclusion is a call and whose arguments all have a stated type
+    pub fn from_rule(rule: &TypeRule, caption: Option<&str>) -> Option<Self> {
+        let ExprPattern::Call(callee, args) = &rule.expr else {
+            return None;
+        };
+        let args = args
+            .iter()
+            .map(|arg| match arg {
+                ExprPattern::Var(e) => {
+                    let judgment = rule.judgments.iter().find(|j| &j.expr == e)?;
+                    Some((e.clone(), judgment.ty.clone()))
+                }
+                _ => None,
+            })
+            .collect::<Option<Vec<_>>>()?;
+        Some(Constructor {
+            kind: ConstructorKind::classify(caption, callee, args.len()),
+            callee: callee.clone(),
+            args,
+            rule: rule.clone(),
+        })
+    }
+
+    /// all constructors and conversions of the given type rule tables
+    pub fn extract(tables: &[TypeRuleTable]) -> Vec<Self> {
+        tables
+            .iter()
+            .flat_map(|t| {
+                let caption = t.caption.as_deref();
+                t.rules
+                    .iter()
+                    .filter_map(move |r| Self::from_rule(r, caption))
+            })
+            .collect()
+    }
+
+    /// the constructor written as a function declaration, named after its callee
+    pub fn fn_decl(&self) -> FnDecl {
+        FnDecl {
+            name: self.callee.to_string().as_str().into(),
+            args: self.args.clone(),
+            out: self.rule.ty.clone(),
+            span: Span::default(),
+        }
+    }
+}
+
+impl Display for Constructor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "#[{}]", self.kind)?;
+        write!(f, "{}", self.fn_decl())?;
+        match self.rule.parametrization.is_empty() {
+            true => writeln!(f, ";"),
+            false => writeln!(f, " where\n{};", self.rule.parametrization),
+        }
+    }
+}
+
+impl Signature for Constructor {
+    fn name(&self) -> String {
+        self.callee.name.to_string()
+    }
+
+    fn parametrization(&self) -> &Parametrization {
+        &self.rule.parametrization
+    }
+
+    fn params(&self) -> Vec<&Ty> {
+        self.args.iter().map(|(_, ty)| ty).collect()
+    }
+
+    fn result(&self) -> &Ty {
+        &self.rule.ty
+    }
+
+    fn callee(&self) -> Ty {
+        self.callee.clone()
+    }
+
+    /// type rules often use type variables without a bound, e.g. `T` in `e: T`
+    fn type_params(&self) -> Vec<Ident> {
+        fn collect(ty: &Ty, params: &mut Vec<Ident>) {
+            if ty.params.is_empty() && is_type_variable_name(&ty.name) && !params.contains(&ty.name)
+            {
+                params.push(ty.name.clone());
+            }
+            ty.param_tys().for_each(|p| collect(p, params));
+        }
+        let mut params: Vec<Ident> = IMPLICIT_TYPE_PARAMS.iter().map(|&p| p.into()).collect();
+        for bound in self.rule.parametrization.iter() {
+            if !params.contains(&bound.type_param) {
+                params.push(bound.type_param.clone());
+            }
+        }
+        collect(&self.callee, &mut params);
+        collect(&self.rule.ty, &mut params);
+        self.args
+            .iter()
+            .for_each(|(_, ty)| collect(ty, &mut params));
+        params
+    }
+}
+
+mod tests {
+    use super::*;
+    use crate::wgsl::resolution::resolve_callee;
+
+    #[test]
+    fn test_constructors() {
+        let str = r#"
+<table class='data'>
+  <caption>Vector construction from components</caption>
+  <thead><tr><th>Precondition<th>Conclusion<th>Notes</thead>
+  <tr><td>|e1|: |T|<br>|e2|: |T|<br>|e3|: |T|<td>`vec3<T>(e1,e2,e3)`: vec3&lt;|T|&gt;<td>
+  <tr><td>|e1|: |T|<br>|e2|: |T|<br>|e3|: |T|<td>`vec3(e1,e2,e3)`: vec3&lt;|T|&gt;<td>Element type inferred.
+  <tr><td><td>`vec3<T>()`: vec3&lt;|T|&gt;<td>
+</table>
+<table class='data'>
+  <caption>Scalar conversion to f32</caption>
+  <thead><tr><th>Precondition<th>Conclusion<th>Notes</thead>
+  <tr><td>|e|: |T|<br>|T| is [=i32=] or [=u32=]<td>`f32(e)`: f32<td>
+</table>"#;
+        let tables = TypeRuleTable::extract(str);
+        let constructors = Constructor::extract(&tables);
+        let kinds: Vec<_> = constructors.iter().map(|c| c.kind).collect();
+        use ConstructorKind::*;
+        assert_eq!(
+            kinds,
+            vec![Construction, Construction, ZeroValue, Conversion]
+        );
+
+        let ty = |s| Ty::parse(s).unwrap().1;
+        let f32s = [ty("f32"), ty("f32"), ty("f32")];
+
+        let resolved = resolve_callee(&constructors, &ty("vec3"), &f32s);
+        assert_eq!(resolved.len(), 1);
+        assert_eq!(resolved[0].result, ty("vec3<f32>"));
+
+        let resolved = resolve_callee(&constructors, &ty("vec3<f32>"), &f32s);
+        assert_eq!(resolved.len(), 1);
+        assert!(resolve_callee(&constructors, &ty("vec3<i32>"), &f32s).is_empty());
+
+        let resolved = resolve_callee(&constructors, &ty("vec3<u32>"), &[]);
+        assert_eq!(resolved[0].result, ty("vec3<u32>"));
+
+        let resolved = resolve_callee(&constructors, &ty("f32"), &[ty("u32")]);
+        assert_eq!(resolved[0].signature.kind, Conversion);
+        assert!(resolve_callee(&constructors, &ty("f32"), &[ty("bool")]).is_empty());
+
+        assert_eq!(
+            constructors[3].to_string(),
+            "#[conversion]\nfn f32(\n    e : T\n) -> f32\n where\n    T: is i32 | u32\n;\n"
+        );
+    }
+}
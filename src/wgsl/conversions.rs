@@ -0,0 +1,314 @@
+use std::fmt::Display;
+
+use super::parametrization::{parse_generic_arg, parse_spec_ty, Bound, BoundKind, UnionBound};
+use super::primitives::*;
+use super::resolution::{
+    is_type_variable_name, satisfy, unify, Substitution, IMPLICIT_TYPE_PARAMS,
+};
+use crate::{bikeshed, misc::normalize_whitespace, nom_prelude::*, span::spanned};
+
+/// the scalar types of the type model, in the order they are listed in the spec
+pub const SCALAR_TYPES: &[&str] = &[
+    "bool",
+    "AbstractInt",
+    "AbstractFloat",
+    "i32",
+    "u32",
+    "f32",
+    "f16",
+];
+
+/// abstract types are `AbstractInt`, `AbstractFloat`, the `_abstract` result structures
+/// such as `__frexp_result_abstract` and the types built from them
+pub fn is_abstract(ty: &Ty) -> bool {
+    ty.name.starts_with("Abstract")
+        || ty.name.ends_with("_abstract")
+        || ty.param_tys().any(is_abstract)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Rank {
+    Finite(u32),
+    /// `ConversionRank(S, T)`, the rank of the component types
+    Inherit(Ident, Ident),
+    /// no automatic conversion exists
+    Infinity,
+}
+
+impl Display for Rank {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Rank::Finite(r) => write!(f, "{r}"),
+            Rank::Inherit(s, t) => write!(f, "ConversionRank({s}, {t})"),
+            Rank::Infinity => write!(f, "infinity"),
+        }
+    }
+}
+
+impl Rank {
+    pub fn parse(s: &str) -> NomResult<&str, Self> {
+        let finite = map(digit1, |d: &str| Rank::Finite(d.parse().unwrap()));
+        let inherit = delimited(
+            pair(tag("ConversionRank"), ws0_then(tag("("))),
+            separated_pair(
+                ws0_then(Ident::parse),
+                ws0_then(tag(",")),
+                ws0_then(Ident::parse),
+            ),
+            ws0_then(tag(")")),
+        );
+        let inherit = map(inherit, |(s, t)| Rank::Inherit(s, t));
+        let infinity = map(tag("infinity"), |_| Rank::Infinity);
+        ws0_then(alt((finite, inherit, infinity)))(s)
+    }
+}
+
+/// a row of the "conversion rank" table, e.g. `AbstractInt | i32 | 3`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConversionRankRule {
+    pub src: Ty,
+    pub dest: Ty,
+    pub rank: Rank,
+    /// conditions on the type parameters, e.g. that the access mode of a `ref` allows reading
+    pub bounds: Vec<Bound>,
+    pub description: String,
+}
+
+impl Display for ConversionRankRule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "ConversionRank({}, {}) = {}",
+            self.src, self.dest, self.rank
+        )
+    }
+}
+
+impl ConversionRankRule {
+    pub fn from_row(row: &bikeshed::TableRow) -> Option<Self> {
+        let [src, dest, rank, rest @ ..] = row.cells.as_slice() else {
+            return None;
+        };
+        // cells may continue with prose, e.g. "ref<AS,T,AM> for address space AS, ..."
+        let (conditions, src) = ws0_then(parse_spec_ty)(src).ok()?;
+        let bounds = conditions
+            .split_once("where")
+            .and_then(|(_, condition)| Self::parse_condition(condition).ok())
+            .map(|(_, bound)| bound)
+            .into_iter()
+            .collect();
+        let (_, dest) = ws0_then(parse_spec_ty)(dest).ok()?;
+        let (_, rank) = Rank::parse(&bikeshed::strip_markup(rank)).ok()?;
+        let description = rest
+            .first()
+            .map(|d| normalize_whitespace(&bikeshed::strip_markup(d)))
+            .unwrap_or_default();
+        Some(ConversionRankRule {
+            src,
+            dest,
+            rank,
+            bounds,
+            description,
+        })
+    }
+
+    /// parses a condition on a type parameter, e.g.
+    /// `[=access mode=] |AM| is [=access/read=] or [=access/read_write=].`
+    fn parse_condition(s: &str) -> NomResult<&str, Bound> {
+        let what = opt(delimited(
+            tag("[="),
+            take_till(|c: char| c == '='),
+            tag("=]"),
+        ));
+        let alternatives = separated_list1(
+            ws0_then(alt((tag(","), tag("or")))),
+            ws0_then(parse_spec_ty),
+        );
+        let parser = pair(
+            preceded(ws0_then(what), ws0_then(parse_generic_arg)),
+            preceded(ws0_then(tag("is")), alternatives),
+        );
+        map(spanned(parser), |((type_param, is_one_of), span)| Bound {
+            type_param,
+            bound_kind: BoundKind::Union(UnionBound { is_one_of }),
+            span,
+        })(s)
+    }
+
+    /// finds the rows of the table with a "ConversionRank" column
+    pub fn extract(text: &str) -> Vec<Self> {
+        bikeshed::tables(text)
+            .iter()
+            .filter(|t| t.has_column("conversionrank"))
+            .flat_map(|t| t.rows.iter().filter_map(Self::from_row))
+            .collect()
+    }
+
+    fn type_params(&self) -> Vec<Ident> {
+        fn collect(ty: &Ty, params: &mut Vec<Ident>) {
+            if ty.params.is_empty() && is_type_variable_name(&ty.name) {
+                params.push(ty.name.clone());
+            }
+            ty.param_tys().for_each(|p| collect(p, params));
+        }
+        let mut params: Vec<Ident> = IMPLICIT_TYPE_PARAMS.iter().map(|&p| p.into()).collect();
+        collect(&self.src, &mut params);
+        collect(&self.dest, &mut params);
+        params
+    }
+
+    fn matches(&self, src: &Ty, dest: &Ty) -> Option<Substitution> {
+        let type_params = self.type_params();
+        let mut subst = Substitution::default();
+        let ok = unify(&self.src, src, &type_params, &mut subst)
+            && unify(&self.dest, dest, &type_params, &mut subst);
+        let bounds: Vec<_> = self.bounds.iter().collect();
+        ok.then(|| satisfy(&bounds, &type_params, &subst)).flatten()
+    }
+}
+
+/// the rank of the automatic conversion from `src` to `dest`, `None` if there is none.
+/// the first matching rule wins, like in the spec table.
+pub fn conversion_rank(rules: &[ConversionRankRule], src: &Ty, dest: &Ty) -> Option<u32> {
+    let (rule, subst) = rules
+        .iter()
+        .find_map(|r| Some((r, r.matches(src, dest)?)))?;
+    match &rule.rank {
+        Rank::Finite(r) => Some(*r),
+        Rank::Infinity => None,
+        Rank::Inherit(s, t) => conversion_rank(rules, subst.get_ty(s)?, subst.get_ty(t)?),
+    }
+}
+
+/// the concrete type an abstract type converts to with the lowest rank,
+/// e.g. `vec3<AbstractInt>` becomes `vec3<i32>`. concrete types are returned unchanged.
+pub fn concretize(rules: &[ConversionRankRule], ty: &Ty) -> Ty {
+    if !is_abstract(ty) {
+        return ty.clone();
+    }
+    if ty.params.is_empty() {
+        // the concrete scalars and the concrete destinations of the rules,
+        // e.g. `__frexp_result_f32`
+        let scalars = SCALAR_TYPES.iter().map(|&s| Ty::new(s.into(), vec![]));
+        let dests = rules.iter().map(|r| r.dest.clone());
+        let concrete = scalars
+            .chain(dests)
+            .filter(|c| c.params.is_empty() && !is_abstract(c) && !is_type_variable_name(&c.name));
+        let best = concrete
+            .filter_map(|c| Some((conversion_rank(rules, ty, &c)?, c)))
+            .min_by_key(|(rank, _)| *rank);
+        return best.map(|(_, c)| c).unwrap_or_else(|| ty.clone());
+    }
+    Ty::with_args(
+        ty.name.clone(),
+        ty.params
+            .iter()
+            .map(|p| p.map_ty(|p| concretize(rules, p)))
+            .collect(),
+    )
+}
+
+/// conversion ranks between every pair of a list of types
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConversionRanks {
+    pub types: Vec<Ty>,
+    /// `ranks[src][dest]`, `None` if there is no automatic conversion
+    pub ranks: Vec<Vec<Option<u32>>>,
+}
+
+impl ConversionRanks {
+    pub fn new(rules: &[ConversionRankRule], types: Vec<Ty>) -> Self {
+        let ranks = types
+            .iter()
+            .map(|src| {
+                types
+                    .iter()
+                    .map(|dest| conversion_rank(rules, src, dest))
+                    .collect()
+            })
+            .collect();
+        ConversionRanks { types, ranks }
+    }
+
+    pub fn get(&self, src: &Ty, dest: &Ty) -> Option<u32> {
+        let src = self.types.iter().position(|t| t == src)?;
+        let dest = self.types.iter().position(|t| t == dest)?;
+        self.ranks[src][dest]
+    }
+}
+
+impl Display for ConversionRanks {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        use prettytable::{Cell, Row, Table};
+        let mut table = Table::new();
+        let mut header = vec![Cell::new("src \\ dest")];
+        header.extend(self.types.iter().map(|t| Cell::new(&t.to_string())));
+        table.add_row(Row::new(header));
+        for (src, ranks) in self.types.iter().zip(&self.ranks) {
+            let mut row = vec![Cell::new(&src.to_string())];
+            row.extend(ranks.iter().map(|r| match r {
+                Some(r) => Cell::new(&r.to_string()),
+                None => Cell::new("-"),
+            }));
+            table.add_row(Row::new(row));
+        }
+        write!(f, "{table}")
+    }
+}
+
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_conversion_rank() {
+        // a trimmed excerpt of the "Conversion Rank" section of the spec
+        let str = include_str!("testdata/conversion_rank.bs");
+        let rules = ConversionRankRule::extract(str);
+        assert_eq!(rules.len(), 17);
+        assert_eq!(
+            rules[1].to_string(),
+            "ConversionRank(ref<AS, T, AM>, T) = 0"
+        );
+
+        let ty = |s| Ty::parse(s).unwrap().1;
+        let rank = |src, dest| conversion_rank(&rules, &ty(src), &ty(dest));
+        assert_eq!(rank("f32", "f32"), Some(0));
+        assert_eq!(rank("ref<function, f32, read>", "f32"), Some(0));
+        assert_eq!(rank("ref<storage, f32, read_write>", "f32"), Some(0));
+        assert_eq!(rank("ref<storage, f32, write>", "f32"), None);
+        assert_eq!(rank("AbstractInt", "f32"), Some(6));
+        assert_eq!(rank("vec3<AbstractFloat>", "vec3<f16>"), Some(2));
+        assert_eq!(rank("vec3<AbstractFloat>", "vec2<f16>"), None);
+        assert_eq!(rank("f32", "AbstractFloat"), None);
+        assert_eq!(rank("i32", "u32"), None);
+        assert_eq!(rank("mat2x3<AbstractFloat>", "mat2x3<f32>"), Some(1));
+        assert_eq!(rank("array<AbstractInt, 4>", "array<u32, 4>"), Some(4));
+        assert_eq!(rank("array<AbstractInt, 4>", "array<u32, 3>"), None);
+        assert_eq!(
+            rank("__frexp_result_abstract", "__frexp_result_f16"),
+            Some(2)
+        );
+
+        assert_eq!(concretize(&rules, &ty("AbstractInt")), ty("i32"));
+        assert_eq!(concretize(&rules, &ty("AbstractFloat")), ty("f32"));
+        assert_eq!(
+            concretize(&rules, &ty("vec2<AbstractInt>")),
+            ty("vec2<i32>")
+        );
+        assert_eq!(concretize(&rules, &ty("u32")), ty("u32"));
+        assert_eq!(
+            concretize(&rules, &ty("__frexp_result_abstract")),
+            ty("__frexp_result_f32")
+        );
+        assert_eq!(
+            concretize(&rules, &ty("__modf_result_vec2_abstract")),
+            ty("__modf_result_vec2_f32")
+        );
+
+        let scalars = SCALAR_TYPES.iter().map(|&s| ty(s)).collect();
+        let ranks = ConversionRanks::new(&rules, scalars);
+        assert_eq!(ranks.get(&ty("AbstractInt"), &ty("AbstractFloat")), Some(5));
+        assert_eq!(ranks.get(&ty("bool"), &ty("i32")), None);
+        assert!(ranks.to_string().contains("AbstractFloat"));
+    }
+}
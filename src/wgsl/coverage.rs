@@ -0,0 +1,371 @@
+use std::{collections::HashMap, fmt::Display, ops::Range};
+
+use lazy_static::lazy_static;
+use regex::Regex;
+
+use super::{operators::OperatorRow, parametrization::OverloadRow, type_rules::TypeRuleTable};
+use crate::{
+    bikeshed,
+    diagnostic::{Diagnostic, Label, Location},
+    span::Span,
+};
+
+/// which parser a `<tr algorithm=...>` row is meant for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RowKind {
+    /// a builtin function overload, with an `<xmp>` declaration
+    Overload,
+    /// a row of a "Precondition | Conclusion" table, e.g. an operator
+    TypeRule,
+}
+
+impl Display for RowKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RowKind::Overload => write!(f, "overload"),
+            RowKind::TypeRule => write!(f, "type rule"),
+        }
+    }
+}
+
+/// a `<tr algorithm=...>` row that could not be parsed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FailedRow {
+    pub kind: RowKind,
+    pub algorithm_attr: String,
+    /// the whole row, up to the next row or the end of the table
+    pub span: Span,
+    pub error: Diagnostic,
+}
+
+/// the rows of the section below one heading
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SectionCoverage {
+    /// `None` for rows before the first heading
+    pub title: Option<String>,
+    pub parsed: usize,
+    pub failed: Vec<FailedRow>,
+    /// rows of tables that are neither overload nor type rule tables
+    pub unrecognized: usize,
+}
+
+/// how many `<tr algorithm=...>` rows of each section could be parsed
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct CoverageReport {
+    pub sections: Vec<SectionCoverage>,
+    /// the byte ranges of all rows with an `algorithm` attribute, in document order
+    pub rows: Vec<Range<usize>>,
+}
+
+impl CoverageReport {
+    pub fn failed_rows(&self) -> impl Iterator<Item = &FailedRow> {
+        self.sections.iter().flat_map(|s| s.failed.iter())
+    }
+
+    pub fn parsed(&self) -> usize {
+        self.sections.iter().map(|s| s.parsed).sum()
+    }
+
+    /// true if no row failed to parse
+    pub fn is_complete(&self) -> bool {
+        self.failed_rows().next().is_none()
+    }
+
+    fn section(&mut self, title: Option<&str>) -> &mut SectionCoverage {
+        if self.sections.last().map(|s| s.title.as_deref()) != Some(title) {
+            self.sections.push(SectionCoverage {
+                title: title.map(ToString::to_string),
+                parsed: 0,
+                failed: vec![],
+                unrecognized: 0,
+            });
+        }
+        self.sections.last_mut().unwrap()
+    }
+}
+
+/// parses every `<tr algorithm=...>` row of `text` on its own, so a row that fails to parse
+/// (even with a `cut`) is recorded instead of ending the search for further rows.
+/// returns the builtin function overloads and the coverage of all rows.
+/// `type_rule_tables` must be extracted from `text`, their unparsed rows are reported as failed
+pub fn parse_rows(
+    text: &str,
+    type_rule_tables: &[TypeRuleTable],
+) -> (Vec<OverloadRow>, CoverageReport) {
+    let unparsed: HashMap<usize, &str> = type_rule_tables
+        .iter()
+        .flat_map(|t| t.unparsed.iter())
+        .map(|row| (row.offset, row.reason.as_str()))
+        .collect();
+    let type_rule_table_offsets: Vec<usize> = type_rule_tables.iter().map(|t| t.offset).collect();
+    let headings = bikeshed::headings(text);
+
+    let mut overloads = vec![];
+    let mut report = CoverageReport::default();
+    for table in bikeshed::tables(text) {
+        let is_type_rule_table = type_rule_table_offsets.contains(&table.offset);
+        for row in table.rows {
+            let Some(algorithm) = row.algorithm else {
+                continue;
+            };
+            let title = bikeshed::section_of(&headings, row.offset).map(|h| h.title.as_str());
+            report.rows.push(row.offset..row.offset + row.raw.len());
+            let section = report.section(title);
+            let failed = |kind, error| FailedRow {
+                kind,
+                algorithm_attr: algorithm.to_string(),
+                span: Span::consumed(row.raw, &row.raw[row.raw.len()..]),
+                error,
+            };
+            if row.raw.contains("<xmp") {
+                match OverloadRow::parse(&text[row.offset..]) {
+                    Ok((_, overload)) => {
+                        overloads.push(overload);
+                        section.parsed += 1;
+                    }
+                    Err(nom::Err::Error(e) | nom::Err::Failure(e)) => {
+                        let error = Diagnostic::from_nom(text, &e);
+                        section.failed.push(failed(RowKind::Overload, error));
+                    }
+                    Err(nom::Err::Incomplete(_)) => unreachable!(),
+                }
+            } else if is_type_rule_table {
+                match unparsed.get(&row.offset) {
+                    Some(reason) if OperatorRow::parse(&text[row.offset..]).is_err() => {
+                        let error = Diagnostic {
+                            file: None,
+                            primary: Label {
+                                message: reason.to_string(),
+                                location: Location::of(text, row.raw, 1),
+                            },
+                            notes: vec![],
+                            help: None,
+                        };
+                        section.failed.push(failed(RowKind::TypeRule, error));
+                    }
+                    _ => section.parsed += 1,
+                }
+            } else {
+                section.unrecognized += 1;
+            }
+        }
+    }
+    (overloads, report)
+}
+
+/// the start of a row or block in the spec text which should be part of a row found by [`parse_rows`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnmatchedAnchor {
+    /// `algorithm="` or `<xmp highlight=rust>`
+    pub anchor: &'static str,
+    pub location: Location,
+}
+
+/// the anchors which are not part of any row of a [`CoverageReport`],
+/// e.g. because a changed row format is no longer recognized as a table row with an `algorithm`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnmatchedAnchors {
+    /// the number of `<tr ... algorithm="` rows in the text
+    pub algorithm_rows: usize,
+    /// the number of `<xmp highlight=rust>` blocks declaring functions in the text
+    pub fn_blocks: usize,
+    pub unmatched: Vec<UnmatchedAnchor>,
+}
+
+/// finds the `<tr>` tags with an `algorithm` attribute and the `<xmp highlight=rust>` function declarations
+/// of `text` that are not part of the rows of `report`, which must be created from `text`
+pub fn check_anchors(text: &str, report: &CoverageReport) -> Result<(), UnmatchedAnchors> {
+    lazy_static! {
+        static ref ALGORITHM_ROW: Regex = Regex::new(r#"<tr\b[^>]*\balgorithm=""#).unwrap();
+        // e.g. `<xmp highlight=rust>@const @must_use fn sqrt(e: T) -> T</xmp>`
+        static ref FN_BLOCK: Regex =
+            Regex::new(r"<xmp highlight=rust>\s*(?:@\w+(?:\([^)]*\))?\s*)*fn\b").unwrap();
+    }
+    let row_starts: Vec<usize> = report.rows.iter().map(|r| r.start).collect();
+    let in_row = |offset: usize| report.rows.iter().any(|r| r.contains(&offset));
+    let algorithm_rows: Vec<usize> = ALGORITHM_ROW.find_iter(text).map(|m| m.start()).collect();
+    let fn_blocks: Vec<usize> = FN_BLOCK.find_iter(text).map(|m| m.start()).collect();
+
+    let unmatched_rows = algorithm_rows.iter().filter(|o| !row_starts.contains(o));
+    let unmatched_rows = unmatched_rows.map(|&o| ("algorithm=\"", o));
+    let unmatched_blocks = fn_blocks.iter().filter(|&&o| !in_row(o));
+    let unmatched_blocks = unmatched_blocks.map(|&o| ("<xmp highlight=rust>", o));
+    let mut unmatched: Vec<_> = unmatched_rows
+        .chain(unmatched_blocks)
+        .filter_map(|(anchor, offset)| {
+            let location = Location::of(text, &text[offset..], anchor.len())?;
+            Some((offset, UnmatchedAnchor { anchor, location }))
+        })
+        .collect();
+    unmatched.sort_by_key(|(offset, _)| *offset);
+    match unmatched.is_empty() {
+        true => Ok(()),
+        false => Err(UnmatchedAnchors {
+            algorithm_rows: algorithm_rows.len(),
+            fn_blocks: fn_blocks.len(),
+            unmatched: unmatched.into_iter().map(|(_, a)| a).collect(),
+        }),
+    }
+}
+
+impl Display for UnmatchedAnchors {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "{} of {} `<tr algorithm=` rows and {} `<xmp highlight=rust>` blocks were not found by the parser:",
+            self.unmatched.len(),
+            self.algorithm_rows,
+            self.fn_blocks
+        )?;
+        for a in &self.unmatched {
+            let l = &a.location;
+            writeln!(
+                f,
+                "    {}:{}: `{}` in {}",
+                l.line,
+                l.column,
+                a.anchor,
+                l.source_line.trim()
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for UnmatchedAnchors {}
+
+impl Display for CoverageReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        use prettytable::{Row, Table};
+        let mut table = Table::new();
+        table.add_row(Row::from(["section", "parsed", "failed", "unrecognized"]));
+        for s in &self.sections {
+            table.add_row(Row::from([
+                s.title.as_deref().unwrap_or("<no section>").to_string(),
+                s.parsed.to_string(),
+                s.failed.len().to_string(),
+                s.unrecognized.to_string(),
+            ]));
+        }
+        write!(f, "{table}")?;
+        for s in self.sections.iter().filter(|s| !s.failed.is_empty()) {
+            writeln!(f, "{}:", s.title.as_deref().unwrap_or("<no section>"))?;
+            for row in &s.failed {
+                writeln!(
+                    f,
+                    "    {} row \"{}\" at {}: {}",
+                    row.kind, row.algorithm_attr, row.span, row.error.primary.message
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+mod tests {
+    use super::*;
+    use crate::span::with_source;
+
+    #[test]
+    fn test_coverage() {
+        let text = r#"
+## Numeric Built-in Functions ## {#numeric-builtin-functions}
+<table class='data builtin'>
+  <tr algorithm="abs">
+    <td>|T| is [=f32=]
+    <td><xmp highlight=rust>fn abs(e: T) -> T</xmp>
+  <tr algorithm="sign">
+    <td>|T| is [=f32=]
+    <td><xmp highlight=rust>fn sign(e: T) -> T</xmb>
+  <tr algorithm="trunc">
+    <td>|T| is [=f32=]
+    <td><xmp highlight=rust>fn trunc(e: T) -> T</xmp>
+</table>
+### Arithmetic Expressions ### {#arithmetic-expr}
+<table class='data'>
+  <caption>Unary arithmetic expressions</caption>
+  <thead><tr><th>Precondition<th>Conclusion<th>Description</thead>
+  <tr algorithm="negation">
+    <td>|e|: |T|<br>|T| is [=f32=]
+    <td>`-`|e|`:` |T|
+    <td>Negation.
+  <tr algorithm="unknown">
+    <td>|e|: |T|<br>|T| is [=f32=]
+    <td>a conclusion
+    <td>Not a rule.
+</table>
+<table class='data'>
+  <tr algorithm="texel format"><td>rgba8unorm<td>f32
+</table>
+"#;
+        let tables = TypeRuleTable::extract(text);
+        let (overloads, report) = with_source(text, || parse_rows(text, &tables));
+        let names: Vec<_> = overloads.iter().map(|o| o.fn_decl.name.as_str()).collect();
+        assert_eq!(names, ["abs", "trunc"]);
+
+        assert_eq!(report.parsed(), 3);
+        assert!(!report.is_complete());
+        let sections: Vec<_> = report
+            .sections
+            .iter()
+            .map(|s| (s.title.as_deref(), s.parsed, s.failed.len(), s.unrecognized))
+            .collect();
+        assert_eq!(
+            sections,
+            [
+                (Some("Numeric Built-in Functions"), 2, 1, 0),
+                (Some("Arithmetic Expressions"), 1, 1, 1)
+            ]
+        );
+
+        let failed: Vec<_> = report.failed_rows().collect();
+        assert_eq!(failed[0].kind, RowKind::Overload);
+        assert_eq!(failed[0].algorithm_attr, "sign");
+        assert_eq!(failed[0].span.line, 7);
+        assert_eq!(
+            failed[0].error.help.as_deref(),
+            Some("did you mean `</xmp>`?")
+        );
+        assert_eq!(failed[0].error.primary.location.as_ref().unwrap().line, 9);
+        assert_eq!(
+            (failed[1].kind, failed[1].span.line),
+            (RowKind::TypeRule, 22)
+        );
+        assert!(report
+            .to_string()
+            .contains("    overload row \"sign\" at 7:3: expected `</xmp>`"));
+        assert_eq!(check_anchors(text, &report), Ok(()));
+
+        let changed = text.replace(
+            "<tr algorithm=\"trunc\">",
+            "<tr class=x algorithm=\"trunc\">",
+        );
+        let tables = TypeRuleTable::extract(&changed);
+        let (overloads, report) = parse_rows(&changed, &tables);
+        assert_eq!(overloads.len(), 1);
+        let unmatched = check_anchors(&changed, &report).unwrap_err();
+        assert_eq!((unmatched.algorithm_rows, unmatched.fn_blocks), (6, 3));
+        let anchors: Vec<_> = unmatched
+            .unmatched
+            .iter()
+            .map(|a| (a.anchor, a.location.line))
+            .collect();
+        assert_eq!(
+            anchors,
+            [("algorithm=\"", 10), ("<xmp highlight=rust>", 12)]
+        );
+
+        // declarations usually start with attributes
+        let outside =
+            format!("{text}<xmp highlight=rust>@const @must_use fn sqrt(e: T) -> T</xmp>\n");
+        let tables = TypeRuleTable::extract(&outside);
+        let (_, report) = parse_rows(&outside, &tables);
+        let unmatched = check_anchors(&outside, &report).unwrap_err();
+        assert_eq!(unmatched.fn_blocks, 4);
+        let anchor = &unmatched.unmatched[0];
+        assert_eq!(
+            (anchor.anchor, anchor.location.line),
+            ("<xmp highlight=rust>", 30)
+        );
+    }
+}
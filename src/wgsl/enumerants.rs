@@ -0,0 +1,194 @@
+use std::fmt::Display;
+
+/// declares an enum of predeclared enumerants with their WGSL names
+macro_rules! enumerants {
+    ($(#[$meta: meta])* $name: ident { $($variant: ident = $wgsl: literal,)* }) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+        pub enum $name {
+            $($variant,)*
+        }
+
+        impl $name {
+            pub const ALL: &'static [$name] = &[$($name::$variant,)*];
+
+            /// the name in WGSL source, e.g. `read_write`
+            pub fn name(&self) -> &'static str {
+                match self {
+                    $($name::$variant => $wgsl,)*
+                }
+            }
+
+            pub fn from_name(name: &str) -> Option<Self> {
+                Self::ALL.iter().copied().find(|e| e.name() == name)
+            }
+        }
+
+        impl Display for $name {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                write!(f, "{}", self.name())
+            }
+        }
+    };
+}
+
+enumerants!(
+    AccessMode {
+        Read = "read",
+        Write = "write",
+        ReadWrite = "read_write",
+    }
+);
+
+enumerants!(
+    /// `handle` is not predeclared, it cannot be written in WGSL source
+    AddressSpace {
+        Function = "function",
+        Private = "private",
+        Workgroup = "workgroup",
+        Uniform = "uniform",
+        Storage = "storage",
+        Handle = "handle",
+    }
+);
+
+enumerants!(
+    /// the formats of storage textures
+    TexelFormat {
+        Rgba8unorm = "rgba8unorm",
+        Rgba8snorm = "rgba8snorm",
+        Rgba8uint = "rgba8uint",
+        Rgba8sint = "rgba8sint",
+        Rgba16uint = "rgba16uint",
+        Rgba16sint = "rgba16sint",
+        Rgba16float = "rgba16float",
+        R32uint = "r32uint",
+        R32sint = "r32sint",
+        R32float = "r32float",
+        Rg32uint = "rg32uint",
+        Rg32sint = "rg32sint",
+        Rg32float = "rg32float",
+        Rgba32uint = "rgba32uint",
+        Rgba32sint = "rgba32sint",
+        Rgba32float = "rgba32float",
+        Bgra8unorm = "bgra8unorm",
+    }
+);
+
+enumerants!(
+    /// the values of the `@builtin` attribute. they are not predeclared enumerants, the names
+    /// only have a meaning inside the attribute
+    BuiltinValue {
+        VertexIndex = "vertex_index",
+        InstanceIndex = "instance_index",
+        ClipDistances = "clip_distances",
+        Position = "position",
+        FrontFacing = "front_facing",
+        FragDepth = "frag_depth",
+        SampleIndex = "sample_index",
+        SampleMask = "sample_mask",
+        LocalInvocationId = "local_invocation_id",
+        LocalInvocationIndex = "local_invocation_index",
+        GlobalInvocationId = "global_invocation_id",
+        WorkgroupId = "workgroup_id",
+        NumWorkgroups = "num_workgroups",
+        SubgroupInvocationId = "subgroup_invocation_id",
+        SubgroupSize = "subgroup_size",
+    }
+);
+
+impl AddressSpace {
+    /// the access mode of a `ptr` or `ref` which does not name one
+    pub fn default_access_mode(&self) -> AccessMode {
+        match self {
+            AddressSpace::Function | AddressSpace::Private | AddressSpace::Workgroup => {
+                AccessMode::ReadWrite
+            }
+            AddressSpace::Uniform | AddressSpace::Storage | AddressSpace::Handle => {
+                AccessMode::Read
+            }
+        }
+    }
+
+    /// true if `atomic<T>` variables can be declared in the address space
+    pub fn allows_atomics(&self) -> bool {
+        matches!(self, AddressSpace::Workgroup | AddressSpace::Storage)
+    }
+}
+
+impl TexelFormat {
+    /// the channel type of the format, `f32`, `i32` or `u32`
+    pub fn channel_type(&self) -> &'static str {
+        let name = self.name();
+        match () {
+            _ if name.ends_with("sint") => "i32",
+            _ if name.ends_with("uint") => "u32",
+            _ => "f32",
+        }
+    }
+}
+
+/// an enumerant used as a template argument, e.g. in `ptr<storage, T, read_write>`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Enumerant {
+    AccessMode(AccessMode),
+    AddressSpace(AddressSpace),
+    TexelFormat(TexelFormat),
+}
+
+impl Display for Enumerant {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl Enumerant {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Enumerant::AccessMode(e) => e.name(),
+            Enumerant::AddressSpace(e) => e.name(),
+            Enumerant::TexelFormat(e) => e.name(),
+        }
+    }
+
+    /// the predeclared enumerant named `name`. `handle` is not predeclared
+    pub fn from_name(name: &str) -> Option<Self> {
+        let address_space = AddressSpace::from_name(name).filter(|s| *s != AddressSpace::Handle);
+        AccessMode::from_name(name)
+            .map(Enumerant::AccessMode)
+            .or(address_space.map(Enumerant::AddressSpace))
+            .or(TexelFormat::from_name(name).map(Enumerant::TexelFormat))
+    }
+
+    /// the enumerant named `name` of the family the spec calls `family`, e.g. "access mode"
+    pub fn from_family(family: &str, name: &str) -> Option<Self> {
+        match family {
+            "access mode" => AccessMode::from_name(name).map(Enumerant::AccessMode),
+            "address space" => AddressSpace::from_name(name).map(Enumerant::AddressSpace),
+            "texel format" => TexelFormat::from_name(name).map(Enumerant::TexelFormat),
+            _ => None,
+        }
+    }
+}
+
+/// the families of [`Enumerant`], as named by the spec
+pub const ENUMERANT_FAMILIES: &[&str] = &["access mode", "address space", "texel format"];
+
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_enumerants() {
+        assert_eq!(
+            Enumerant::from_name("storage"),
+            Some(Enumerant::AddressSpace(AddressSpace::Storage))
+        );
+        assert_eq!(Enumerant::from_name("handle"), None);
+        assert_eq!(TexelFormat::R32sint.channel_type(), "i32");
+        assert_eq!(TexelFormat::Bgra8unorm.channel_type(), "f32");
+        assert_eq!(
+            AddressSpace::Storage.default_access_mode(),
+            AccessMode::Read
+        );
+    }
+}
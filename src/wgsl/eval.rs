@@ -0,0 +1,788 @@
+use std::fmt::Display;
+
+use super::conversions::{conversion_rank, ConversionRankRule};
+use super::parametrization::OverloadRow;
+use super::primitives::*;
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum EvalError {
+    #[error("no overload of `{0}` accepts arguments of type ({1})")]
+    NoOverload(String, String),
+    #[error("no overload of `{0}` is better than the others for arguments of type ({1})")]
+    Ambiguous(String, String),
+    #[error("`{0}` is not a const builtin")]
+    NotConst(String),
+    #[error("`{0}` is not implemented")]
+    NotImplemented(String),
+    #[error("`{0}` overflows")]
+    Overflow(String),
+    #[error("arguments are outside of the domain of `{0}`")]
+    Domain(String),
+}
+
+/// rounds to the nearest value representable as an IEEE-754 binary16, ties to even.
+/// values outside of the finite range of `f16` become infinite.
+pub fn round_to_f16(v: f64) -> f64 {
+    const MAX: f64 = 65504.0;
+    if v == 0.0 || !v.is_finite() {
+        return v;
+    }
+    // the distance between neighboring f16 values at the magnitude of `v`, subnormals below 2^-14
+    let exponent = (v.abs().log2().floor() as i32).max(-14);
+    let quantum = 2f64.powi(exponent - 10);
+    let rounded = (v / quantum).round_ties_even() * quantum;
+    match rounded.abs() > MAX {
+        true => rounded.signum() * f64::INFINITY,
+        false => rounded,
+    }
+}
+
+/// a concrete or abstract scalar value
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Scalar {
+    Bool(bool),
+    AbstractInt(i64),
+    AbstractFloat(f64),
+    I32(i32),
+    U32(u32),
+    F32(f32),
+    /// stored as `f32`, always representable as `f16`
+    F16(f32),
+}
+
+impl Display for Scalar {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Scalar::Bool(b) => write!(f, "{b}"),
+            Scalar::AbstractInt(i) => write!(f, "{i}"),
+            Scalar::AbstractFloat(x) => write!(f, "{x:?}"),
+            Scalar::I32(i) => write!(f, "{i}i"),
+            Scalar::U32(u) => write!(f, "{u}u"),
+            Scalar::F32(x) => write!(f, "{x}f"),
+            Scalar::F16(x) => write!(f, "{x}h"),
+        }
+    }
+}
+
+impl Scalar {
+    /// a scalar of the type named `type_name`, e.g. `"f32"`. integers are rounded towards positive infinity
+    pub fn new(type_name: &str, v: f64) -> Option<Self> {
+        let int = v.ceil();
+        Some(match type_name {
+            "bool" => Scalar::Bool(v != 0.0),
+            "AbstractInt" => Scalar::AbstractInt(int as i64),
+            "AbstractFloat" => Scalar::AbstractFloat(v),
+            "i32" => Scalar::I32(int as i32),
+            "u32" => Scalar::U32(int as u32),
+            "f32" => Scalar::F32(v as f32),
+            "f16" => Scalar::F16(round_to_f16(v) as f32),
+            _ => return None,
+        })
+    }
+
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Scalar::Bool(_) => "bool",
+            Scalar::AbstractInt(_) => "AbstractInt",
+            Scalar::AbstractFloat(_) => "AbstractFloat",
+            Scalar::I32(_) => "i32",
+            Scalar::U32(_) => "u32",
+            Scalar::F32(_) => "f32",
+            Scalar::F16(_) => "f16",
+        }
+    }
+
+    pub fn is_float(&self) -> bool {
+        matches!(
+            self,
+            Scalar::AbstractFloat(_) | Scalar::F32(_) | Scalar::F16(_)
+        )
+    }
+
+    pub fn as_f64(&self) -> f64 {
+        match *self {
+            Scalar::Bool(b) => b as u8 as f64,
+            Scalar::AbstractInt(i) => i as f64,
+            Scalar::AbstractFloat(x) => x,
+            Scalar::I32(i) => i as f64,
+            Scalar::U32(u) => u as f64,
+            Scalar::F32(x) | Scalar::F16(x) => x as f64,
+        }
+    }
+
+    /// the value of an integer scalar
+    pub fn as_i128(&self) -> Option<i128> {
+        match *self {
+            Scalar::AbstractInt(i) => Some(i as i128),
+            Scalar::I32(i) => Some(i as i128),
+            Scalar::U32(u) => Some(u as i128),
+            _ => None,
+        }
+    }
+
+    /// the bit pattern of a 32 bit integer scalar
+    pub fn as_bits32(&self) -> Option<u32> {
+        match *self {
+            Scalar::I32(i) => Some(i as u32),
+            Scalar::U32(u) => Some(u),
+            _ => None,
+        }
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match *self {
+            Scalar::Bool(b) => Some(b),
+            _ => None,
+        }
+    }
+
+    /// a float of the same type as `self`, rounded to its precision.
+    /// in a const-expression, non-finite results are errors.
+    pub fn float_like(&self, v: f64, op: &str) -> Result<Scalar, EvalError> {
+        let check = |v: f64| match v {
+            v if v.is_nan() => Err(EvalError::Domain(op.to_string())),
+            v if v.is_infinite() => Err(EvalError::Overflow(op.to_string())),
+            v => Ok(v),
+        };
+        let v = check(v)?;
+        match self {
+            Scalar::F32(_) => Ok(Scalar::F32(check(v as f32 as f64)? as f32)),
+            Scalar::F16(_) => Ok(Scalar::F16(check(round_to_f16(v))? as f32)),
+            _ => Ok(Scalar::AbstractFloat(v)),
+        }
+    }
+
+    /// an integer of the same type as `self`. in a const-expression, results out of range are errors.
+    pub fn int_like(&self, v: i128, op: &str) -> Result<Scalar, EvalError> {
+        let overflow = || EvalError::Overflow(op.to_string());
+        match self {
+            Scalar::I32(_) => i32::try_from(v).map(Scalar::I32).map_err(|_| overflow()),
+            Scalar::U32(_) => u32::try_from(v).map(Scalar::U32).map_err(|_| overflow()),
+            _ => i64::try_from(v)
+                .map(Scalar::AbstractInt)
+                .map_err(|_| overflow()),
+        }
+    }
+
+    /// a 32 bit integer of the same type as `self` with the bit pattern `bits`
+    pub fn bits32_like(&self, bits: u32) -> Scalar {
+        match self {
+            Scalar::I32(_) => Scalar::I32(bits as i32),
+            _ => Scalar::U32(bits),
+        }
+    }
+
+    /// a number of the same type as `self`
+    pub fn number_like(&self, v: f64, op: &str) -> Result<Scalar, EvalError> {
+        match self.is_float() {
+            true => self.float_like(v, op),
+            false => self.int_like(v as i128, op),
+        }
+    }
+}
+
+/// the value of a const-expression
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Scalar(Scalar),
+    Vector(Vec<Scalar>),
+}
+
+/// written as a WGSL expression, e.g. `vec2<f32>(1f, 2f)`.
+/// abstract vectors have no type that can be written, so their element type is inferred, e.g. `vec2(1, 2)`
+impl Display for Value {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Value::Scalar(s) => write!(f, "{s}"),
+            Value::Vector(v) => {
+                let components: Vec<_> = v.iter().map(ToString::to_string).collect();
+                let ty = self.ty();
+                let is_abstract = ty.param_tys().any(|p| p.name.starts_with("Abstract"));
+                match is_abstract {
+                    true => write!(f, "{}({})", ty.name, components.join(", ")),
+                    false => write!(f, "{ty}({})", components.join(", ")),
+                }
+            }
+        }
+    }
+}
+
+impl From<Scalar> for Value {
+    fn from(s: Scalar) -> Self {
+        Value::Scalar(s)
+    }
+}
+
+impl Value {
+    pub fn ty(&self) -> Ty {
+        let scalar = |s: &Scalar| Ty::new(s.type_name().into(), vec![]);
+        match self {
+            Value::Scalar(s) => scalar(s),
+            Value::Vector(v) => Ty::new(
+                format!("vec{}", v.len()).as_str().into(),
+                v.first().map(scalar).into_iter().collect(),
+            ),
+        }
+    }
+
+    pub fn components(&self) -> &[Scalar] {
+        match self {
+            Value::Scalar(s) => std::slice::from_ref(s),
+            Value::Vector(v) => v,
+        }
+    }
+}
+
+/// applies `f` to the components of the arguments. scalar arguments are used for every component.
+fn componentwise(
+    args: &[Value],
+    f: impl Fn(&[Scalar]) -> Result<Scalar, EvalError>,
+) -> Result<Value, EvalError> {
+    let width = args.iter().find_map(|a| match a {
+        Value::Vector(v) => Some(v.len()),
+        Value::Scalar(_) => None,
+    });
+    let component = |i: usize| -> Vec<Scalar> {
+        args.iter()
+            .map(|a| match a {
+                Value::Scalar(s) => *s,
+                Value::Vector(v) => v[i],
+            })
+            .collect()
+    };
+    match width {
+        None => f(&component(0)).map(Value::Scalar),
+        Some(n) => (0..n)
+            .map(|i| f(&component(i)))
+            .collect::<Result<_, _>>()
+            .map(Value::Vector),
+    }
+}
+
+/// a component-wise float function
+fn float_fn(op: &str, args: &[Value], f: impl Fn(&[f64]) -> f64) -> Result<Value, EvalError> {
+    componentwise(args, |s| {
+        let x: Vec<f64> = s.iter().map(Scalar::as_f64).collect();
+        s[0].float_like(f(&x), op)
+    })
+}
+
+/// a component-wise function on 32 bit integers
+fn bits_fn(op: &str, args: &[Value], f: impl Fn(&[u32]) -> u32) -> Result<Value, EvalError> {
+    componentwise(args, |s| {
+        let bits = s.iter().map(Scalar::as_bits32).collect::<Option<Vec<_>>>();
+        let bits = bits.ok_or_else(|| EvalError::NotImplemented(op.to_string()))?;
+        Ok(s[0].bits32_like(f(&bits)))
+    })
+}
+
+fn dot(op: &str, a: &Value, b: &Value) -> Result<Scalar, EvalError> {
+    let first = a.components()[0];
+    match first.is_float() {
+        true => {
+            let products = a.components().iter().zip(b.components());
+            let dot = products.map(|(a, b)| a.as_f64() * b.as_f64()).sum();
+            first.float_like(dot, op)
+        }
+        false => {
+            let products = a.components().iter().zip(b.components());
+            let dot = products
+                .map(|(a, b)| a.as_i128().unwrap_or_default() * b.as_i128().unwrap_or_default());
+            first.int_like(dot.sum(), op)
+        }
+    }
+}
+
+fn length(op: &str, e: &Value) -> Result<Scalar, EvalError> {
+    let components = e.components();
+    let sum: f64 = components.iter().map(|c| c.as_f64() * c.as_f64()).sum();
+    components[0].float_like(sum.sqrt(), op)
+}
+
+fn scale(op: &str, e: &Value, factor: f64) -> Result<Value, EvalError> {
+    componentwise(std::slice::from_ref(e), |s| {
+        s[0].float_like(s[0].as_f64() * factor, op)
+    })
+}
+
+fn sub(op: &str, a: &Value, b: &Value) -> Result<Value, EvalError> {
+    float_fn(op, &[a.clone(), b.clone()], |x| x[0] - x[1])
+}
+
+fn min_max(op: &str, args: &[Value], take_min: bool) -> Result<Value, EvalError> {
+    componentwise(args, |s| {
+        let ordered = match s[0].as_i128().zip(s[1].as_i128()) {
+            Some((a, b)) => a <= b,
+            None => s[0].as_f64() <= s[1].as_f64(),
+        };
+        let min = if ordered { s[0] } else { s[1] };
+        let max = if ordered { s[1] } else { s[0] };
+        let result = if take_min { min } else { max };
+        match result.is_float() {
+            true => result.float_like(result.as_f64(), op),
+            false => Ok(result),
+        }
+    })
+}
+
+/// `firstLeadingBit`, for signed integers the most significant bit that differs from the sign bit
+fn first_leading_bit(s: Scalar, bits: u32) -> u32 {
+    let bits = match s {
+        Scalar::I32(i) if i < 0 => !bits,
+        _ => bits,
+    };
+    match bits {
+        0 => u32::MAX,
+        bits => 31 - bits.leading_zeros(),
+    }
+}
+
+/// `offset + count` must not exceed the bit width, in a const-expression this is an error
+fn check_bit_range(op: &str, offset: u32, count: u32) -> Result<(), EvalError> {
+    match offset as u64 + count as u64 > 32 {
+        true => Err(EvalError::Domain(op.to_string())),
+        false => Ok(()),
+    }
+}
+
+fn extract_bits(s: Scalar, e: u32, offset: u32, count: u32) -> u32 {
+    if count == 0 {
+        return 0;
+    }
+    let bits = ((e as u64 >> offset) & ((1u64 << count) - 1)) as u32;
+    match s {
+        // sign extend
+        Scalar::I32(_) if count < 32 && bits & (1 << (count - 1)) != 0 => {
+            bits | (u32::MAX << count)
+        }
+        _ => bits,
+    }
+}
+
+fn insert_bits(e: u32, new_bits: u32, offset: u32, count: u32) -> u32 {
+    let mask = (((1u64 << count) - 1) << offset) as u32;
+    (e & !mask) | ((((new_bits as u64) << offset) as u32) & mask)
+}
+
+macro_rules! builtins {
+    ($($name: literal => $f: expr,)*) => {
+        /// the builtins [`eval_builtin`] can evaluate
+        pub const IMPLEMENTED: &[&str] = &[$($name),*];
+
+        /// evaluates a builtin on arguments that match one of its overloads, which
+        /// [`Evaluator::call`] checks. `None` if the builtin is not implemented.
+        pub(super) fn eval_builtin(name: &str, args: &[Value]) -> Option<Result<Value, EvalError>> {
+            let f: fn(&str, &[Value]) -> Result<Value, EvalError> = match name {
+                $($name => $f,)*
+                _ => return None,
+            };
+            Some(f(name, args))
+        }
+    };
+}
+
+builtins! {
+    "abs" => |op, a| componentwise(a, |s| match s[0] {
+        Scalar::AbstractInt(i) => i.checked_abs().map(Scalar::AbstractInt).ok_or(EvalError::Overflow(op.to_string())),
+        // the most negative i32 is its own absolute value
+        Scalar::I32(i) => Ok(Scalar::I32(i.wrapping_abs())),
+        Scalar::U32(u) => Ok(Scalar::U32(u)),
+        s => s.float_like(s.as_f64().abs(), op),
+    }),
+    "acos" => |op, a| float_fn(op, a, |x| x[0].acos()),
+    "acosh" => |op, a| float_fn(op, a, |x| x[0].acosh()),
+    "asin" => |op, a| float_fn(op, a, |x| x[0].asin()),
+    "asinh" => |op, a| float_fn(op, a, |x| x[0].asinh()),
+    "atan" => |op, a| float_fn(op, a, |x| x[0].atan()),
+    "atanh" => |op, a| float_fn(op, a, |x| match x[0].abs() < 1.0 {
+        true => x[0].atanh(),
+        false => f64::NAN,
+    }),
+    "atan2" => |op, a| float_fn(op, a, |x| x[0].atan2(x[1])),
+    "ceil" => |op, a| float_fn(op, a, |x| x[0].ceil()),
+    "clamp" => |op, a| {
+        let max = min_max(op, &a[..2], false)?;
+        min_max(op, &[max, a[2].clone()], true)
+    },
+    "cos" => |op, a| float_fn(op, a, |x| x[0].cos()),
+    "cosh" => |op, a| float_fn(op, a, |x| x[0].cosh()),
+    "cross" => |op, a| {
+        let (u, v) = (a[0].components(), a[1].components());
+        let (u, v): (Vec<f64>, Vec<f64>) = (u.iter().map(Scalar::as_f64).collect(), v.iter().map(Scalar::as_f64).collect());
+        let cross = [u[1] * v[2] - u[2] * v[1], u[2] * v[0] - u[0] * v[2], u[0] * v[1] - u[1] * v[0]];
+        let first = a[0].components()[0];
+        let components = cross.iter().map(|&c| first.float_like(c, op)).collect::<Result<_, _>>()?;
+        Ok(Value::Vector(components))
+    },
+    "degrees" => |op, a| float_fn(op, a, |x| x[0].to_degrees()),
+    "distance" => |op, a| length(op, &sub(op, &a[0], &a[1])?).map(Value::Scalar),
+    "dot" => |op, a| dot(op, &a[0], &a[1]).map(Value::Scalar),
+    "exp" => |op, a| float_fn(op, a, |x| x[0].exp()),
+    "exp2" => |op, a| float_fn(op, a, |x| x[0].exp2()),
+    "faceForward" => |op, a| {
+        let sign = if dot(op, &a[1], &a[2])?.as_f64() < 0.0 { 1.0 } else { -1.0 };
+        scale(op, &a[0], sign)
+    },
+    "floor" => |op, a| float_fn(op, a, |x| x[0].floor()),
+    "fma" => |op, a| float_fn(op, a, |x| x[0].mul_add(x[1], x[2])),
+    "fract" => |op, a| float_fn(op, a, |x| x[0] - x[0].floor()),
+    "inverseSqrt" => |op, a| float_fn(op, a, |x| match x[0] > 0.0 {
+        true => 1.0 / x[0].sqrt(),
+        false => f64::NAN,
+    }),
+    "ldexp" => |op, a| float_fn(op, a, |x| x[0] * 2f64.powf(x[1])),
+    "length" => |op, a| length(op, &a[0]).map(Value::Scalar),
+    "log" => |op, a| float_fn(op, a, |x| match x[0] > 0.0 {
+        true => x[0].ln(),
+        false => f64::NAN,
+    }),
+    "log2" => |op, a| float_fn(op, a, |x| match x[0] > 0.0 {
+        true => x[0].log2(),
+        false => f64::NAN,
+    }),
+    "max" => |op, a| min_max(op, a, false),
+    "min" => |op, a| min_max(op, a, true),
+    "mix" => |op, a| float_fn(op, a, |x| x[0] * (1.0 - x[2]) + x[1] * x[2]),
+    "normalize" => |op, a| {
+        let length = length(op, &a[0])?.as_f64();
+        match length > 0.0 {
+            true => scale(op, &a[0], 1.0 / length),
+            false => Err(EvalError::Domain(op.to_string())),
+        }
+    },
+    "pow" => |op, a| float_fn(op, a, |x| x[0].powf(x[1])),
+    "quantizeToF16" => |op, a| float_fn(op, a, |x| round_to_f16(x[0])),
+    "radians" => |op, a| float_fn(op, a, |x| x[0].to_radians()),
+    "reflect" => |op, a| {
+        let d = dot(op, &a[1], &a[0])?.as_f64();
+        sub(op, &a[0], &scale(op, &a[1], 2.0 * d)?)
+    },
+    "refract" => |op, a| {
+        let (e1, e2, e3) = (&a[0], &a[1], a[2].components()[0].as_f64());
+        let d = dot(op, e2, e1)?.as_f64();
+        let k = 1.0 - e3 * e3 * (1.0 - d * d);
+        match k < 0.0 {
+            true => scale(op, e1, 0.0),
+            false => sub(op, &scale(op, e1, e3)?, &scale(op, e2, e3 * d + k.sqrt())?),
+        }
+    },
+    "round" => |op, a| float_fn(op, a, |x| x[0].round_ties_even()),
+    "saturate" => |op, a| float_fn(op, a, |x| x[0].clamp(0.0, 1.0)),
+    "sign" => |op, a| componentwise(a, |s| {
+        let x = s[0].as_f64();
+        let sign = if x > 0.0 { 1.0 } else if x < 0.0 { -1.0 } else { 0.0 };
+        s[0].number_like(sign, op)
+    }),
+    "sin" => |op, a| float_fn(op, a, |x| x[0].sin()),
+    "sinh" => |op, a| float_fn(op, a, |x| x[0].sinh()),
+    "smoothstep" => |op, a| float_fn(op, a, |x| {
+        let t = ((x[2] - x[0]) / (x[1] - x[0])).clamp(0.0, 1.0);
+        t * t * (3.0 - 2.0 * t)
+    }),
+    "sqrt" => |op, a| float_fn(op, a, |x| x[0].sqrt()),
+    "step" => |op, a| float_fn(op, a, |x| if x[0] <= x[1] { 1.0 } else { 0.0 }),
+    "tan" => |op, a| float_fn(op, a, |x| x[0].tan()),
+    "tanh" => |op, a| float_fn(op, a, |x| x[0].tanh()),
+    "trunc" => |op, a| float_fn(op, a, |x| x[0].trunc()),
+    "countLeadingZeros" => |op, a| bits_fn(op, a, |b| b[0].leading_zeros()),
+    "countOneBits" => |op, a| bits_fn(op, a, |b| b[0].count_ones()),
+    "countTrailingZeros" => |op, a| bits_fn(op, a, |b| b[0].trailing_zeros()),
+    "reverseBits" => |op, a| bits_fn(op, a, |b| b[0].reverse_bits()),
+    "firstLeadingBit" => |op, a| componentwise(a, |s| {
+        let bits = s[0].as_bits32().ok_or(EvalError::NotImplemented(op.to_string()))?;
+        Ok(s[0].bits32_like(first_leading_bit(s[0], bits)))
+    }),
+    "firstTrailingBit" => |op, a| bits_fn(op, a, |b| match b[0] {
+        0 => u32::MAX,
+        b => b.trailing_zeros(),
+    }),
+    "extractBits" => |op, a| componentwise(a, |s| {
+        let bits = s.iter().map(Scalar::as_bits32).collect::<Option<Vec<_>>>();
+        let b = bits.ok_or(EvalError::NotImplemented(op.to_string()))?;
+        check_bit_range(op, b[1], b[2])?;
+        Ok(s[0].bits32_like(extract_bits(s[0], b[0], b[1], b[2])))
+    }),
+    "insertBits" => |op, a| componentwise(a, |s| {
+        let bits = s.iter().map(Scalar::as_bits32).collect::<Option<Vec<_>>>();
+        let b = bits.ok_or(EvalError::NotImplemented(op.to_string()))?;
+        check_bit_range(op, b[2], b[3])?;
+        Ok(s[0].bits32_like(insert_bits(b[0], b[1], b[2], b[3])))
+    }),
+    "all" => |_, a| Ok(Scalar::Bool(a[0].components().iter().all(|c| c.as_bool() == Some(true))).into()),
+    "any" => |_, a| Ok(Scalar::Bool(a[0].components().iter().any(|c| c.as_bool() == Some(true))).into()),
+    "select" => |_, a| componentwise(a, |s| Ok(match s[2].as_bool() {
+        Some(true) => s[1],
+        _ => s[0],
+    })),
+}
+
+/// converts a scalar to the scalar type named `type_name`, e.g. an `AbstractInt` to `f32`.
+/// in a const-expression, values that cannot be represented are errors.
+fn convert_scalar(s: Scalar, type_name: &str, op: &str) -> Result<Scalar, EvalError> {
+    if s.type_name() == type_name {
+        return Ok(s);
+    }
+    let like = Scalar::new(type_name, 0.0).ok_or(EvalError::NotImplemented(op.to_string()))?;
+    match s.as_i128() {
+        Some(i) if !like.is_float() => like.int_like(i, op),
+        _ => like.number_like(s.as_f64(), op),
+    }
+}
+
+/// converts a value to `ty`, a scalar or a vector of scalars
+fn convert(value: &Value, ty: &Ty, op: &str) -> Result<Value, EvalError> {
+    let component = ty.param_tys().next().unwrap_or(ty).name.as_str();
+    match value {
+        Value::Scalar(s) => convert_scalar(*s, component, op).map(Value::Scalar),
+        Value::Vector(v) => v
+            .iter()
+            .map(|s| convert_scalar(*s, component, op))
+            .collect::<Result<_, _>>()
+            .map(Value::Vector),
+    }
+}
+
+/// evaluates calls of `@const` builtins on concrete values, using the parsed overloads to type check calls
+pub struct Evaluator<'a> {
+    pub overloads: Vec<&'a OverloadRow>,
+    /// the instances of the overloads, the candidates of overload resolution
+    pub instances: Vec<FnDecl>,
+    /// used to convert arguments, e.g. the `AbstractInt` of `sqrt(4)` to `AbstractFloat`
+    pub conversion_rank_rules: &'a [ConversionRankRule],
+}
+
+impl<'a> Evaluator<'a> {
+    pub fn new(
+        overloads: impl IntoIterator<Item = &'a OverloadRow>,
+        conversion_rank_rules: &'a [ConversionRankRule],
+    ) -> Self {
+        let overloads: Vec<_> = overloads.into_iter().filter(|o| o.is_const()).collect();
+        let instances = overloads.iter().flat_map(|o| o.instances()).collect();
+        Evaluator {
+            overloads,
+            instances,
+            conversion_rank_rules,
+        }
+    }
+
+    /// the conversion ranks of the arguments to the parameters of `decl`,
+    /// `None` if an argument cannot be converted
+    fn ranks(&self, decl: &FnDecl, args: &[Ty]) -> Option<Vec<u32>> {
+        if decl.args.len() != args.len() {
+            return None;
+        }
+        let rank = |(param, arg): (&Ty, &Ty)| match param == arg {
+            true => Some(0),
+            false => conversion_rank(self.conversion_rank_rules, arg, param),
+        };
+        let params = decl.args.iter().map(|(_, ty)| ty);
+        params.zip(args).map(rank).collect()
+    }
+
+    /// the instance that is called, like the overload resolution of the spec: the candidate
+    /// whose conversion ranks are all less than or equal to the ones of every other candidate.
+    /// the call is ambiguous if there is no such candidate
+    pub fn resolve(&self, name: &str, args: &[Ty]) -> Result<&FnDecl, EvalError> {
+        let candidates: Vec<(&FnDecl, Vec<u32>)> = self
+            .instances
+            .iter()
+            .filter(|i| i.name.as_str() == name)
+            .filter_map(|i| Some((i, self.ranks(i, args)?)))
+            .collect();
+        let is_better = |a: &[u32], b: &[u32]| a.iter().zip(b).all(|(a, b)| a <= b);
+        let best = candidates
+            .iter()
+            .find(|(_, r)| candidates.iter().all(|(_, other)| is_better(r, other)));
+        let tys = || {
+            let tys: Vec<_> = args.iter().map(ToString::to_string).collect();
+            tys.join(", ")
+        };
+        match best {
+            Some((decl, _)) => Ok(decl),
+            None if candidates.is_empty() => Err(EvalError::NoOverload(name.to_string(), tys())),
+            None => Err(EvalError::Ambiguous(name.to_string(), tys())),
+        }
+    }
+
+    pub fn call(&self, name: &str, args: &[Value]) -> Result<Value, EvalError> {
+        if !self
+            .overloads
+            .iter()
+            .any(|o| o.fn_decl.name.as_str() == name)
+        {
+            return Err(EvalError::NotConst(name.to_string()));
+        }
+        let tys: Vec<Ty> = args.iter().map(Value::ty).collect();
+        let decl = self.resolve(name, &tys)?;
+        let args = args.iter().zip(&decl.args);
+        let args = args.map(|(arg, (_, ty))| convert(arg, ty, name));
+        let args = args.collect::<Result<Vec<_>, _>>()?;
+        eval_builtin(name, &args)
+            .unwrap_or_else(|| Err(EvalError::NotImplemented(name.to_string())))
+    }
+
+    /// the names of all `@const` builtins, sorted
+    pub fn const_builtins(&self) -> Vec<&'a str> {
+        let mut names: Vec<&str> = self
+            .overloads
+            .iter()
+            .map(|o| o.fn_decl.name.as_str())
+            .collect();
+        names.sort();
+        names.dedup();
+        names
+    }
+
+    /// the `@const` builtins that [`eval_builtin`] cannot evaluate
+    pub fn missing(&self) -> Vec<&'a str> {
+        let mut missing = self.const_builtins();
+        missing.retain(|name| !IMPLEMENTED.contains(name));
+        missing
+    }
+}
+
+mod tests {
+    use super::*;
+    use crate::nom_prelude::*;
+
+    #[test]
+    fn test_eval() {
+        let rows = [
+            r#"<tr algorithm="abs">
+    <td>|S| is [=type/abstract|AbstractInt=], [=type/abstract|AbstractFloat=], [=i32=], [=u32=], [=f32=], or [=f16=]<br>
+        |T| is |S|, or vec|N|&lt;|S|&gt;
+    <td><xmp highlight=rust>@const @must_use fn abs(e: T ) -> T</xmp>"#,
+            r#"<tr algorithm="sqrt">
+    <td>|S| is [=type/abstract|AbstractFloat=], [=f32=], or [=f16=]<br>
+        |T| is |S|, or vec|N|&lt;|S|&gt;
+    <td><xmp highlight=rust>@const @must_use fn sqrt(e: T ) -> T</xmp>"#,
+            r#"<tr algorithm="frexp">
+    <td>|T| is [=f32=]
+    <td><xmp highlight=rust>@const @must_use fn frexp(e: T) -> __frexp_result_f32</xmp>"#,
+            r#"<tr algorithm="arrayLength">
+    <td>|T| is [=f32=]
+    <td><xmp highlight=rust>@must_use fn arrayLength(p: T) -> u32</xmp>"#,
+        ];
+        let rows: Vec<OverloadRow> = rows
+            .iter()
+            .map(|s| OverloadRow::parse(s).report(s).unwrap().1)
+            .collect();
+        assert!(rows[0].is_const());
+        assert!(!rows[3].is_const());
+
+        let conversions = ConversionRankRule::extract(include_str!("testdata/conversion_rank.bs"));
+        let eval = Evaluator::new(&rows, &conversions);
+        assert_eq!(eval.const_builtins(), vec!["abs", "frexp", "sqrt"]);
+        assert_eq!(eval.missing(), vec!["frexp"]);
+
+        let v = Value::Vector(vec![Scalar::F32(-1.5), Scalar::F32(2.0)]);
+        assert_eq!(
+            eval.call("abs", &[v]),
+            Ok(Value::Vector(vec![Scalar::F32(1.5), Scalar::F32(2.0)]))
+        );
+        assert_eq!(
+            eval.call("abs", &[Scalar::I32(i32::MIN).into()]),
+            Ok(Scalar::I32(i32::MIN).into())
+        );
+        assert_eq!(
+            eval.call("abs", &[Scalar::AbstractInt(i64::MIN).into()]),
+            Err(EvalError::Overflow("abs".to_string()))
+        );
+        assert_eq!(
+            eval.call("sqrt", &[Scalar::F16(-1.0).into()]),
+            Err(EvalError::Domain("sqrt".to_string()))
+        );
+        assert_eq!(
+            eval.call("sqrt", &[Scalar::AbstractFloat(2.25).into()]),
+            Ok(Scalar::AbstractFloat(1.5).into())
+        );
+        assert!(matches!(
+            eval.call("sqrt", &[Scalar::I32(4).into()]),
+            Err(EvalError::NoOverload(..))
+        ));
+        // AbstractInt converts to AbstractFloat, the candidate with the lowest rank
+        assert_eq!(
+            eval.call("sqrt", &[Scalar::AbstractInt(4).into()]),
+            Ok(Scalar::AbstractFloat(2.0).into())
+        );
+        let v = Value::Vector(vec![Scalar::AbstractInt(9), Scalar::AbstractInt(16)]);
+        assert_eq!(
+            eval.call("sqrt", &[v]),
+            Ok(Value::Vector(vec![
+                Scalar::AbstractFloat(3.0),
+                Scalar::AbstractFloat(4.0)
+            ]))
+        );
+        assert_eq!(
+            eval.call("abs", &[Scalar::AbstractInt(-3).into()]),
+            Ok(Scalar::AbstractInt(3).into())
+        );
+        assert!(matches!(
+            eval.call("arrayLength", &[Scalar::F32(4.0).into()]),
+            Err(EvalError::NotConst(..))
+        ));
+        assert!(matches!(
+            eval.call("frexp", &[Scalar::F32(4.0).into()]),
+            Err(EvalError::NotImplemented(..))
+        ));
+
+        // neither overload converts both arguments with a lower rank
+        let rows = [
+            r#"<tr algorithm="pick a">
+    <td>|T| is [=f32=]
+    <td><xmp highlight=rust>@const fn pick(a: T, b: AbstractFloat) -> T</xmp>"#,
+            r#"<tr algorithm="pick b">
+    <td>|T| is [=f32=]
+    <td><xmp highlight=rust>@const fn pick(a: AbstractFloat, b: T) -> T</xmp>"#,
+        ];
+        let rows: Vec<OverloadRow> = rows
+            .iter()
+            .map(|s| OverloadRow::parse(s).report(s).unwrap().1)
+            .collect();
+        let eval = Evaluator::new(&rows, &conversions);
+        let float = Scalar::AbstractFloat(1.0).into();
+        assert_eq!(
+            eval.call("pick", &[float, Scalar::AbstractFloat(2.0).into()]),
+            Err(EvalError::Ambiguous(
+                "pick".to_string(),
+                "AbstractFloat, AbstractFloat".to_string()
+            ))
+        );
+
+        assert_eq!(round_to_f16(1.0 + 2f64.powi(-11)), 1.0);
+        assert_eq!(round_to_f16(65520.0), f64::INFINITY);
+        assert_eq!(
+            eval_builtin(
+                "extractBits",
+                &[
+                    Scalar::I32(0b1100).into(),
+                    Scalar::U32(2).into(),
+                    Scalar::U32(2).into()
+                ]
+            ),
+            Some(Ok(Scalar::I32(-1).into()))
+        );
+        let bits = |offset: u32, count: u32| {
+            let args = [
+                Scalar::U32(0xff).into(),
+                Scalar::U32(offset).into(),
+                Scalar::U32(count).into(),
+            ];
+            eval_builtin("extractBits", &args)
+        };
+        assert_eq!(bits(28, 4), Some(Ok(Scalar::U32(0).into())));
+        assert_eq!(
+            bits(30, 4),
+            Some(Err(EvalError::Domain("extractBits".to_string())))
+        );
+        let args = [0u32, 1, 16, 17].map(|u| Scalar::U32(u).into());
+        assert_eq!(
+            eval_builtin("insertBits", &args),
+            Some(Err(EvalError::Domain("insertBits".to_string())))
+        );
+        assert_eq!(
+            eval_builtin("firstLeadingBit", &[Scalar::I32(-1).into()]),
+            Some(Ok(Scalar::I32(-1).into()))
+        );
+    }
+}
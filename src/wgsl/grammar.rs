@@ -0,0 +1,180 @@
+use std::collections::HashSet;
+
+use lazy_static::lazy_static;
+use nom::{
+    bytes::complete::take_until, character::complete::one_of, combinator::map_opt, multi::many1,
+};
+use regex::Regex;
+
+use crate::{
+    bikeshed, fn_name,
+    grammar::{GrammarRule, GrammarSymbol},
+    nom_prelude::*,
+    span::*,
+};
+
+/// the rule listing the reserved words
+pub const RESERVED_WORDS_RULE: &str = "_reserved";
+
+/// parses a symbol of a `<div class='syntax'>` block, e.g. `` `'fn'` ``, `[=syntax/ident=]` or `( ... ) *`
+fn parse_symbol(s: &str) -> NomResult<&str, GrammarSymbol> {
+    let literal = delimited(tag("`'"), take_until("'`"), tag("'`"));
+    let literal = map(literal, |l: &str| GrammarSymbol::Literal(l.to_string()));
+    // e.g. `/[0-9]+/` or `/[_\p{XID_Start}][\p{XID_Continue}]*/uy` with flags
+    let pattern = delimited(tag("`/"), take_till(|c: char| c == '`'), tag("`"));
+    let pattern = map_opt(pattern, |p: &str| {
+        let (pattern, _flags) = p.rsplit_once('/')?;
+        Some(GrammarSymbol::Pattern(pattern.to_string()))
+    });
+    // keywords are links to their definition, e.g. `[=syntax_kw/fn=]`
+    let link = delimited(tag("[="), take_until("=]"), tag("=]"));
+    let link = map(link, |l: &str| {
+        let l = l.split('|').next().unwrap_or_default();
+        match l.split_once('/') {
+            Some(("syntax_kw", keyword)) => GrammarSymbol::Literal(keyword.to_string()),
+            Some((_, rule)) => GrammarSymbol::Rule(rule.to_string()),
+            None => GrammarSymbol::Rule(l.to_string()),
+        }
+    });
+    let group = delimited(tag("("), parse_alternatives, ws0_then(tag(")")));
+    let group = map(group, GrammarSymbol::Group);
+    let atom = ws0_then(alt((literal, pattern, link, group)));
+    let parser = pair(atom, many0(ws0_then(one_of("?*+"))));
+    let parser = map(parser, |(symbol, postfixes)| {
+        postfixes
+            .into_iter()
+            .fold(symbol, |s, postfix| match postfix {
+                '?' => GrammarSymbol::Optional(Box::new(s)),
+                '*' => GrammarSymbol::ZeroOrMore(Box::new(s)),
+                _ => GrammarSymbol::OneOrMore(Box::new(s)),
+            })
+    });
+    context(fn_name!(), parser)(s)
+}
+
+/// sequences of symbols separated by `|`, which may also precede the first one
+fn parse_alternatives(s: &str) -> NomResult<&str, Vec<Vec<GrammarSymbol>>> {
+    let parser = preceded(
+        opt(ws0_then(tag("|"))),
+        separated_list1(ws0_then(tag("|")), many1(parse_symbol)),
+    );
+    context(fn_name!(), parser)(s)
+}
+
+/// parses a rule like ``<dfn for=syntax>global_directive</dfn> : | `'enable'` [=syntax/ident=] `';'` </div>``
+fn parse_rule(s: &str) -> NomResult<&str, GrammarRule> {
+    let name = delimited(
+        pair(tag("<dfn"), take_till(|c: char| c == '>')),
+        preceded(tag(">"), take_until("</dfn>")),
+        tag("</dfn>"),
+    );
+    let parser = pair(
+        terminated(name, ws0_then(tag(":"))),
+        terminated(parse_alternatives, ws0_then(tag("</div>"))),
+    );
+    let parser = preceded(ws0, spanned(parser));
+    map(
+        context(fn_name!(), parser),
+        |((name, alternatives), span)| GrammarRule {
+            name: bikeshed::strip_markup(name).trim().to_string(),
+            alternatives,
+            span,
+        },
+    )(s)
+}
+
+/// the rules of all `<div class='syntax'>` blocks. blocks that cannot be parsed are skipped
+pub fn extract_grammar(text: &str) -> Vec<GrammarRule> {
+    lazy_static! {
+        static ref SYNTAX: Regex = Regex::new(r#"<div class=["']?syntax["']?[^>]*>"#).unwrap();
+    }
+    SYNTAX
+        .find_iter(text)
+        .filter_map(|m| parse_rule(&text[m.end()..]).ok().map(|(_, rule)| rule))
+        .collect()
+}
+
+/// the keywords defined as `<dfn for=syntax_kw>`, e.g. `` <dfn for=syntax_kw noexport>`alias`</dfn> ``,
+/// in document order without duplicates
+pub fn extract_keywords(text: &str) -> Vec<String> {
+    lazy_static! {
+        static ref KEYWORD: Regex =
+            Regex::new(r#"<dfn\b[^>]*\bfor=["']?syntax_kw\b[^>]*>(.*?)</dfn>"#).unwrap();
+    }
+    let mut seen = HashSet::new();
+    KEYWORD
+        .captures_iter(text)
+        .map(|c| bikeshed::strip_markup(&c[1]).trim().to_string())
+        .filter(|k| seen.insert(k.clone()))
+        .collect()
+}
+
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_grammar() {
+        let str = r#"
+<div class='syntax' noexport='true'>
+  <dfn for=syntax>global_directive</dfn> :
+
+    | [=syntax/diagnostic_directive=]
+
+    | [=syntax/enable_directive=]
+</div>
+<div class='syntax' noexport='true'>
+  <dfn for=syntax>argument_expression_list</dfn> :
+
+    | `'('` ( [=syntax/expression=] ( `','` [=syntax/expression=] ) * `','` ? ) ? `')'`
+</div>
+<div class='syntax' noexport='true'>
+  <dfn for=syntax>decimal_int_literal</dfn> :
+
+    | `/0[iu]?/`
+
+    | `/[1-9][0-9]*[iu]?/uy`
+</div>
+<div class='syntax' noexport='true'>
+  <dfn for=syntax>_reserved</dfn> :
+
+    | `'NULL'`
+
+    | `'Self'`
+</div>
+<div class='syntax'>not a rule</div>
+
+* <dfn for=syntax_kw noexport>`alias`</dfn>
+* <dfn for="syntax_kw" noexport>`break`</dfn>
+* <dfn for=syntax_kw noexport>`alias`</dfn>
+"#;
+        let rules = extract_grammar(str);
+        let names: Vec<_> = rules.iter().map(|r| r.name.as_str()).collect();
+        assert_eq!(
+            names,
+            [
+                "global_directive",
+                "argument_expression_list",
+                "decimal_int_literal",
+                RESERVED_WORDS_RULE
+            ]
+        );
+        assert_eq!(
+            rules[0].to_string(),
+            "global_directive :\n    | diagnostic_directive\n    | enable_directive\n"
+        );
+        assert_eq!(
+            rules[1].alternatives[0]
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join(" "),
+            "'(' ( expression ( ',' expression ) * ',' ? ) ? ')'"
+        );
+        assert_eq!(
+            rules[2].alternatives[1],
+            [GrammarSymbol::Pattern("[1-9][0-9]*[iu]?".to_string())]
+        );
+        assert_eq!(rules[3].literal_alternatives(), ["NULL", "Self"]);
+        assert_eq!(extract_keywords(str), ["alias", "break"]);
+    }
+}
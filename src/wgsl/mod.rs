@@ -1,42 +1,256 @@
+use crate::diagnostic::{Diagnostic, SpecError};
+use crate::grammar::GrammarRule;
 use crate::nom_prelude::*;
-use std::error::Error;
+use crate::spec::{BuiltinSignature, ShaderLangSpec};
 
-use self::{parametrization::OverloadRow, primitives::FnDecl};
+use self::{
+    accuracy::Accuracy,
+    compare::SignatureComparison,
+    conformance::ConformanceSuite,
+    constructors::Constructor,
+    conversions::{ConversionRankRule, ConversionRanks},
+    coverage::{CoverageReport, UnmatchedAnchors},
+    eval::Evaluator,
+    operators::OperatorRow,
+    parametrization::OverloadRow,
+    predeclared::EnumerantFamily,
+    primitives::{FnDecl, Ty},
+    resolution::{Resolved, Signature},
+    type_rules::{TypeRule, TypeRuleCoverage, TypeRuleTable},
+};
 
-pub mod parametrization;
+#[macro_use]
 pub mod primitives;
+pub mod accuracy;
+pub mod compare;
+pub mod conformance;
+pub mod constructors;
+pub mod conversions;
+pub mod coverage;
+pub mod enumerants;
+pub mod eval;
+pub mod grammar;
+pub mod operators;
+pub mod parametrization;
+pub mod predeclared;
+pub mod resolution;
+pub mod type_rules;
 
 pub struct WgslSpec {
     pub text: String,
     pub fns: Vec<FnDecl>,
     pub overloads: Vec<OverloadRow>,
+    pub operators: Vec<OperatorRow>,
+    pub type_rule_tables: Vec<TypeRuleTable>,
+    pub constructors: Vec<Constructor>,
+    pub conversion_rank_rules: Vec<ConversionRankRule>,
+    pub accuracies: Vec<Accuracy>,
+    /// the rows of the predeclared enumerants table
+    pub enumerant_families: Vec<EnumerantFamily>,
+    /// which `<tr algorithm=...>` rows could be parsed, and why the others could not
+    pub coverage: CoverageReport,
+    /// the rules of the `<div class='syntax'>` blocks
+    pub grammar: Vec<GrammarRule>,
+    pub keywords: Vec<String>,
+    /// the `Date:` of the metadata block, if any
+    pub revision: Option<String>,
 }
 
 impl WgslSpec {
-    pub fn from_download() -> Result<Self, Box<dyn Error>> {
+    pub fn from_download() -> Result<Self, SpecError> {
         Self::from_bs_url("https://raw.githubusercontent.com/gpuweb/gpuweb/main/wgsl/index.bs")
     }
 
-    pub fn from_bs_url(bs_url: &str) -> Result<Self, Box<dyn Error>> {
-        let text = crate::misc::download_text(bs_url)?;
-        let (_, spec) = WgslSpec::parse_bs(&text).map_err(|x| x.report_into_string(&text))?;
+    pub fn from_bs_url(bs_url: &str) -> Result<Self, SpecError> {
+        let text = crate::misc::download_text(bs_url).map_err(|e| SpecError::Download {
+            url: bs_url.to_string(),
+            message: e.to_string(),
+        })?;
+        let text = crate::bikeshed::expand_text_macros(&text);
+        let (_, spec) = WgslSpec::parse_bs(&text).map_err(|e| match e {
+            nom::Err::Error(e) | nom::Err::Failure(e) => {
+                SpecError::Parse(Box::new(Diagnostic::from_nom(&text, &e).with_file(bs_url)))
+            }
+            nom::Err::Incomplete(_) => unreachable!(),
+        })?;
+        spec.check_anchors().map_err(SpecError::Unmatched)?;
         Ok(spec)
     }
 
+    /// the spans of the parsed nodes point into [`Self::text`]
     pub fn parse_bs(i: &str) -> NomResult<&str, Self> {
+        crate::span::with_source(i, || Self::parse_bs_spanned(i))
+    }
+
+    fn parse_bs_spanned(i: &str) -> NomResult<&str, Self> {
         let text = i.to_string();
         let (s, fns) = many0(preceded(take_until_matches(FnDecl::parse), FnDecl::parse))(i)?;
-        let (s, overloads) = many0(preceded(
-            take_until_matches(OverloadRow::parse),
-            OverloadRow::parse,
+        let (s, operators) = many0(preceded(
+            take_until_matches(OperatorRow::parse),
+            OperatorRow::parse,
         ))(i)?;
+        let type_rule_tables = TypeRuleTable::extract(i);
+        let (overloads, coverage) = coverage::parse_rows(i, &type_rule_tables);
+        let constructors = Constructor::extract(&type_rule_tables);
+        let conversion_rank_rules = ConversionRankRule::extract(i);
+        let accuracies = Accuracy::extract(i);
+        let enumerant_families = EnumerantFamily::extract(i);
+        let grammar = grammar::extract_grammar(i);
+        let keywords = grammar::extract_keywords(i);
+        let revision = crate::bikeshed::metadata(i, "Date").map(str::to_string);
         Ok((
             s,
             WgslSpec {
                 text,
                 overloads,
                 fns,
+                operators,
+                type_rule_tables,
+                constructors,
+                conversion_rank_rules,
+                accuracies,
+                enumerant_families,
+                coverage,
+                grammar,
+                keywords,
+                revision,
             },
         ))
     }
+
+    /// fails if a `<tr algorithm=...>` row or a `<xmp highlight=rust>` function declaration
+    /// was skipped by the parser, instead of being parsed or reported in [`Self::coverage`]
+    pub fn check_anchors(&self) -> Result<(), UnmatchedAnchors> {
+        coverage::check_anchors(&self.text, &self.coverage)
+    }
+
+    /// the rules of all "Precondition | Conclusion" tables
+    pub fn type_rules(&self) -> impl Iterator<Item = &TypeRule> {
+        self.type_rule_tables.iter().flat_map(|t| t.rules.iter())
+    }
+
+    /// lists the type rule tables with rows that could not be parsed
+    pub fn type_rule_coverage(&self) -> TypeRuleCoverage<'_> {
+        TypeRuleCoverage(&self.type_rule_tables)
+    }
+
+    /// the rank of the automatic conversion from `src` to `dest`, `None` if there is none
+    pub fn conversion_rank(&self, src: &Ty, dest: &Ty) -> Option<u32> {
+        conversions::conversion_rank(&self.conversion_rank_rules, src, dest)
+    }
+
+    /// conversion ranks between all scalar types
+    pub fn conversion_ranks(&self) -> ConversionRanks {
+        let scalars = conversions::SCALAR_TYPES
+            .iter()
+            .map(|&s| Ty::new(s.into(), vec![]));
+        ConversionRanks::new(&self.conversion_rank_rules, scalars.collect())
+    }
+
+    /// the concrete type an abstract type converts to, e.g. `vec3<AbstractInt>` becomes `vec3<i32>`
+    pub fn concretize(&self, ty: &Ty) -> Ty {
+        conversions::concretize(&self.conversion_rank_rules, ty)
+    }
+
+    /// the accuracy table rows of a builtin function or operator, e.g. `"acos"` or `"/"`
+    pub fn accuracy_of<'a>(&'a self, builtin: &'a str) -> impl Iterator<Item = &'a Accuracy> {
+        self.accuracies.iter().filter(move |a| a.builtin == builtin)
+    }
+
+    /// the predeclared enumerants of the spec which are not modelled by [`enumerants::Enumerant`],
+    /// as `(family, name)`
+    pub fn unknown_enumerants(&self) -> Vec<(&str, &str)> {
+        let families = self.enumerant_families.iter();
+        families
+            .flat_map(|f| f.unknown().into_iter().map(|n| (f.name.as_str(), n)))
+            .collect()
+    }
+
+    /// evaluates calls of the `@const` builtin functions
+    pub fn evaluator(&self) -> Evaluator<'_> {
+        Evaluator::new(&self.overloads, &self.conversion_rank_rules)
+    }
+
+    /// every builtin function overload instantiated with concrete types, e.g. `fn abs(e: vec3<f32>) -> vec3<f32>`
+    pub fn builtin_instances(&self) -> Vec<FnDecl> {
+        self.overloads.iter().flat_map(|o| o.instances()).collect()
+    }
+
+    /// compares the builtin function instances with the signatures of another source, e.g. a compiler's
+    /// builtin table read with [`compare::signatures_from_json`] or [`compare::signatures_from_text`].
+    /// compilers often do not list the overloads for abstract types, so these can be left out
+    pub fn compare_builtins(&self, other: &[FnDecl], abstract_types: bool) -> SignatureComparison {
+        let mut instances = self.builtin_instances();
+        if !abstract_types {
+            instances.retain(|decl| {
+                let mut tys = decl.args.iter().map(|(_, ty)| ty).chain([&decl.out]);
+                !tys.any(conversions::is_abstract)
+            });
+        }
+        SignatureComparison::new(&instances, other)
+    }
+
+    /// test shaders calling every instance of every builtin function overload
+    pub fn conformance_tests(&self) -> ConformanceSuite {
+        ConformanceSuite::generate(&self.overloads)
+    }
+
+    /// builtin function overloads, operators, value constructors and conversions,
+    /// as candidates for overload resolution
+    pub fn signatures(&self) -> Vec<&dyn Signature> {
+        let overloads = self.overloads.iter().map(|o| o as &dyn Signature);
+        let operators = self.operators.iter().map(|o| o as &dyn Signature);
+        let constructors = self.constructors.iter().map(|o| o as &dyn Signature);
+        overloads.chain(operators).chain(constructors).collect()
+    }
+
+    /// finds the builtin function overloads or operators named `name` that accept arguments of the given types
+    pub fn resolve(&self, name: &str, args: &[Ty]) -> Vec<Resolved<'_, dyn Signature + '_>> {
+        resolution::resolve(self.signatures(), name, args)
+    }
+
+    /// like [`Self::resolve`], but the callee may have a template list, e.g. `vec3<f32>`
+    pub fn resolve_callee(
+        &self,
+        callee: &Ty,
+        args: &[Ty],
+    ) -> Vec<Resolved<'_, dyn Signature + '_>> {
+        resolution::resolve_callee(self.signatures(), callee, args)
+    }
+}
+
+impl ShaderLangSpec for WgslSpec {
+    fn language(&self) -> &'static str {
+        "WGSL"
+    }
+
+    fn revision(&self) -> Option<&str> {
+        self.revision.as_deref()
+    }
+
+    fn source_text(&self) -> &str {
+        &self.text
+    }
+
+    fn builtin_functions(&self) -> Vec<BuiltinSignature> {
+        let overloads = self.overloads.iter();
+        overloads
+            .flat_map(|o| o.signatures(Ty::to_string))
+            .collect()
+    }
+
+    fn keywords(&self) -> Vec<&str> {
+        self.keywords.iter().map(String::as_str).collect()
+    }
+
+    fn reserved_words(&self) -> Vec<&str> {
+        let reserved = self.grammar_rule(grammar::RESERVED_WORDS_RULE);
+        reserved
+            .map(GrammarRule::literal_alternatives)
+            .unwrap_or_default()
+    }
+
+    fn grammar(&self) -> &[GrammarRule] {
+        &self.grammar
+    }
 }
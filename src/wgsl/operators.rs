@@ -0,0 +1,172 @@
+use std::fmt::Display;
+
+use nom::combinator::map_opt;
+
+use super::parametrization::*;
+use super::primitives::*;
+use super::type_rules::parse_preconditions;
+use crate::{bikeshed, fn_name, nom_prelude::*, span::*};
+
+/// the expression part of a conclusion like `e1 + e2 : T`, after bikeshed markup was stripped
+pub fn parse_operator_expr(s: &str) -> NomResult<&str, (Operator, Vec<Ident>)> {
+    let unary = map(
+        pair(ws0_then(Operator::parse_unary), ws0_then(Ident::parse)),
+        |(op, e)| (op, vec![e]),
+    );
+    let binary = map(
+        tuple((
+            ws0_then(Ident::parse),
+            ws0_then(Operator::parse_binary),
+            ws0_then(Ident::parse),
+        )),
+        |(e1, op, e2)| (op, vec![e1, e2]),
+    );
+    context(fn_name!(), alt((unary, binary)))(s)
+}
+
+/// a row of one of the operator tables, e.g.
+/// ```text
+/// <tr algorithm="addition">
+///   <td>|e1|: |T|<br>|e2|: |T|<br>|T| is i32 or u32
+///   <td>|e1| + |e2| : |T|
+///   <td>Addition.
+/// ```
+#[derive(Debug, Clone)]
+pub struct OperatorRow {
+    pub algorithm_attr: String,
+    pub operator: Operator,
+    pub operands: Vec<Ty>,
+    pub result: Ty,
+    pub parametrization: Parametrization,
+    pub span: Span,
+}
+
+crate::eq_ignoring_span!(OperatorRow {
+    algorithm_attr,
+    operator,
+    operands,
+    result,
+    parametrization
+});
+
+impl OperatorRow {
+    pub fn parse(s: &str) -> NomResult<&str, Self> {
+        let parse_tr = delimited(
+            tag("<tr algorithm=\""),
+            take_till(|c: char| c == '"'),
+            tag("\">"),
+        );
+        let parse_conclusion = map_opt(bikeshed::cell_content, |cell: &str| {
+            let text = bikeshed::strip_markup(cell);
+            let mut parser = tuple((
+                parse_operator_expr,
+                preceded(ws0_then(tag(":")), ws0_then(Ty::parse)),
+                ws0,
+                eof,
+            ));
+            let res: NomResult<&str, _> = parser(&text);
+            res.ok().map(|(_, (expr, ty, _, _))| (expr, ty))
+        });
+
+        let parser = tuple((
+            map(parse_tr, |s: &str| s.to_string()),
+            ws0_then(preceded(bikeshed::td_open, parse_preconditions)),
+            ws0_then(preceded(bikeshed::td_open, parse_conclusion)),
+        ));
+
+        let parser = map_opt(
+            spanned(parser),
+            |(
+                (algorithm_attr, (judgments, parametrization), ((operator, exprs), result)),
+                span,
+            )| {
+                // every operand must have its type stated in the preconditions
+                let operands = exprs
+                    .iter()
+                    .map(|e| {
+                        judgments
+                            .iter()
+                            .find(|j| &j.expr == e)
+                            .map(|j| j.ty.clone())
+                    })
+                    .collect::<Option<Vec<_>>>()?;
+                Some(OperatorRow {
+                    algorithm_attr,
+                    operator,
+                    operands,
+                    result,
+                    parametrization,
+                    span,
+                })
+            },
+        );
+        context(fn_name!(), parser)(s)
+    }
+}
+
+impl Display for OperatorRow {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "#[{}]", self.algorithm_attr)?;
+        match self.operands.as_slice() {
+            [e] => write!(f, "{}({e})", self.operator)?,
+            [e1, e2] => write!(f, "({e1}) {} ({e2})", self.operator)?,
+            operands => {
+                let operands: Vec<_> = operands.iter().map(ToString::to_string).collect();
+                write!(f, "{}({})", self.operator, operands.join(", "))?
+            }
+        }
+        write!(f, " -> {}", self.result)?;
+        match self.parametrization.is_empty() {
+            true => writeln!(f, ";"),
+            false => writeln!(f, " where\n{};", self.parametrization),
+        }
+    }
+}
+
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_operator_row() {
+        let str = r#"<tr algorithm="addition">
+    <td>|e1|: |T|<br>
+        |e2|: |T|<br>
+        |S| is [=type/abstract|AbstractInt=], [=i32=], or [=u32=]<br>
+        |T| is |S|, or vec|N|&lt;|S|&gt;
+    <td class="nowrap">|e1| + |e2| : |T|
+    <td>Addition. [=Component-wise=] when |T| is a vector."#;
+        let (_, row) = OperatorRow::parse(str).report(str).unwrap();
+        assert_eq!(row.operator, Operator::Add);
+        assert_eq!(row.operands, vec![make_ty!(T), make_ty!(T)]);
+        assert_eq!(row.result, make_ty!(T));
+        assert_eq!(row.parametrization.len(), 2);
+        let (_, row) = with_source(str, || OperatorRow::parse(str)).unwrap();
+        assert_eq!((row.span.line, row.span.column), (1, 1));
+        assert!(row
+            .span
+            .source(str)
+            .trim_end()
+            .ends_with("|e1| + |e2| : |T|"));
+
+        let str = r#"<tr algorithm="logical negation">
+    <td>|e|: |T|<br>
+        |T| is [=bool=] or vec|N|&lt;[=bool=]&gt;
+    <td class="nowrap">`!`|e|`:` |T|
+    <td>Logical negation."#;
+        let (_, row) = OperatorRow::parse(str).report(str).unwrap();
+        assert_eq!(row.operator, Operator::LogicalNot);
+        assert_eq!(row.operands, vec![make_ty!(T)]);
+
+        let str = r#"<tr algorithm="less than">
+    <td>|e1|: |T|<br>|e2|: |T|<br>|T| is [=i32=]
+    <td class="nowrap">|e1| &lt;= |e2| : [=bool=]
+    <td>Less than or equal."#;
+        let (_, row) = OperatorRow::parse(str).report(str).unwrap();
+        assert_eq!(row.operator, Operator::LessEqual);
+        assert_eq!(row.result, make_ty!(bool));
+
+        // operand types must be stated
+        let str = r#"<tr algorithm="negation"><td>|T| is [=i32=]<td>`-`|e|`:` |T|<td>"#;
+        assert!(OperatorRow::parse(str).is_err());
+    }
+}
@@ -2,12 +2,20 @@ use std::fmt::Display;
 
 use derive_deref::Deref;
 use nom::{
-    bytes::complete::{take_until, take_until1},
-    multi::{many1, many_till},
+    bytes::complete::{take, take_till1, take_until, take_until1},
+    combinator::{not, verify},
+    multi::{many1, many1_count, many_till},
 };
 
 use super::primitives::*;
-use crate::{fn_name, misc::normalize_whitespace, nom_prelude::*};
+use super::resolution;
+use crate::{
+    fn_name,
+    misc::normalize_whitespace,
+    nom_prelude::*,
+    span::*,
+    spec::{BuiltinSignature, SignatureType},
+};
 
 pub fn parse_generic_arg(s: &str) -> NomResult<&str, Ident> {
     let ident = || {
@@ -30,6 +38,80 @@ pub fn parse_generic_arg(s: &str) -> NomResult<&str, Ident> {
     )(s)
 }
 
+/// parses a type written in bikeshed markup, e.g. `[=i32=]`, `|T|`,
+/// `[=type/abstract|AbstractInt=]` or `vec|N|&lt;|S|&gt;`.
+/// variables inside of a type name are inlined, so `vec|N|` becomes `vecN`.
+pub fn parse_spec_ty(s: &str) -> NomResult<&str, Ty> {
+    let var = || {
+        alt((
+            delimited(tag("<var ignore>"), identifier, tag("</var>")),
+            delimited(tag("|"), identifier, tag("|")),
+        ))
+    };
+    let name_piece = alt((var(), alphanumeric1, tag("_")));
+    let name = verify(recognize(many1_count(name_piece)), |s: &str| {
+        !s.starts_with(|c: char| c.is_ascii_digit())
+    });
+    let name = map(spanned(name), |(s, span): (&str, _)| {
+        let name = s.replace("<var ignore>", "").replace("</var>", "");
+        Ident::new(&name.replace('|', ""), span)
+    });
+    // a `<` that starts a html tag such as `<br>` does not open a template list
+    let html_tag = alt((tag("br"), tag("td"), tag("tr"), tag("xmp"), tag("/")));
+    let open = ws0_then(alt((tag("&lt;"), terminated(tag("<"), not(html_tag)))));
+    let close = ws0_then(alt((tag(">"), tag("&gt;"))));
+    let ty = map(
+        spanned(pair(
+            name,
+            opt(delimited(
+                open,
+                separated_list1(ws0_then(tag(",")), ws0_then(parse_spec_ty)),
+                close,
+            )),
+        )),
+        |((name, params), span)| Ty {
+            name,
+            params: params
+                .unwrap_or_default()
+                .into_iter()
+                .map(TemplateArg::from_ty)
+                .collect(),
+            span,
+        },
+    );
+    // `[=for/term=]` or `[=term|text=]`, e.g. `[=access/read=]`
+    let autolink = delimited(
+        tag("[="),
+        preceded(
+            pair(
+                opt(pair(take_till(|c: char| "/|=".contains(c)), tag("/"))),
+                opt(pair(take_till(|c: char| c == '|' || c == '='), tag("|"))),
+            ),
+            Ty::parse,
+        ),
+        tag("=]"),
+    );
+    context(
+        fn_name!(),
+        alt((autolink, delimited(tag("`"), Ty::parse, tag("`")), ty)),
+    )(s)
+}
+
+/// succeeds without consuming input if the input is at the end of a bound/line inside a table cell
+pub fn peek_bound_end(s: &str) -> NomResult<&str, ()> {
+    let end = alt((
+        tag("<br>"),
+        tag("<td"),
+        tag("</"),
+        tag("<tr"),
+        tag("\n"),
+        tag("\r"),
+        eof,
+    ));
+    let space = take_while(|c: char| c == ' ' || c == '\t');
+    map(peek(tuple((space, opt(tag(".")), end))), |_| ())(s)
+}
+
 pub fn parse_trait_name(s: &str) -> NomResult<&str, String> {
     context(
         fn_name!(),
@@ -48,7 +130,7 @@ pub fn parse_trait_name(s: &str) -> NomResult<&str, String> {
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct UnionBound {
-    is_one_of: Vec<Ty>,
+    pub is_one_of: Vec<Ty>,
 }
 
 impl Display for UnionBound {
@@ -78,6 +160,7 @@ impl UnionBound {
             ws0_then(alt((
                 context("[=ty=]", delimited(tag("[="), cut(Ty::parse), tag("=]"))),
                 context("`ty`", delimited(tag("`"), Ty::parse, tag("`"))),
+                parse_spec_ty,
             ))),
         );
         map(context(fn_name!(), parser), |is_one_of| UnionBound {
@@ -88,7 +171,7 @@ impl UnionBound {
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct TraitBound {
-    is_a: String,
+    pub is_a: String,
 }
 
 impl Display for TraitBound {
@@ -117,17 +200,27 @@ impl Display for BoundKind {
         match self {
             BoundKind::Union(b) => write!(f, "{b}"),
             BoundKind::Trait(b) => write!(f, "{b}"),
-            BoundKind::Prose(s) => write!(f, "\"{s}\""),
+            // `"` and `\` are escaped with a `\`
+            BoundKind::Prose(s) => {
+                let escaped = s.replace('\\', "\\\\").replace('"', "\\\"");
+                write!(f, "\"{escaped}\"")
+            }
         }
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone)]
 pub struct Bound {
-    type_param: Ident,
-    bound_kind: BoundKind,
+    pub type_param: Ident,
+    pub bound_kind: BoundKind,
+    pub span: Span,
 }
 
+crate::eq_ignoring_span!(Bound {
+    type_param,
+    bound_kind
+});
+
 impl Display for Bound {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{}: {}", self.type_param, self.bound_kind)
@@ -136,7 +229,7 @@ impl Display for Bound {
 
 impl Bound {
     pub fn parse(s: &str) -> NomResult<&str, Self> {
-        let prose = take_until_matches(alt((tag("<br>"), tag("<td>"))));
+        let prose = take_until_matches(alt((tag("<br>"), tag("<td"))));
         let prose = map(prose, |s: &str| {
             BoundKind::Prose(normalize_whitespace(s.trim()))
         });
@@ -153,21 +246,68 @@ impl Bound {
                     is(),
                     preceded(a_an, cut(map(TraitBound::parse, BoundKind::Trait))),
                 ),
-                preceded(is(), map(UnionBound::parse, BoundKind::Union)),
+                preceded(
+                    is(),
+                    map(
+                        terminated(UnionBound::parse, peek_bound_end),
+                        BoundKind::Union,
+                    ),
+                ),
+                prose,
+            ))),
+        );
+        let parser = preceded(ws0, spanned(parser));
+        map(
+            context(fn_name!(), parser),
+            |((type_param, bound_kind), span)| Bound {
+                type_param,
+                bound_kind,
+                span,
+            },
+        )(s)
+    }
+}
+
+impl Bound {
+    /// parses the display format, e.g. `T: is f32 | vec3<f32>`, ``T: is a `texel format` `` or `T: "prose"`
+    pub fn parse_display(s: &str) -> NomResult<&str, Self> {
+        let union = separated_list1(ws0_then(tag("|")), ws0_then(Ty::parse));
+        let union = map(union, |is_one_of| {
+            BoundKind::Union(UnionBound { is_one_of })
+        });
+        let trait_name = delimited(tag("`"), take_till(|c: char| c == '`'), tag("`"));
+        let trait_bound = map(trait_name, |is_a: &str| {
+            BoundKind::Trait(TraitBound {
+                is_a: is_a.to_string(),
+            })
+        });
+        let escaped = preceded(tag("\\"), take(1usize));
+        let prose = many0(alt((escaped, take_till1(|c: char| c == '"' || c == '\\'))));
+        let prose = delimited(tag("\""), prose, tag("\""));
+        let prose = map(prose, |pieces: Vec<&str>| BoundKind::Prose(pieces.concat()));
+        let parser = separated_pair(
+            Ident::parse,
+            ws0_then(tag(":")),
+            ws0_then(alt((
+                preceded(pair(tag("is"), ws1_then(tag("a"))), ws1_then(trait_bound)),
+                preceded(tag("is"), ws1_then(union)),
                 prose,
             ))),
         );
-        map(context(fn_name!(), parser), |(type_param, bound_kind)| {
-            Bound {
+        let parser = preceded(ws0, spanned(parser));
+        map(
+            context(fn_name!(), parser),
+            |((type_param, bound_kind), span)| Bound {
                 type_param,
                 bound_kind,
-            }
-        })(s)
+                span,
+            },
+        )(s)
     }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Deref)]
-pub struct Parametrization(Vec<Bound>);
+pub struct Parametrization(pub Vec<Bound>);
 
 impl Parametrization {
     pub fn parse(s: &str) -> NomResult<&str, Self> {
@@ -177,6 +317,12 @@ impl Parametrization {
         ));
         map(context(fn_name!(), parser), Parametrization)(s)
     }
+
+    /// parses the display format, bounds separated by `,`
+    pub fn parse_display(s: &str) -> NomResult<&str, Self> {
+        let parser = separated_list0(ws0_then(tag(",")), ws0_then(Bound::parse_display));
+        map(context(fn_name!(), parser), Parametrization)(s)
+    }
 }
 
 impl Display for Parametrization {
@@ -189,13 +335,23 @@ impl Display for Parametrization {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone)]
 pub struct OverloadRow {
     pub algorithm_attr: String,
     pub parametrization: Parametrization,
+    /// the attributes in front of the declaration, e.g. `const` and `must_use` for `@const @must_use fn ...`
+    pub attributes: Vec<String>,
     pub fn_decl: FnDecl,
+    pub span: Span,
 }
 
+crate::eq_ignoring_span!(OverloadRow {
+    algorithm_attr,
+    parametrization,
+    attributes,
+    fn_decl
+});
+
 impl OverloadRow {
     pub fn parse(s: &str) -> NomResult<&str, Self> {
         let parse_tr = delimited(
@@ -204,9 +360,10 @@ impl OverloadRow {
             tag("\">"),
         );
         let parse_param = Parametrization::parse;
+        let parse_attr = map(preceded(tag("@"), identifier), ToString::to_string);
         let parse_decl = delimited(
             tag("<xmp highlight=rust>"),
-            ws0_then(FnDecl::parse),
+            pair(many0(ws0_then(parse_attr)), ws0_then(FnDecl::parse)),
             ws0_then(tag("</xmp>")),
         );
 
@@ -216,27 +373,119 @@ impl OverloadRow {
             ws0_then(preceded(tag("<td>"), ws0_then(parse_decl))),
         ));
 
+        map(
+            context(fn_name!(), spanned(parser)),
+            |((algorithm_attr, parametrization, (attributes, fn_decl)), span)| OverloadRow {
+                algorithm_attr,
+                parametrization,
+                attributes,
+                fn_decl,
+                span,
+            },
+        )(s)
+    }
+
+    /// parses the display format, e.g.
+    /// ```text
+    /// #[abs]
+    /// @const @must_use fn abs(
+    ///     e : T
+    /// ) -> T
+    ///  where
+    ///     T: is f32 | vec3<f32>
+    /// ;
+    /// ```
+    pub fn parse_display(s: &str) -> NomResult<&str, Self> {
+        let algorithm_attr = delimited(tag("#["), take_till(|c: char| c == ']'), tag("]"));
+        let attr = map(preceded(tag("@"), identifier), ToString::to_string);
+        let parser = tuple((
+            map(algorithm_attr, ToString::to_string),
+            many0(ws0_then(attr)),
+            FnDecl::parse,
+            preceded(ws0_then(tag("where")), Parametrization::parse_display),
+        ));
+        let parser = terminated(preceded(ws0, spanned(parser)), ws0_then(tag(";")));
         map(
             context(fn_name!(), parser),
-            |(algorithm_attr, parametrization, fn_decl)| OverloadRow {
+            |((algorithm_attr, attributes, fn_decl, parametrization), span)| OverloadRow {
                 algorithm_attr,
                 parametrization,
+                attributes,
                 fn_decl,
+                span,
             },
         )(s)
     }
+
+    /// true for builtins which can be used in const-expressions
+    pub fn is_const(&self) -> bool {
+        self.attributes.iter().any(|a| a == "const")
+    }
+
+    /// the declaration with every concrete binding of its type parameters,
+    /// see [`resolution::instantiations`]
+    pub fn instances(&self) -> Vec<FnDecl> {
+        resolution::instantiations(self)
+            .iter()
+            .map(|subst| subst.apply_fn_decl(&self.fn_decl))
+            .collect()
+    }
+
+    /// the [`Self::instances`] as language independent signatures, with the types written by
+    /// `type_name`. an overload without instances gives its declaration without types
+    pub fn signatures(&self, type_name: impl Fn(&Ty) -> String) -> Vec<BuiltinSignature> {
+        fn collect(ty: &Ty, names: &mut Vec<String>) {
+            names.push(ty.name.to_string());
+            ty.param_tys().for_each(|t| collect(t, names));
+        }
+        let signature_type = |ty: &Ty| {
+            let mut names = vec![];
+            collect(ty, &mut names);
+            SignatureType {
+                text: type_name(ty),
+                names,
+            }
+        };
+        let instances = self.instances();
+        if instances.is_empty() {
+            let decl = &self.fn_decl;
+            return vec![BuiltinSignature {
+                name: decl.name.to_string(),
+                params: decl
+                    .args
+                    .iter()
+                    .map(|(p, _)| (p.to_string(), None))
+                    .collect(),
+                result: None,
+            }];
+        }
+        let signatures = instances.iter().map(|decl| BuiltinSignature {
+            name: decl.name.to_string(),
+            params: (decl.args.iter())
+                .map(|(p, ty)| (p.to_string(), Some(signature_type(ty))))
+                .collect(),
+            result: Some(signature_type(&decl.out)),
+        });
+        signatures.collect()
+    }
 }
 
 impl Display for OverloadRow {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        writeln!(f, "#[{}]", self.algorithm_attr);
+        writeln!(f, "#[{}]", self.algorithm_attr)?;
+        for attr in &self.attributes {
+            write!(f, "@{attr} ")?;
+        }
         write!(f, "{}", self.fn_decl)?;
         writeln!(f, " where\n{};", self.parametrization)
     }
 }
 
+#[cfg(test)]
 mod tests {
     use super::*;
+    use crate::wgsl::enumerants::{AccessMode, AddressSpace, Enumerant, TexelFormat};
+    use proptest::prelude::*;
 
     #[test]
     fn test_overload_row() {
@@ -263,6 +512,132 @@ mod tests {
                             level: f32) -> vec4<f32></xmp>"#;
         OverloadRow::parse(str).report(str);
 
-        OverloadRow::parse(str).report(str);
+        let (_, row) = with_source(str, || OverloadRow::parse(str)).unwrap();
+        assert_eq!((row.span.line, row.span.column), (1, 1));
+        assert_eq!(row.span.source(str), str);
+        assert_eq!((row.fn_decl.span.line, row.fn_decl.span.column), (9, 9));
+        assert_eq!(
+            row.parametrization[1].span.source(str),
+            "<var ignore>X</var> is [=i32=] or [=u32=]"
+        );
+        let coords = &row.fn_decl.args[2].1;
+        assert_eq!(coords.span.source(str), "vec2<f32>");
+        assert_eq!(coords.span.line, 11);
+        // spans are not compared
+        assert_eq!(coords, &Ty::parse("vec2<f32>").unwrap().1);
+        assert!(!Ty::parse("vec2<f32>").unwrap().1.span.is_known());
+    }
+
+    /// identifiers, including the names of enumerants like `read` or `r32uint`
+    fn ident() -> impl Strategy<Value = Ident> {
+        let names = (AccessMode::ALL.iter().map(|e| e.name()))
+            .chain(AddressSpace::ALL.iter().map(|e| e.name()))
+            .chain(TexelFormat::ALL.iter().map(|e| e.name()));
+        let enumerant = prop::sample::select(names.collect::<Vec<_>>()).prop_map(String::from);
+        let name = prop_oneof![3 => "[a-zA-Z_][a-zA-Z0-9_]{0,6}", 1 => enumerant];
+        name.prop_map(|s| s.as_str().into())
+    }
+
+    fn expr() -> impl Strategy<Value = ExprPattern> {
+        let leaf = prop_oneof![
+            "[0-9]{1,3}[iuf]?".prop_map(ExprPattern::Literal),
+            ident().prop_map(ExprPattern::Var),
+        ];
+        leaf.prop_recursive(3, 12, 3, |inner| {
+            let boxed = || inner.clone().prop_map(Box::new);
+            prop_oneof![
+                (prop::sample::select(Operator::UNARY), boxed())
+                    .prop_map(|(op, e)| ExprPattern::Unary(op, e)),
+                (boxed(), prop::sample::select(Operator::BINARY), boxed())
+                    .prop_map(|(e1, op, e2)| ExprPattern::Binary(e1, op, e2)),
+                (ident(), prop::collection::vec(inner.clone(), 0..3))
+                    .prop_map(|(callee, args)| ExprPattern::Call(Ty::new(callee, vec![]), args)),
+                (boxed(), ident()).prop_map(|(e, member)| ExprPattern::Member(e, member)),
+                (boxed(), boxed()).prop_map(|(e, i)| ExprPattern::Index(e, i)),
+            ]
+        })
+    }
+
+    /// a template argument as the parser produces it: a single identifier is a type
+    /// or an enumerant, never an [`ExprPattern::Var`]
+    fn template_arg(ty: impl Strategy<Value = Ty>) -> impl Strategy<Value = TemplateArg> {
+        let expr = expr().prop_filter("identifier", |e| !matches!(e, ExprPattern::Var(_)));
+        prop_oneof![
+            2 => ty.prop_map(TemplateArg::from_ty),
+            1 => expr.prop_map(TemplateArg::Expr),
+        ]
+    }
+
+    fn ty() -> impl Strategy<Value = Ty> {
+        let leaf = ident().prop_map(|name| Ty::new(name, vec![]));
+        leaf.prop_recursive(3, 16, 3, |inner| {
+            (ident(), prop::collection::vec(template_arg(inner), 1..4))
+                .prop_map(|(name, params)| Ty::with_args(name, params))
+        })
+    }
+
+    fn fn_decl() -> impl Strategy<Value = FnDecl> {
+        let args = prop::collection::vec((ident(), ty()), 0..4);
+        (ident(), args, ty()).prop_map(|(name, args, out)| FnDecl {
+            name,
+            args,
+            out,
+            span: Span::default(),
+        })
+    }
+
+    fn bound() -> impl Strategy<Value = Bound> {
+        let bound_kind = prop_oneof![
+            prop::collection::vec(ty(), 1..4)
+                .prop_map(|is_one_of| BoundKind::Union(UnionBound { is_one_of })),
+            "[a-z]{1,8}( [a-z]{1,8}){0,2}".prop_map(|is_a| BoundKind::Trait(TraitBound { is_a })),
+            r#"[a-zA-Z0-9,.|`<>"\\]{1,8}( [a-zA-Z0-9,.|`<>"\\]{1,8}){0,3}"#
+                .prop_map(BoundKind::Prose),
+        ];
+        (ident(), bound_kind).prop_map(|(type_param, bound_kind)| Bound {
+            type_param,
+            bound_kind,
+            span: Span::default(),
+        })
+    }
+
+    fn overload_row() -> impl Strategy<Value = OverloadRow> {
+        (
+            "[a-zA-Z0-9 ]{1,20}",
+            prop::collection::vec(bound(), 0..4),
+            prop::collection::vec("[a-z_]{1,8}", 0..3),
+            fn_decl(),
+        )
+            .prop_map(
+                |(algorithm_attr, bounds, attributes, fn_decl)| OverloadRow {
+                    algorithm_attr,
+                    parametrization: Parametrization(bounds),
+                    attributes,
+                    fn_decl,
+                    span: Span::default(),
+                },
+            )
+    }
+
+    fn parse_all(s: &str) -> NomResult<&str, OverloadRow> {
+        terminated(OverloadRow::parse_display, ws0_then(eof))(s)
+    }
+
+    proptest! {
+        #[test]
+        fn test_display_roundtrip(row in overload_row()) {
+            let text = row.to_string();
+            prop_assert_eq!(&parse_all(&text).unwrap().1, &row, "{}", text);
+
+            let text = row.fn_decl.to_string();
+            prop_assert_eq!(&FnDecl::parse(&text).unwrap().1, &row.fn_decl);
+            for (_, ty) in &row.fn_decl.args {
+                let text = ty.to_string();
+                prop_assert_eq!(Ty::parse(&text).unwrap(), ("", ty.clone()));
+            }
+            let text = row.parametrization.to_string();
+            let parsed = Parametrization::parse_display(&text).unwrap();
+            prop_assert_eq!(parsed.1, row.parametrization.clone());
+        }
     }
 }
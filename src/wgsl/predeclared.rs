@@ -0,0 +1,97 @@
+use super::enumerants::{Enumerant, ENUMERANT_FAMILIES};
+use crate::{bikeshed, misc::normalize_whitespace};
+
+/// a row of the predeclared enumerants table, e.g. "access mode" with `read`, `write` and `read_write`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EnumerantFamily {
+    pub name: String,
+    pub names: Vec<String>,
+}
+
+impl EnumerantFamily {
+    /// finds the rows of the table with a "Predeclared enumerant" column
+    pub fn extract(text: &str) -> Vec<Self> {
+        bikeshed::tables(text)
+            .iter()
+            .filter(|t| t.has_column("enumerant"))
+            .flat_map(|t| t.rows.iter().filter_map(Self::from_row))
+            .collect()
+    }
+
+    fn from_row(row: &bikeshed::TableRow) -> Option<Self> {
+        let [family, names, ..] = row.cells.as_slice() else {
+            return None;
+        };
+        // the family cell may continue with a note
+        let family = bikeshed::strip_markup(family);
+        let name = normalize_whitespace(family.lines().next()?).to_lowercase();
+        // links like `[=access/read=]` keep their prefix
+        let names = bikeshed::strip_markup(names)
+            .lines()
+            .map(|n| n.trim().rsplit('/').next().unwrap_or_default().to_string())
+            .filter(|n| !n.is_empty())
+            .collect();
+        Some(EnumerantFamily { name, names })
+    }
+
+    /// the enumerants of a family modelled by [`Enumerant`]
+    pub fn enumerants(&self) -> impl Iterator<Item = Enumerant> + '_ {
+        let names = self.names.iter();
+        names.filter_map(|n| Enumerant::from_family(&self.name, n))
+    }
+
+    /// the names of a family modelled by [`Enumerant`] which are not known to it,
+    /// e.g. a texel format added to the spec
+    pub fn unknown(&self) -> Vec<&str> {
+        if !ENUMERANT_FAMILIES.contains(&self.name.as_str()) {
+            return vec![];
+        }
+        let names = self.names.iter().map(String::as_str);
+        names
+            .filter(|n| Enumerant::from_family(&self.name, n).is_none())
+            .collect()
+    }
+}
+
+mod tests {
+    use super::*;
+    use crate::wgsl::enumerants::AccessMode;
+
+    #[test]
+    fn test_enumerant_families() {
+        let str = r#"
+<table class='data'>
+  <caption>Predeclared enumerants</caption>
+  <thead>
+    <tr><th>Enumeration<br>(Cannot be spelled in WGSL)<th>Predeclared enumerant
+  </thead>
+  <tr><td>[=access mode=]<td>[=access/read=]<br>[=access/write=]<br>[=access/read_write=]
+  <tr><td>[=address space=]
+      <p class="note">Note: The `handle` address space is never written in a WGSL source.</p>
+      <td>[=address spaces/function=]<br>[=address spaces/private=]<br>[=address spaces/workgroup=]
+          <br>[=address spaces/uniform=]<br>[=address spaces/storage=]
+  <tr><td>[=interpolation type=]<td>[=interpolation type/perspective=]<br>[=interpolation type/flat=]
+  <tr><td>[=texel format=]<td>[=texel format/rgba8unorm=]<br>[=texel format/r8unorm=]
+</table>"#;
+        let families = EnumerantFamily::extract(str);
+        let names: Vec<_> = families.iter().map(|f| f.name.as_str()).collect();
+        assert_eq!(
+            names,
+            [
+                "access mode",
+                "address space",
+                "interpolation type",
+                "texel format"
+            ]
+        );
+        let access_modes: Vec<_> = families[0].enumerants().collect();
+        assert_eq!(access_modes.len(), 3);
+        assert_eq!(
+            access_modes[2],
+            Enumerant::AccessMode(AccessMode::ReadWrite)
+        );
+        assert_eq!(families[1].enumerants().count(), 5);
+        assert!(families[2].unknown().is_empty());
+        assert_eq!(families[3].unknown(), ["r8unorm"]);
+    }
+}
@@ -1,10 +1,22 @@
 use std::fmt::Display;
 
-use crate::nom_prelude::*;
+use nom::combinator::fail;
+
+use super::enumerants::{AccessMode, AddressSpace, Enumerant, TexelFormat};
+use crate::{fn_name, nom_prelude::*, span::*};
 use derive_deref::{Deref, DerefMut};
 
-#[derive(Debug, Clone, PartialEq, Eq)]
-pub struct Ident(String);
+#[derive(Debug, Clone)]
+pub struct Ident(String, Span);
+
+/// the span is ignored, like in [`eq_ignoring_span!`](crate::eq_ignoring_span)
+impl PartialEq for Ident {
+    fn eq(&self, other: &Self) -> bool {
+        self.0 == other.0
+    }
+}
+
+impl Eq for Ident {}
 
 impl Display for Ident {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -28,7 +40,15 @@ impl Ident {
                 many0(alt((alphanumeric1, tag("_")))),
             ))(s)
         };
-        map(parser, |x| Ident(x.to_string()))(s)
+        map(spanned(parser), |(x, span)| Ident(x.to_string(), span))(s)
+    }
+
+    pub fn new(name: &str, span: Span) -> Self {
+        Ident(name.to_string(), span)
+    }
+
+    pub fn span(&self) -> &Span {
+        &self.1
     }
 
     pub fn as_str(&self) -> &str {
@@ -38,36 +58,107 @@ impl Ident {
 
 impl From<&str> for Ident {
     fn from(value: &str) -> Self {
-        Ident(value.to_string())
+        Ident(value.to_string(), Span::default())
     }
 }
 
 macro_rules! make_ty {
     ($name: ident) => {
-        Ty { name: stringify!($name).into(), params: vec![], }
+        Ty::new(stringify!($name).into(), vec![])
     };
     ($name: ident <$($param: ident),*>) => {
-        Ty { name: stringify!($name).into(), params: vec![$(make_ty!($param)),*],}
+        Ty::new(stringify!($name).into(), vec![$(make_ty!($param)),*])
     };
     ($name: ident <$($ty: expr,)*>) => {
-        Ty { name: stringify!($name).into(), params: vec![$($ty),*],}
+        Ty::new(stringify!($name).into(), vec![$($ty),*])
     };
 }
 
+/// an element of a template list, e.g. `f32`, `4` and `read_write` in
+/// `array<f32, 4>` and `ptr<storage, f32, read_write>`
 #[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TemplateArg {
+    Type(Ty),
+    /// a constant expression, e.g. `4` or `N + 1`. a single identifier like `N` is a [`TemplateArg::Type`]
+    Expr(ExprPattern),
+    Enumerant(Enumerant),
+}
+
+impl Display for TemplateArg {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TemplateArg::Type(ty) => write!(f, "{ty}"),
+            // a `>` would close the template list, a `<` could open one
+            TemplateArg::Expr(e) => match e.to_string() {
+                e if e.contains(['<', '>']) => write!(f, "({e})"),
+                e => write!(f, "{e}"),
+            },
+            TemplateArg::Enumerant(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+impl TemplateArg {
+    /// parses a template list element. like the WGSL template list grammar,
+    /// an expression containing `>` must be parenthesized
+    pub fn parse(s: &str) -> NomResult<&str, Self> {
+        let end = peek(ws0_then(alt((tag(","), tag(">")))));
+        let ty = map(terminated(Ty::parse, end), TemplateArg::from_ty);
+        let expr = map(ExprPattern::parse_template_arg, TemplateArg::Expr);
+        context(stringify!(TemplateArg::parse), alt((ty, expr)))(s)
+    }
+
+    /// a type without template list that names an enumerant becomes a [`TemplateArg::Enumerant`],
+    /// a type named like an integer literal (e.g. a size bound to `N`) a [`TemplateArg::Expr`]
+    pub fn from_ty(ty: Ty) -> Self {
+        let is_int = ty.name.starts_with(|c: char| c.is_ascii_digit());
+        match (ty.params.is_empty(), Enumerant::from_name(&ty.name)) {
+            (true, Some(e)) => TemplateArg::Enumerant(e),
+            (true, None) if is_int => TemplateArg::Expr(ExprPattern::Literal(ty.name.to_string())),
+            _ => TemplateArg::Type(ty),
+        }
+    }
+
+    pub fn as_enumerant(&self) -> Option<Enumerant> {
+        match self {
+            TemplateArg::Enumerant(e) => Some(*e),
+            _ => None,
+        }
+    }
+
+    pub fn as_ty(&self) -> Option<&Ty> {
+        match self {
+            TemplateArg::Type(ty) => Some(ty),
+            _ => None,
+        }
+    }
+
+    /// applies `f` to a [`TemplateArg::Type`], other arguments are kept
+    pub fn map_ty(&self, f: impl FnOnce(&Ty) -> Ty) -> Self {
+        match self {
+            TemplateArg::Type(ty) => TemplateArg::Type(f(ty)),
+            arg => arg.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
 pub struct Ty {
     pub name: Ident,
-    pub params: Vec<Ty>,
+    pub params: Vec<TemplateArg>,
+    pub span: Span,
 }
 
+crate::eq_ignoring_span!(Ty { name, params });
+
 impl Display for Ty {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{}", self.name)?;
         if !self.params.is_empty() {
             write!(f, "<")?;
-            for (i, ty) in self.params.iter().enumerate() {
+            for (i, arg) in self.params.iter().enumerate() {
                 let comma = if i + 1 != self.params.len() { ", " } else { "" };
-                write!(f, "{ty}{comma}")?;
+                write!(f, "{arg}{comma}")?;
             }
             write!(f, ">")?;
         }
@@ -76,18 +167,77 @@ impl Display for Ty {
 }
 
 impl Ty {
+    /// a type whose template arguments are all types, e.g. `vec3<f32>`
+    pub fn new(name: Ident, params: Vec<Ty>) -> Self {
+        Self::with_args(name, params.into_iter().map(TemplateArg::Type).collect())
+    }
+
+    pub fn with_args(name: Ident, params: Vec<TemplateArg>) -> Self {
+        Ty {
+            name,
+            params,
+            span: Span::default(),
+        }
+    }
+
+    /// the template arguments which are types
+    pub fn param_tys(&self) -> impl Iterator<Item = &Ty> {
+        self.params.iter().filter_map(TemplateArg::as_ty)
+    }
+
+    fn enumerant_param(&self, i: usize) -> Option<Enumerant> {
+        self.params.get(i).and_then(TemplateArg::as_enumerant)
+    }
+
+    /// the address space of a `ptr<AS, T, AM>` or `ref<AS, T, AM>`
+    pub fn address_space(&self) -> Option<AddressSpace> {
+        match (self.name.as_str(), self.enumerant_param(0)) {
+            ("ptr" | "ref", Some(Enumerant::AddressSpace(space))) => Some(space),
+            _ => None,
+        }
+    }
+
+    /// the access mode of a `ptr` or `ref`, which defaults to the one of its address space,
+    /// or of a `texture_storage_*<F, AM>`
+    pub fn access_mode(&self) -> Option<AccessMode> {
+        let i = match self.name.as_str() {
+            "ptr" | "ref" => 2,
+            name if name.starts_with("texture_storage_") => 1,
+            _ => return None,
+        };
+        match self.enumerant_param(i) {
+            Some(Enumerant::AccessMode(access)) => Some(access),
+            None => self.address_space().map(|s| s.default_access_mode()),
+            Some(_) => None,
+        }
+    }
+
+    /// the texel format of a `texture_storage_*<F, AM>`
+    pub fn texel_format(&self) -> Option<TexelFormat> {
+        match self.enumerant_param(0) {
+            Some(Enumerant::TexelFormat(format)) if self.name.starts_with("texture_storage_") => {
+                Some(format)
+            }
+            _ => None,
+        }
+    }
+
     pub fn parse(s: &str) -> NomResult<&str, Ty> {
         let parser = tuple((
             Ident::parse,
             opt(delimited(
                 ws0_then(tag("<")),
-                ws0_then(separated_list0(ws0_then(tag(",")), ws0_then(Ty::parse))),
+                ws0_then(separated_list0(
+                    ws0_then(tag(",")),
+                    ws0_then(TemplateArg::parse),
+                )),
                 ws0_then(tag(">")),
             )),
         ));
-        map(parser, |(name, params)| Ty {
+        map(spanned(parser), |((name, params), span)| Ty {
             name,
             params: params.unwrap_or_default(),
+            span,
         })(s)
     }
 
@@ -106,18 +256,22 @@ macro_rules! make_fn {
             name: stringify!($name).into(),
             args: vec![$((stringify!($arg).into(), $arg_ty)),*],
             out: $out,
+            span: $crate::span::Span::default(),
         }
     };
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone)]
 pub struct FnDecl {
     // fn foo(a: vec3<f32>, b: vec4<f32>) -> vec3<f32>
     pub name: Ident,
     pub args: Vec<(Ident, Ty)>,
     pub out: Ty,
+    pub span: Span,
 }
 
+crate::eq_ignoring_span!(FnDecl { name, args, out });
+
 impl Display for FnDecl {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         writeln!(f, "fn {}(", self.name)?;
@@ -144,7 +298,7 @@ impl Display for FnDecl {
 impl FnDecl {
     pub fn parse(s: &str) -> NomResult<&str, Self> {
         let parser = tuple((
-            preceded(ws0_then(tag("fn")), ws1_then(Ident::parse)),
+            preceded(tag("fn"), ws1_then(Ident::parse)),
             ws0_then(delimited(
                 ws0_then(tag("(")),
                 ws0_then(separated_list0(
@@ -163,12 +317,336 @@ impl FnDecl {
             ),
         ));
 
-        map(parser, |(name, args, out)| FnDecl { name, args, out })(s)
+        map(
+            preceded(ws0, spanned(parser)),
+            |((name, args, out), span)| FnDecl {
+                name,
+                args,
+                out,
+                span,
+            },
+        )(s)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Operator {
+    // unary
+    Negation,
+    LogicalNot,
+    BitwiseNot,
+    AddressOf,
+    Indirection,
+    // binary
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Remainder,
+    Equal,
+    NotEqual,
+    Less,
+    LessEqual,
+    Greater,
+    GreaterEqual,
+    BitwiseAnd,
+    BitwiseOr,
+    BitwiseXor,
+    ShiftLeft,
+    ShiftRight,
+    ShortCircuitAnd,
+    ShortCircuitOr,
+}
+
+impl Operator {
+    pub const UNARY: &'static [Operator] = &[
+        Operator::Negation,
+        Operator::LogicalNot,
+        Operator::BitwiseNot,
+        Operator::AddressOf,
+        Operator::Indirection,
+    ];
+
+    /// ordered such that no symbol is preceded by one of its prefixes
+    pub const BINARY: &'static [Operator] = &[
+        Operator::ShiftLeft,
+        Operator::ShiftRight,
+        Operator::LessEqual,
+        Operator::GreaterEqual,
+        Operator::Equal,
+        Operator::NotEqual,
+        Operator::ShortCircuitAnd,
+        Operator::ShortCircuitOr,
+        Operator::Add,
+        Operator::Subtract,
+        Operator::Multiply,
+        Operator::Divide,
+        Operator::Remainder,
+        Operator::Less,
+        Operator::Greater,
+        Operator::BitwiseAnd,
+        Operator::BitwiseOr,
+        Operator::BitwiseXor,
+    ];
+
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            Operator::Negation => "-",
+            Operator::LogicalNot => "!",
+            Operator::BitwiseNot => "~",
+            Operator::AddressOf => "&",
+            Operator::Indirection => "*",
+            Operator::Add => "+",
+            Operator::Subtract => "-",
+            Operator::Multiply => "*",
+            Operator::Divide => "/",
+            Operator::Remainder => "%",
+            Operator::Equal => "==",
+            Operator::NotEqual => "!=",
+            Operator::Less => "<",
+            Operator::LessEqual => "<=",
+            Operator::Greater => ">",
+            Operator::GreaterEqual => ">=",
+            Operator::BitwiseAnd => "&",
+            Operator::BitwiseOr => "|",
+            Operator::BitwiseXor => "^",
+            Operator::ShiftLeft => "<<",
+            Operator::ShiftRight => ">>",
+            Operator::ShortCircuitAnd => "&&",
+            Operator::ShortCircuitOr => "||",
+        }
+    }
+
+    pub fn is_unary(&self) -> bool {
+        Self::UNARY.contains(self)
+    }
+
+    /// binding strength of a binary operator, higher binds tighter
+    pub fn precedence(&self) -> u8 {
+        match self {
+            Operator::ShortCircuitOr => 1,
+            Operator::ShortCircuitAnd => 2,
+            Operator::BitwiseOr => 3,
+            Operator::BitwiseXor => 4,
+            Operator::BitwiseAnd => 5,
+            Operator::Equal | Operator::NotEqual => 6,
+            Operator::Less | Operator::LessEqual | Operator::Greater | Operator::GreaterEqual => 7,
+            Operator::ShiftLeft | Operator::ShiftRight => 8,
+            Operator::Add | Operator::Subtract => 9,
+            Operator::Multiply | Operator::Divide | Operator::Remainder => 10,
+            _ => 11,
+        }
+    }
+
+    pub fn parse_unary(s: &str) -> NomResult<&str, Self> {
+        Self::parse_one_of(Self::UNARY, s)
+    }
+
+    pub fn parse_binary(s: &str) -> NomResult<&str, Self> {
+        Self::parse_one_of(Self::BINARY, s)
+    }
+
+    fn parse_one_of<'a>(ops: &[Operator], s: &'a str) -> NomResult<&'a str, Self> {
+        for op in ops {
+            if let Ok((s, _)) = tag::<_, _, NomError<&str>>(op.symbol())(s) {
+                return Ok((s, *op));
+            }
+        }
+        context(fn_name!(), fail)(s)
+    }
+}
+
+impl Display for Operator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.symbol())
+    }
+}
+
+/// the expression of a type rule conclusion, e.g. `e1 + e2`, `e.x`, `e[i]` or `vec3<T>(e1, e2, e3)`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExprPattern {
+    /// a placeholder for an expression, e.g. `e`
+    Var(Ident),
+    /// a literal, e.g. `0`
+    Literal(String),
+    Unary(Operator, Box<ExprPattern>),
+    Binary(Box<ExprPattern>, Operator, Box<ExprPattern>),
+    /// a function, constructor or conversion call, e.g. `f32(e)` or `bitcast<T>(e)`
+    Call(Ty, Vec<ExprPattern>),
+    /// a member or swizzle access, e.g. `e.x`
+    Member(Box<ExprPattern>, Ident),
+    Index(Box<ExprPattern>, Box<ExprPattern>),
+}
+
+impl Display for ExprPattern {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ExprPattern::Var(v) => write!(f, "{v}"),
+            ExprPattern::Literal(l) => write!(f, "{l}"),
+            ExprPattern::Unary(op, e) => match **e {
+                ExprPattern::Binary(..) => write!(f, "{op}({e})"),
+                _ => write!(f, "{op}{e}"),
+            },
+            ExprPattern::Binary(e1, op, e2) => {
+                match e1.precedence() < op.precedence() {
+                    true => write!(f, "({e1})")?,
+                    false => write!(f, "{e1}")?,
+                }
+                write!(f, " {op} ")?;
+                match e2.precedence() <= op.precedence() {
+                    true => write!(f, "({e2})"),
+                    false => write!(f, "{e2}"),
+                }
+            }
+            ExprPattern::Call(callee, args) => {
+                write!(f, "{callee}(")?;
+                for (i, arg) in args.iter().enumerate() {
+                    let comma = if i + 1 != args.len() { ", " } else { "" };
+                    write!(f, "{arg}{comma}")?;
+                }
+                write!(f, ")")
+            }
+            ExprPattern::Member(e, member) if e.needs_parens_before_postfix() => {
+                write!(f, "({e}).{member}")
+            }
+            ExprPattern::Member(e, member) => write!(f, "{e}.{member}"),
+            ExprPattern::Index(e, i) if e.needs_parens_before_postfix() => write!(f, "({e})[{i}]"),
+            ExprPattern::Index(e, i) => write!(f, "{e}[{i}]"),
+        }
+    }
+}
+
+impl ExprPattern {
+    /// parses an expression pattern from text with bikeshed markup already stripped
+    pub fn parse(s: &str) -> NomResult<&str, Self> {
+        context(fn_name!(), |s| Self::parse_binary(s, 0))(s)
+    }
+
+    /// parses an expression in a template list, where an unparenthesized `>` closes the list
+    pub fn parse_template_arg(s: &str) -> NomResult<&str, Self> {
+        context(fn_name!(), |s| Self::parse_binary_until(s, 0, true))(s)
+    }
+
+    /// precedence climbing over binary operators which bind at least as tight as `min_precedence`
+    fn parse_binary(s: &str, min_precedence: u8) -> NomResult<&str, Self> {
+        Self::parse_binary_until(s, min_precedence, false)
+    }
+
+    /// like [`Self::parse_binary`], but stops at operators starting with `>` if `in_template` is set
+    fn parse_binary_until(s: &str, min_precedence: u8, in_template: bool) -> NomResult<&str, Self> {
+        let (mut s, mut lhs) = Self::parse_unary(s)?;
+        while let Ok((rest, op)) = ws0_then(Operator::parse_binary)(s) {
+            if op.precedence() < min_precedence || in_template && op.symbol().starts_with('>') {
+                break;
+            }
+            let rhs = Self::parse_binary_until(rest, op.precedence() + 1, in_template);
+            let Ok((rest, rhs)) = rhs else {
+                break;
+            };
+            lhs = ExprPattern::Binary(Box::new(lhs), op, Box::new(rhs));
+            s = rest;
+        }
+        Ok((s, lhs))
+    }
+
+    /// operators bind weaker than `.` and `[]`, and a literal would take the `.` as a decimal point
+    fn needs_parens_before_postfix(&self) -> bool {
+        matches!(
+            self,
+            ExprPattern::Unary(..) | ExprPattern::Binary(..) | ExprPattern::Literal(_)
+        )
+    }
+
+    fn precedence(&self) -> u8 {
+        match self {
+            ExprPattern::Binary(_, op, _) => op.precedence(),
+            _ => u8::MAX,
+        }
+    }
+
+    fn parse_unary(s: &str) -> NomResult<&str, Self> {
+        let unary = map(
+            pair(ws0_then(Operator::parse_unary), Self::parse_unary),
+            |(op, e)| ExprPattern::Unary(op, Box::new(e)),
+        );
+        alt((unary, Self::parse_postfix))(s)
+    }
+
+    fn parse_postfix(s: &str) -> NomResult<&str, Self> {
+        enum Postfix {
+            Member(Ident),
+            Index(ExprPattern),
+        }
+        let postfix = alt((
+            map(
+                preceded(ws0_then(tag(".")), ws0_then(Ident::parse)),
+                Postfix::Member,
+            ),
+            map(
+                delimited(ws0_then(tag("[")), Self::parse, ws0_then(tag("]"))),
+                Postfix::Index,
+            ),
+        ));
+        let parser = pair(Self::parse_primary, many0(postfix));
+        map(parser, |(e, postfixes)| {
+            postfixes.into_iter().fold(e, |e, postfix| match postfix {
+                Postfix::Member(m) => ExprPattern::Member(Box::new(e), m),
+                Postfix::Index(i) => ExprPattern::Index(Box::new(e), Box::new(i)),
+            })
+        })(s)
+    }
+
+    fn parse_primary(s: &str) -> NomResult<&str, Self> {
+        let call = pair(
+            ws0_then(Ty::parse),
+            delimited(
+                ws0_then(tag("(")),
+                separated_list0(ws0_then(tag(",")), Self::parse),
+                ws0_then(tag(")")),
+            ),
+        );
+        let literal = recognize(pair(digit1, many0_count(alt((alphanumeric1, tag("."))))));
+        alt((
+            delimited(ws0_then(tag("(")), Self::parse, ws0_then(tag(")"))),
+            map(call, |(callee, args)| ExprPattern::Call(callee, args)),
+            map(ws0_then(literal), |l: &str| {
+                ExprPattern::Literal(l.to_string())
+            }),
+            map(ws0_then(Ident::parse), ExprPattern::Var),
+        ))(s)
     }
 }
 
 mod tests {
     use super::*;
+
+    #[test]
+    fn test_expr_pattern() {
+        macro_rules! roundtrip {
+            ($s: literal) => {
+                let (rest, e) = ExprPattern::parse($s).unwrap();
+                assert_eq!((rest, e.to_string().as_str()), ("", $s));
+            };
+        }
+        roundtrip!("e");
+        roundtrip!("-e");
+        roundtrip!("e1 + e2");
+        roundtrip!("e1 << e2");
+        roundtrip!("x * (1.0 - z) + y * z");
+        roundtrip!("a - b - c");
+        roundtrip!("a - (b - c)");
+        roundtrip!("atan2(sqrt(1.0 - x * x), x)");
+        roundtrip!("e.x");
+        roundtrip!("e.rgba");
+        roundtrip!("e[i]");
+        roundtrip!("e[0].x");
+        roundtrip!("vec3<T>(e1, e2, e3)");
+        roundtrip!("bitcast<T>(e)");
+        roundtrip!("&e");
+        roundtrip!("-(a + b)");
+        roundtrip!("*e");
+        roundtrip!("T()");
+    }
     #[test]
     fn test_ident() {
         macro_rules! ok {
@@ -212,6 +690,45 @@ mod tests {
 
         let ty = make_ty!(vec4<make_ty!(vec3<f32>),>);
         assert_eq!(Ty::parse("vec4<vec3<f32>>"), Ok(("", ty)));
+
+        let args = |s| Ty::parse(s).unwrap().1.params;
+        let literal = |l: &str| TemplateArg::Expr(ExprPattern::Literal(l.to_string()));
+        assert_eq!(args("array<f32, 4>")[1], literal("4"));
+        assert_eq!(args("array<f32, 4u>")[1], literal("4u"));
+        assert_eq!(args("array<T, N>")[1], TemplateArg::Type(make_ty!(N)));
+        assert_eq!(
+            args("ptr<storage, array<f32>, read_write>"),
+            [
+                TemplateArg::Enumerant(Enumerant::AddressSpace(AddressSpace::Storage)),
+                TemplateArg::Type(make_ty!(array<f32>)),
+                TemplateArg::Enumerant(Enumerant::AccessMode(AccessMode::ReadWrite))
+            ]
+        );
+        let ty = |s| Ty::parse(s).unwrap().1;
+        let texture = ty("texture_storage_2d<rgba8unorm, write>");
+        assert_eq!(texture.params[0].as_ty(), None);
+        assert_eq!(texture.texel_format(), Some(TexelFormat::Rgba8unorm));
+        assert_eq!(texture.access_mode(), Some(AccessMode::Write));
+        let ptr = ty("ptr<storage, atomic<u32>>");
+        assert_eq!(ptr.address_space(), Some(AddressSpace::Storage));
+        assert_eq!(ptr.access_mode(), Some(AccessMode::Read));
+        assert_eq!(
+            ty("ptr<function, f32>").access_mode(),
+            Some(AccessMode::ReadWrite)
+        );
+        assert_eq!(ty("vec3<f32>").access_mode(), None);
+        for s in [
+            "array<f32, N + 1>",
+            "array<f32, (N > 2)>",
+            "array<vec2<f32>, 2 * (N - 1)>",
+            "ptr<function, array<f32, 4>>",
+        ] {
+            let (rest, ty) = Ty::parse(s).unwrap();
+            assert_eq!((rest, ty.to_string().as_str()), ("", s));
+        }
+        let (rest, ty) = Ty::parse("array<f32, 4> > x").unwrap();
+        assert_eq!((rest, ty.params.len()), (" > x", 2));
+        assert!(matches!(args("array<f32, N + 1>")[1], TemplateArg::Expr(_)));
     }
 
     #[test]
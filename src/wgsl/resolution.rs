@@ -0,0 +1,543 @@
+use std::fmt::Display;
+
+use derive_deref::Deref;
+
+use super::operators::OperatorRow;
+use super::parametrization::*;
+use super::primitives::*;
+
+/// type parameters the spec uses without declaring a bound for them,
+/// e.g. the `N` in `vecN<T>` or the `C` and `R` in `matCxR<T>`
+pub const IMPLICIT_TYPE_PARAMS: &[&str] = &["N", "C", "R"];
+
+/// something that can be called with a list of typed arguments, such as a builtin function overload or an operator
+pub trait Signature {
+    /// the function name, or the operator symbol
+    fn name(&self) -> String;
+    fn parametrization(&self) -> &Parametrization;
+    fn params(&self) -> Vec<&Ty>;
+    fn result(&self) -> &Ty;
+
+    /// what is written in front of the argument list of a call.
+    /// for constructors this may have a template list, e.g. `vecN<T>`
+    fn callee(&self) -> Ty {
+        Ty::new(self.name().as_str().into(), vec![])
+    }
+
+    fn type_params(&self) -> Vec<Ident> {
+        let mut params: Vec<Ident> = IMPLICIT_TYPE_PARAMS.iter().map(|&p| p.into()).collect();
+        for bound in self.parametrization().iter() {
+            if !params.contains(&bound.type_param) {
+                params.push(bound.type_param.clone());
+            }
+        }
+        params
+    }
+}
+
+impl Signature for OverloadRow {
+    fn name(&self) -> String {
+        self.fn_decl.name.to_string()
+    }
+
+    fn parametrization(&self) -> &Parametrization {
+        &self.parametrization
+    }
+
+    fn params(&self) -> Vec<&Ty> {
+        self.fn_decl.args.iter().map(|(_, ty)| ty).collect()
+    }
+
+    fn result(&self) -> &Ty {
+        &self.fn_decl.out
+    }
+}
+
+impl Signature for OperatorRow {
+    fn name(&self) -> String {
+        self.operator.symbol().to_string()
+    }
+
+    fn parametrization(&self) -> &Parametrization {
+        &self.parametrization
+    }
+
+    fn params(&self) -> Vec<&Ty> {
+        self.operands.iter().collect()
+    }
+
+    fn result(&self) -> &Ty {
+        &self.result
+    }
+}
+
+/// assignment of concrete types, sizes or enumerants to type parameters
+#[derive(Debug, Clone, PartialEq, Eq, Default, Deref)]
+pub struct Substitution(Vec<(Ident, TemplateArg)>);
+
+impl Display for Substitution {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (i, (param, arg)) in self.iter().enumerate() {
+            let comma = if i + 1 != self.len() { ", " } else { "" };
+            write!(f, "{param} = {arg}{comma}")?;
+        }
+        Ok(())
+    }
+}
+
+impl Substitution {
+    pub fn get(&self, param: &str) -> Option<&TemplateArg> {
+        self.iter()
+            .find(|(p, _)| p.as_str() == param)
+            .map(|(_, arg)| arg)
+    }
+
+    /// the type bound to `param`, `None` if it is unbound or bound to a size or enumerant
+    pub fn get_ty(&self, param: &str) -> Option<&Ty> {
+        self.get(param).and_then(TemplateArg::as_ty)
+    }
+
+    pub fn insert(&mut self, param: Ident, arg: TemplateArg) {
+        self.0.retain(|(p, _)| p != &param);
+        self.0.push((param, arg));
+    }
+
+    /// replaces the bound type parameters in the argument and return types of `decl`
+    pub fn apply_fn_decl(&self, decl: &FnDecl) -> FnDecl {
+        FnDecl {
+            name: decl.name.clone(),
+            args: decl
+                .args
+                .iter()
+                .map(|(arg, ty)| (arg.clone(), self.apply(ty)))
+                .collect(),
+            out: self.apply(&decl.out),
+            span: decl.span.clone(),
+        }
+    }
+
+    /// replaces all bound type parameters in `ty`, including size parameters inside of names like `vecN`,
+    /// see [`is_size_param_at`]
+    pub fn apply(&self, ty: &Ty) -> Ty {
+        if ty.params.is_empty() {
+            if let Some(bound) = self.get_ty(&ty.name) {
+                return bound.clone();
+            }
+        }
+        let mut name = String::new();
+        let mut rest = ty.name.as_str();
+        'outer: while !rest.is_empty() {
+            for (param, bound) in self.iter() {
+                let TemplateArg::Expr(ExprPattern::Literal(size)) = bound else {
+                    continue;
+                };
+                let at = ty.name.len() - rest.len();
+                if is_size_literal(size) && is_size_param_at(&ty.name, at, param) {
+                    name.push_str(size);
+                    rest = &rest[param.len()..];
+                    continue 'outer;
+                }
+            }
+            let c = rest.chars().next().unwrap();
+            name.push(c);
+            rest = &rest[c.len_utf8()..];
+        }
+        let params = ty.params.iter().map(|p| self.apply_arg(p)).collect();
+        Ty {
+            span: ty.span.clone(),
+            ..Ty::with_args(name.as_str().into(), params)
+        }
+    }
+
+    /// like [`Self::apply`], a type parameter may also be replaced by a size or an enumerant
+    pub fn apply_arg(&self, arg: &TemplateArg) -> TemplateArg {
+        match arg {
+            TemplateArg::Type(ty) if ty.params.is_empty() => match self.get(&ty.name) {
+                Some(bound) => bound.clone(),
+                None => TemplateArg::Type(self.apply(ty)),
+            },
+            arg => arg.map_ty(|ty| self.apply(ty)),
+        }
+    }
+}
+
+/// true for names the spec uses for type variables, e.g. `T`, `S`, `T1` or `AM`
+pub fn is_type_variable_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    let first_upper = chars.next().is_some_and(|c| c.is_ascii_uppercase());
+    let rest_upper = chars.all(|c| c.is_ascii_uppercase() || c.is_ascii_digit());
+    first_upper && rest_upper && name.len() <= 3
+}
+
+fn is_size_literal(s: &str) -> bool {
+    !s.is_empty() && s.chars().all(|c| c.is_ascii_digit())
+}
+
+/// the template argument a size parameter like the `N` of `vecN` is bound to, e.g. `3`
+pub fn size_arg(size: &str) -> TemplateArg {
+    TemplateArg::Expr(ExprPattern::Literal(size.to_string()))
+}
+
+/// matches a type name which may contain size parameters (e.g. `vecN`, `matCxR`)
+/// against a concrete name (e.g. `vec3`, `mat2x4`)
+fn unify_name(
+    pattern: &str,
+    concrete: &str,
+    type_params: &[Ident],
+    subst: &mut Substitution,
+) -> bool {
+    if pattern == concrete {
+        return true;
+    }
+    for param in type_params {
+        if let Some(pattern_rest) = pattern.strip_prefix(param.as_str()) {
+            let digits = concrete.len()
+                - concrete
+                    .trim_start_matches(|c: char| c.is_ascii_digit())
+                    .len();
+            if digits == 0 {
+                continue;
+            }
+            let (size, concrete_rest) = concrete.split_at(digits);
+            match subst.get(param) {
+                Some(bound) if bound != &size_arg(size) => continue,
+                _ => {}
+            }
+            let mut candidate = subst.clone();
+            candidate.insert(param.clone(), size_arg(size));
+            if unify_name(pattern_rest, concrete_rest, type_params, &mut candidate) {
+                *subst = candidate;
+                return true;
+            }
+        }
+    }
+    match (pattern.chars().next(), concrete.chars().next()) {
+        (Some(p), Some(c)) if p == c => unify_name(
+            &pattern[p.len_utf8()..],
+            &concrete[c.len_utf8()..],
+            type_params,
+            subst,
+        ),
+        _ => false,
+    }
+}
+
+/// tries to bind the type parameters in `pattern` such that it becomes equal to `concrete`
+pub fn unify(pattern: &Ty, concrete: &Ty, type_params: &[Ident], subst: &mut Substitution) -> bool {
+    if pattern.params.is_empty() && type_params.contains(&pattern.name) {
+        return unify_arg(
+            &TemplateArg::Type(pattern.clone()),
+            &TemplateArg::Type(concrete.clone()),
+            type_params,
+            subst,
+        );
+    }
+    let mut candidate = subst.clone();
+    let ok = pattern.params.len() == concrete.params.len()
+        && unify_name(&pattern.name, &concrete.name, type_params, &mut candidate)
+        && pattern
+            .params
+            .iter()
+            .zip(&concrete.params)
+            .all(|(p, c)| unify_arg(p, c, type_params, &mut candidate));
+    if ok {
+        *subst = candidate;
+    }
+    ok
+}
+
+/// like [`unify`], a type parameter may also be bound to a size or an enumerant
+pub fn unify_arg(
+    pattern: &TemplateArg,
+    concrete: &TemplateArg,
+    type_params: &[Ident],
+    subst: &mut Substitution,
+) -> bool {
+    match (pattern, concrete) {
+        (TemplateArg::Type(p), c) if p.params.is_empty() && type_params.contains(&p.name) => {
+            match subst.get(&p.name) {
+                Some(bound) => bound == c,
+                None => {
+                    subst.insert(p.name.clone(), c.clone());
+                    true
+                }
+            }
+        }
+        (TemplateArg::Type(p), TemplateArg::Type(c)) => unify(p, c, type_params, subst),
+        (p, c) => p == c,
+    }
+}
+
+/// checks the bounds whose type parameters are already bound, backtracking over the alternatives of union bounds.
+/// bounds that are not expressed as a union of types are assumed to hold.
+pub(super) fn satisfy(
+    bounds: &[&Bound],
+    type_params: &[Ident],
+    subst: &Substitution,
+) -> Option<Substitution> {
+    let Some(i) = bounds
+        .iter()
+        .position(|b| subst.get(&b.type_param).is_some())
+    else {
+        return Some(subst.clone());
+    };
+    let bound = bounds[i];
+    let mut rest = bounds.to_vec();
+    rest.remove(i);
+    match &bound.bound_kind {
+        BoundKind::Union(union) => {
+            let arg = subst.get(&bound.type_param).unwrap().clone();
+            union.is_one_of.iter().find_map(|alternative| {
+                let alternative = TemplateArg::from_ty(alternative.clone());
+                let mut candidate = subst.clone();
+                match unify_arg(&alternative, &arg, type_params, &mut candidate) {
+                    true => satisfy(&rest, type_params, &candidate),
+                    false => None,
+                }
+            })
+        }
+        BoundKind::Trait(_) | BoundKind::Prose(_) => satisfy(&rest, type_params, subst),
+    }
+}
+
+/// a signature that accepts a given list of argument types
+#[derive(Debug, Clone)]
+pub struct Resolved<'a, S: ?Sized> {
+    pub signature: &'a S,
+    pub substitution: Substitution,
+    /// the result type of the call after substitution
+    pub result: Ty,
+}
+
+/// checks if `signature` can be called with arguments of the given types
+pub fn try_call<'a, S: Signature + ?Sized>(
+    signature: &'a S,
+    args: &[Ty],
+) -> Option<Resolved<'a, S>> {
+    instantiate(signature, Substitution::default(), args)
+}
+
+/// checks if `signature` can be called as `callee(args...)`, e.g. `vec3<f32>(...)` or `max(...)`
+pub fn try_call_callee<'a, S: Signature + ?Sized>(
+    signature: &'a S,
+    callee: &Ty,
+    args: &[Ty],
+) -> Option<Resolved<'a, S>> {
+    let mut subst = Substitution::default();
+    match unify(
+        &signature.callee(),
+        callee,
+        &signature.type_params(),
+        &mut subst,
+    ) {
+        true => instantiate(signature, subst, args),
+        false => None,
+    }
+}
+
+fn instantiate<'a, S: Signature + ?Sized>(
+    signature: &'a S,
+    mut subst: Substitution,
+    args: &[Ty],
+) -> Option<Resolved<'a, S>> {
+    let params = signature.params();
+    if params.len() != args.len() {
+        return None;
+    }
+    let type_params = signature.type_params();
+    for (param, arg) in params.iter().zip(args) {
+        if !unify(param, arg, &type_params, &mut subst) {
+            return None;
+        }
+    }
+    let bounds: Vec<&Bound> = signature.parametrization().iter().collect();
+    let substitution = satisfy(&bounds, &type_params, &subst)?;
+    Some(Resolved {
+        signature,
+        result: substitution.apply(signature.result()),
+        substitution,
+    })
+}
+
+/// all `candidates` named `name` that accept arguments of the given types
+pub fn resolve<'a, S: Signature + ?Sized + 'a>(
+    candidates: impl IntoIterator<Item = &'a S>,
+    name: &str,
+    args: &[Ty],
+) -> Vec<Resolved<'a, S>> {
+    let callee = Ty::new(name.into(), vec![]);
+    resolve_callee(candidates, &callee, args)
+}
+
+/// all `candidates` that can be called as `callee(args...)`
+pub fn resolve_callee<'a, S: Signature + ?Sized + 'a>(
+    candidates: impl IntoIterator<Item = &'a S>,
+    callee: &Ty,
+    args: &[Ty],
+) -> Vec<Resolved<'a, S>> {
+    candidates
+        .into_iter()
+        .filter_map(|c| try_call_callee(c, callee, args))
+        .collect()
+}
+
+/// the sizes an implicit size parameter like the `N` of `vecN` is instantiated with
+pub const SIZES: &[&str] = &["2", "3", "4"];
+
+/// true if `name` contains the size parameter `param` at byte `i` like `vecN` or `matCxR` do,
+/// and not as the start of a word like the `C` of `samplerCube`
+fn is_size_param_at(name: &str, i: usize, param: &str) -> bool {
+    let Some(rest) = name[i..].strip_prefix(param) else {
+        return false;
+    };
+    let next = rest.chars().next();
+    !next.is_some_and(|c| c.is_ascii_lowercase() && c != 'x')
+}
+
+fn contains_size_param(name: &str, param: &str) -> bool {
+    (name.match_indices(param)).any(|(i, _)| is_size_param_at(name, i, param))
+}
+
+fn contains_type_param(ty: &Ty, type_params: &[Ident]) -> bool {
+    let is_param = ty.params.is_empty() && type_params.contains(&ty.name);
+    let has_size_param = IMPLICIT_TYPE_PARAMS
+        .iter()
+        .any(|p| contains_size_param(&ty.name, p));
+    is_param || has_size_param || ty.param_tys().any(|p| contains_type_param(p, type_params))
+}
+
+/// applies `subst` to its own bindings until they no longer refer to bound type parameters,
+/// e.g. `T = vecN<S>, S = f32` becomes `T = vecN<f32>, S = f32`
+fn close(subst: &Substitution) -> Substitution {
+    let mut closed = subst.clone();
+    for _ in 0..subst.len() {
+        closed = Substitution(
+            closed
+                .iter()
+                .map(|(p, arg)| (p.clone(), closed.apply_arg(arg)))
+                .collect(),
+        );
+    }
+    closed
+}
+
+fn expand<S: Signature + ?Sized>(
+    signature: &S,
+    bounds: &[&Bound],
+    subst: Substitution,
+    instances: &mut Vec<Substitution>,
+) {
+    let next = bounds.iter().find_map(|b| match &b.bound_kind {
+        BoundKind::Union(union) if subst.get(&b.type_param).is_none() => Some((b, union)),
+        _ => None,
+    });
+    if let Some((bound, union)) = next {
+        for alternative in &union.is_one_of {
+            let mut subst = subst.clone();
+            let alternative = TemplateArg::from_ty(alternative.clone());
+            subst.insert(bound.type_param.clone(), alternative);
+            expand(signature, bounds, subst, instances);
+        }
+        return;
+    }
+    let subst = close(&subst);
+    let mut types: Vec<Ty> = signature
+        .params()
+        .into_iter()
+        .map(|p| subst.apply(p))
+        .collect();
+    types.push(subst.apply(signature.result()));
+
+    let free_size = IMPLICIT_TYPE_PARAMS.iter().find(|&&p| {
+        let mentioned = types.iter().any(|t| contains_size_param(&t.to_string(), p));
+        subst.get(p).is_none() && mentioned
+    });
+    if let Some(&param) = free_size {
+        for &size in SIZES {
+            let mut subst = subst.clone();
+            subst.insert(param.into(), size_arg(size));
+            expand(signature, bounds, subst, instances);
+        }
+        return;
+    }
+    let type_params = signature.type_params();
+    if types.iter().any(|t| contains_type_param(t, &type_params)) {
+        return;
+    }
+    // the alternatives of different bounds may contradict each other
+    if let Some(resolved) = try_call(signature, &types[..types.len() - 1]) {
+        if !instances.contains(&resolved.substitution) {
+            instances.push(resolved.substitution);
+        }
+    }
+}
+
+/// every binding of the type parameters of `signature` to concrete types, expanding the
+/// alternatives of union bounds and the [`SIZES`] of implicit size parameters.
+/// signatures with type parameters that only have a trait or prose bound have no instances.
+pub fn instantiations<S: Signature + ?Sized>(signature: &S) -> Vec<Substitution> {
+    let bounds: Vec<&Bound> = signature.parametrization().iter().collect();
+    let mut instances = vec![];
+    expand(signature, &bounds, Substitution::default(), &mut instances);
+    instances
+}
+
+mod tests {
+    use super::*;
+    use crate::nom_prelude::*;
+    use crate::wgsl::enumerants::*;
+
+    #[test]
+    fn test_resolve() {
+        let str = r#"<tr algorithm="abs">
+    <td>|S| is [=type/abstract|AbstractFloat=], [=f32=], or [=f16=]<br>
+        |T| is |S|, or vec|N|&lt;|S|&gt;
+    <td><xmp highlight=rust>fn abs(e: T ) -> T</xmp>"#;
+        let (_, abs) = OverloadRow::parse(str).report(str).unwrap();
+        let ty = |s| Ty::parse(s).unwrap().1;
+        let size = size_arg;
+
+        let resolved = resolve([&abs], "abs", &[ty("vec3<f32>")]);
+        assert_eq!(resolved.len(), 1);
+        assert_eq!(resolved[0].result, ty("vec3<f32>"));
+        assert_eq!(resolved[0].substitution.get_ty("S"), Some(&ty("f32")));
+        assert_eq!(resolved[0].substitution.get("N"), Some(&size("3")));
+
+        assert_eq!(resolve([&abs], "abs", &[ty("f16")]).len(), 1);
+        assert!(resolve([&abs], "abs", &[ty("vec3<i32>")]).is_empty());
+        assert!(resolve([&abs], "abs", &[ty("f32"), ty("f32")]).is_empty());
+        assert!(resolve([&abs], "max", &[ty("f32")]).is_empty());
+
+        let mut subst = Substitution::default();
+        subst.insert("C".into(), size("2"));
+        subst.insert("R".into(), size("4"));
+        subst.insert("T".into(), TemplateArg::Type(ty("f32")));
+        assert_eq!(subst.apply(&ty("matCxR<T>")), ty("mat2x4<f32>"));
+        assert_eq!(subst.apply(&ty("samplerCube")), ty("samplerCube"));
+        let read_write = Enumerant::AccessMode(AccessMode::ReadWrite);
+        subst.insert("AM".into(), TemplateArg::Enumerant(read_write));
+        assert_eq!(
+            subst.apply(&ty("ptr<storage, array<T, R>, AM>")),
+            ty("ptr<storage, array<f32, 4>, read_write>")
+        );
+
+        let instances: Vec<String> = abs
+            .instances()
+            .iter()
+            .map(|f| f.args[0].1.to_string())
+            .collect();
+        assert_eq!(instances.len(), 12);
+        assert_eq!(
+            instances[..4],
+            [
+                "AbstractFloat",
+                "vec2<AbstractFloat>",
+                "vec3<AbstractFloat>",
+                "vec4<AbstractFloat>"
+            ]
+        );
+        assert!(instances.contains(&"vec4<f16>".to_string()));
+        assert!(contains_size_param("matCxR", "C"));
+        assert!(!contains_size_param("samplerCube", "C"));
+    }
+}
@@ -0,0 +1,344 @@
+use std::fmt::Display;
+
+use nom::combinator::map_opt;
+
+use super::parametrization::*;
+use super::primitives::*;
+use crate::{bikeshed, fn_name, misc::normalize_whitespace, nom_prelude::*, span::*};
+
+/// a precondition of the form `e: T`, stating the type of an operand
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TypeJudgment {
+    pub expr: Ident,
+    pub ty: Ty,
+}
+
+impl Display for TypeJudgment {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.expr, self.ty)
+    }
+}
+
+impl TypeJudgment {
+    pub fn parse(s: &str) -> NomResult<&str, Self> {
+        let parser = terminated(
+            separated_pair(
+                parse_generic_arg,
+                ws0_then(tag(":")),
+                ws0_then(parse_spec_ty),
+            ),
+            peek_bound_end,
+        );
+        map(context(fn_name!(), parser), |(expr, ty)| TypeJudgment {
+            expr,
+            ty,
+        })(s)
+    }
+}
+
+/// a precondition with alternatives, e.g. `|i|: [=i32=] or [=u32=]`
+fn parse_alternatives(s: &str) -> NomResult<&str, ((Ident, UnionBound), Span)> {
+    let parser = separated_pair(
+        parse_generic_arg,
+        ws0_then(tag(":")),
+        ws0_then(UnionBound::parse),
+    );
+    let parser = terminated(parser, peek_bound_end);
+    context(fn_name!(), spanned(parser))(s)
+}
+
+/// true if `name` is used by a judgment type or a bound
+fn is_used(name: &str, judgments: &[TypeJudgment], bounds: &[Bound]) -> bool {
+    fn mentions(ty: &Ty, name: &str) -> bool {
+        ty.name.as_str() == name || ty.param_tys().any(|p| mentions(p, name))
+    }
+    bounds.iter().any(|b| b.type_param.as_str() == name)
+        || judgments.iter().any(|j| mentions(&j.ty, name))
+}
+
+/// the content of a precondition cell, which mixes operand type judgments and type parameter bounds.
+/// an operand with alternative types like `|i|: [=i32=] or [=u32=]` gets a type parameter named
+/// after it, `i: I` with the bound `I: is i32 | u32`
+pub fn parse_preconditions(s: &str) -> NomResult<&str, (Vec<TypeJudgment>, Parametrization)> {
+    enum Item {
+        Judgment(TypeJudgment),
+        Alternatives(Ident, UnionBound, Span),
+        Bound(Bound),
+    }
+    let item = alt((
+        map(TypeJudgment::parse, Item::Judgment),
+        map(parse_alternatives, |((expr, union), span)| {
+            Item::Alternatives(expr, union, span)
+        }),
+        map(Bound::parse, Item::Bound),
+    ));
+    let parser = many0(terminated(ws0_then(item), many0(ws0_then(tag("<br>")))));
+    map(context(fn_name!(), parser), |items| {
+        let mut judgments = vec![];
+        let mut bounds = vec![];
+        let mut alternatives = vec![];
+        for item in items {
+            match item {
+                Item::Judgment(j) => judgments.push(j),
+                Item::Alternatives(expr, mut union, _) if union.is_one_of.len() == 1 => {
+                    let ty = union.is_one_of.remove(0);
+                    judgments.push(TypeJudgment { expr, ty });
+                }
+                Item::Alternatives(expr, union, span) => alternatives.push((expr, union, span)),
+                Item::Bound(b) => bounds.push(b),
+            }
+        }
+        for (expr, union, span) in alternatives {
+            let base = expr.to_uppercase();
+            let mut name = base.clone();
+            for i in 1.. {
+                if !is_used(&name, &judgments, &bounds) {
+                    break;
+                }
+                name = format!("{base}{i}");
+            }
+            let type_param = Ident::from(name.as_str());
+            judgments.push(TypeJudgment {
+                expr,
+                ty: Ty::new(type_param.clone(), vec![]),
+            });
+            bounds.push(Bound {
+                type_param,
+                bound_kind: BoundKind::Union(union),
+                span,
+            });
+        }
+        (judgments, Parametrization(bounds))
+    })(s)
+}
+
+/// a type rule of a "Precondition | Conclusion" table: if the preconditions hold, `expr` has type `ty`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TypeRule {
+    pub algorithm_attr: Option<String>,
+    pub judgments: Vec<TypeJudgment>,
+    pub parametrization: Parametrization,
+    pub expr: ExprPattern,
+    pub ty: Ty,
+    /// the description cell, with markup stripped
+    pub description: String,
+}
+
+impl Display for TypeRule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(attr) = &self.algorithm_attr {
+            writeln!(f, "#[{attr}]")?;
+        }
+        write!(f, "{}: {}", self.expr, self.ty)?;
+        if self.judgments.is_empty() && self.parametrization.is_empty() {
+            return writeln!(f, ";");
+        }
+        writeln!(f, " where")?;
+        for judgment in &self.judgments {
+            writeln!(f, "    {judgment},")?;
+        }
+        writeln!(f, "{};", self.parametrization)
+    }
+}
+
+impl TypeRule {
+    /// parses the conclusions of a conclusion cell, one per line, e.g. `e1 + e2 : T`
+    pub fn parse_conclusions(cell: &str) -> Result<Vec<(ExprPattern, Ty)>, String> {
+        let text = bikeshed::strip_markup(cell);
+        let conclusion = tuple((
+            ExprPattern::parse,
+            preceded(ws0_then(tag(":")), ws0_then(Ty::parse)),
+            ws0,
+            eof,
+        ));
+        let mut conclusion = map(conclusion, |(expr, ty, _, _)| (expr, ty));
+        let conclusions = text
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| {
+                let res: NomResult<&str, _> = conclusion(line);
+                res.map(|(_, c)| c)
+                    .map_err(|_| format!("conclusion `{}`", normalize_whitespace(line)))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        match conclusions.is_empty() {
+            true => Err("empty conclusion".to_string()),
+            false => Ok(conclusions),
+        }
+    }
+
+    /// parses a table row, with one type rule per conclusion
+    pub fn from_row(row: &bikeshed::TableRow) -> Result<Vec<Self>, String> {
+        let [precondition, conclusion, rest @ ..] = row.cells.as_slice() else {
+            return Err(format!(
+                "expected at least 2 cells, found {}",
+                row.cells.len()
+            ));
+        };
+        let (judgments, parametrization) = match parse_preconditions(precondition) {
+            Ok((rest, p)) if rest.trim().is_empty() => p,
+            Ok((rest, _)) => {
+                let rest = normalize_whitespace(rest);
+                return Err(format!("precondition, unexpected `{rest}`"));
+            }
+            Err(_) => return Err("precondition".to_string()),
+        };
+        let description = rest
+            .first()
+            .map(|d| normalize_whitespace(&bikeshed::strip_markup(d)))
+            .unwrap_or_default();
+        let rules = Self::parse_conclusions(conclusion)?
+            .into_iter()
+            .map(|(expr, ty)| TypeRule {
+                algorithm_attr: row.algorithm.map(ToString::to_string),
+                judgments: judgments.clone(),
+                parametrization: parametrization.clone(),
+                expr,
+                ty,
+                description: description.clone(),
+            })
+            .collect();
+        Ok(rules)
+    }
+}
+
+/// a table row that could not be parsed into type rules
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnparsedRow {
+    /// byte offset of the row in the spec text
+    pub offset: usize,
+    pub algorithm_attr: Option<String>,
+    pub reason: String,
+}
+
+/// a table with "Precondition" and "Conclusion" columns
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TypeRuleTable {
+    /// byte offset of the table in the spec text
+    pub offset: usize,
+    pub caption: Option<String>,
+    pub rules: Vec<TypeRule>,
+    pub unparsed: Vec<UnparsedRow>,
+}
+
+impl TypeRuleTable {
+    /// finds all type rule tables in the spec text
+    pub fn extract(text: &str) -> Vec<Self> {
+        bikeshed::tables(text)
+            .iter()
+            .filter(|t| t.has_column("precondition") && t.has_column("conclusion"))
+            .map(Self::from_table)
+            .collect()
+    }
+
+    pub fn from_table(table: &bikeshed::Table) -> Self {
+        let mut rules = vec![];
+        let mut unparsed = vec![];
+        for row in &table.rows {
+            match TypeRule::from_row(row) {
+                Ok(r) => rules.extend(r),
+                Err(reason) => unparsed.push(UnparsedRow {
+                    offset: row.offset,
+                    algorithm_attr: row.algorithm.map(ToString::to_string),
+                    reason,
+                }),
+            }
+        }
+        TypeRuleTable {
+            offset: table.offset,
+            caption: table.caption.clone(),
+            rules,
+            unparsed,
+        }
+    }
+
+    pub fn is_fully_parsed(&self) -> bool {
+        self.unparsed.is_empty()
+    }
+}
+
+/// which type rule tables could (not) be parsed
+pub struct TypeRuleCoverage<'a>(pub &'a [TypeRuleTable]);
+
+impl<'a> TypeRuleCoverage<'a> {
+    pub fn unparsed_tables(&self) -> impl Iterator<Item = &'a TypeRuleTable> {
+        self.0.iter().filter(|t| !t.is_fully_parsed())
+    }
+}
+
+impl Display for TypeRuleCoverage<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let rules: usize = self.0.iter().map(|t| t.rules.len()).sum();
+        let unparsed: usize = self.0.iter().map(|t| t.unparsed.len()).sum();
+        writeln!(
+            f,
+            "{} type rule tables, {rules} rules, {unparsed} unparsed rows",
+            self.0.len()
+        )?;
+        for table in self.unparsed_tables() {
+            let caption = table.caption.as_deref().unwrap_or("<no caption>");
+            writeln!(f, "table \"{caption}\" at byte {}:", table.offset)?;
+            for row in &table.unparsed {
+                let attr = row.algorithm_attr.as_deref().unwrap_or("");
+                writeln!(
+                    f,
+                    "    row \"{attr}\" at byte {}: {}",
+                    row.offset, row.reason
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_type_rule_table() {
+        let str = r#"<table class='data'>
+  <caption>Vector single component selection</caption>
+  <thead><tr><th>Precondition<th>Conclusion<th>Description</thead>
+  <tr algorithm="vector single component">
+    <td>|e|: vec|N|&lt;|T|&gt;<br>
+    <td>|e|`.x`: |T|<br>
+        |e|`.r`: |T|
+    <td>Select the first component of |e|
+  <tr algorithm="vector indexed component">
+    <td>|e|: vec|N|&lt;|T|&gt;<br>|i|: [=i32=] or [=u32=]
+    <td>|e|[|i|]: |T|
+    <td>Select the |i|'th component of vector
+  <tr algorithm="vector broken">
+    <td>|e|: vec|N|&lt;|T|&gt;
+    <td>|e| is a vector
+    <td>
+</table>"#;
+        let tables = TypeRuleTable::extract(str);
+        assert_eq!(tables.len(), 1);
+        let table = &tables[0];
+        assert_eq!(table.rules.len(), 3);
+        assert_eq!(
+            table.rules[0].to_string(),
+            "#[vector single component]\ne.x: T where\n    e: vecN<T>,\n;\n"
+        );
+        assert_eq!(table.rules[1].expr.to_string(), "e.r");
+        assert_eq!(
+            table.rules[0].description,
+            "Select the first component of e"
+        );
+        assert_eq!(
+            table.rules[2].to_string(),
+            "#[vector indexed component]\ne[i]: T where\n    e: vecN<T>,\n    i: I,\n    I: is i32 | u32\n;\n"
+        );
+        assert_eq!(table.unparsed.len(), 1);
+        assert_eq!(
+            table.unparsed[0].algorithm_attr.as_deref(),
+            Some("vector broken")
+        );
+        assert_eq!(table.unparsed[0].reason, "conclusion `e is a vector`");
+
+        let report = TypeRuleCoverage(&tables).to_string();
+        assert!(report.starts_with("1 type rule tables, 3 rules, 1 unparsed rows\n"));
+        assert!(report.contains("table \"Vector single component selection\""));
+    }
+}